@@ -0,0 +1,238 @@
+use aws_sdk_dynamodb::model::{put, AttributeValue, TransactWriteItem};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::client::{INVERTED_INDEX, PK, SK};
+use crate::condition_check::condition_check_not_exists;
+use crate::create::resource_as_item;
+use crate::{
+    begin_transaction, transact_delete, Client, DynarustError, ListOptions, Page, Resource,
+    TransactionContext,
+};
+
+/// Adds the two directions of an adjacency list edge to the transaction context. The edge is
+/// written under its own pk and sk, and mirrored with the keys swapped, so the edges of any
+/// of the two related entities can be listed with a single query. If any of the two directions
+/// already exists, the transaction fails.
+///
+/// # arguments
+///
+/// * `edge` - The edge resource, whose pk and sk are the keys of the two related entities.
+/// * `transaction_context` - The transaction context to which the edge will be added.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_create_edge(
+///         &Enrollment { student: "student#1".into(), course: "course#2".into() },
+///         &mut context
+///     )?;
+///     client.execute_transaction(context).await?;
+/// }
+/// ```
+pub fn transact_create_edge<'a, E: Resource + Serialize>(
+    edge: &'a E,
+    transaction_context: &mut TransactionContext,
+) -> Result<&'a E, DynarustError> {
+    let item = resource_as_item(edge)?;
+    let (source, target) = edge.pk_sk();
+
+    for (pk, sk) in [(source.clone(), target.clone()), (target, source)] {
+        let mut builder = put::Builder::default().table_name(E::table());
+        for (k, v) in item.iter() {
+            builder = builder.item(k, Client::value2attr(v)?)
+        }
+        builder = builder
+            .item(PK, AttributeValue::S(pk))
+            .item(SK, AttributeValue::S(sk));
+        let put = condition_check_not_exists().dump_in_put(builder).build();
+        transaction_context.push(TransactWriteItem::builder().put(put).build())?;
+    }
+
+    Ok(edge)
+}
+
+/// Adds the deletion of the two directions of an adjacency list edge to the transaction context.
+///
+/// # arguments
+///
+/// * `pk_sk` - The pk and sk pair of the edge, in any of its two directions.
+/// * `transaction_context` - The transaction context to which the deletions will be added.
+pub fn transact_delete_edge<E: Resource>(
+    (source, target): (String, String),
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
+    transact_delete::<E>((source.clone(), target.clone()), transaction_context)?;
+    transact_delete::<E>((target, source), transaction_context)
+}
+
+impl Client {
+    /// Creates the two directions of an adjacency list edge in a single transaction.
+    ///
+    /// # arguments
+    ///
+    /// * `edge` - The edge resource, whose pk and sk are the keys of the two related entities.
+    pub async fn create_edge<'a, E: Resource + Serialize>(
+        &self,
+        edge: &'a E,
+    ) -> Result<&'a E, DynarustError> {
+        let mut context = begin_transaction();
+        let edge = transact_create_edge(edge, &mut context)?;
+        self.execute_transaction(context).await?;
+        Ok(edge)
+    }
+
+    /// Deletes the two directions of an adjacency list edge in a single transaction.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - The pk and sk pair of the edge, in any of its two directions.
+    pub async fn delete_edge<E: Resource>(
+        &self,
+        pk_sk: (String, String),
+    ) -> Result<(), DynarustError> {
+        let mut context = begin_transaction();
+        transact_delete_edge::<E>(pk_sk, &mut context)?;
+        self.execute_transaction(context).await
+    }
+
+    /// Lists the edges stored under the provided entity key, querying the base table.
+    ///
+    /// # arguments
+    ///
+    /// * `source` - Key of the entity whose edges will be listed.
+    /// * `options` - optional pagination options.
+    pub async fn list_edges_from<E: Resource + DeserializeOwned>(
+        &self,
+        source: String,
+        options: &ListOptions,
+    ) -> Result<Page<E>, DynarustError> {
+        self.list(source, options).await
+    }
+
+    /// Lists the edges that point to the provided entity key, querying the inverted index
+    /// of the table, which has to be created with `SecondaryIndex::INVERTED` in the
+    /// `Resource::secondary_indexes` of the edges or in the options of `create_table`.
+    /// This works also for edges that were written in a single direction.
+    /// The `from` and `sk_condition` fields of the options are ignored, and strongly
+    /// consistent reads are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `target` - Key of the entity whose incoming edges will be listed.
+    /// * `options` - optional pagination options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let students = client
+    ///         .list_edges_to::<Enrollment>("course#2".into(), &ListOptions::default())
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn list_edges_to<E: Resource + DeserializeOwned>(
+        &self,
+        target: String,
+        options: &ListOptions,
+    ) -> Result<Page<E>, DynarustError> {
+        let mut builder = self
+            .dynamo(&E::table())
+            .query()
+            .table_name(E::table())
+            .index_name(INVERTED_INDEX)
+            .expression_attribute_names("#sk", SK)
+            .expression_attribute_values(":sk", AttributeValue::S(target))
+            .consistent_read(self.index_consistent_read(options)?);
+
+        builder = match self.tenant() {
+            Some(tenant) => builder
+                .key_condition_expression("#sk = :sk and begins_with(#pk, :tenant)")
+                .expression_attribute_names("#pk", PK)
+                .expression_attribute_values(":tenant", AttributeValue::S(tenant.prefix(""))),
+            None => builder.key_condition_expression("#sk = :sk"),
+        };
+
+        self.query_page(builder, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::TABLE;
+    use crate::{Client, ListOptions, Resource, SecondaryIndex};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Enrollment {
+        student: String,
+        course: String,
+    }
+
+    impl Resource for Enrollment {
+        fn table() -> String {
+            format!("{}-edges", *TABLE)
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.student.clone(), self.course.clone())
+        }
+
+        fn secondary_indexes() -> &'static [SecondaryIndex] {
+            &[SecondaryIndex::INVERTED]
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_edges_in_both_directions() {
+        let client = Client::local().await;
+        client.create_table::<Enrollment>(None).await.unwrap();
+
+        let student = "lists_edges_in_both_directions#student".to_string();
+        let courses = [
+            "lists_edges_in_both_directions#course1".to_string(),
+            "lists_edges_in_both_directions#course2".to_string(),
+        ];
+        for course in courses.iter() {
+            let edge = Enrollment {
+                student: student.clone(),
+                course: course.clone(),
+            };
+            client.create_edge(&edge).await.unwrap();
+        }
+
+        let from_student = client
+            .list_edges_from::<Enrollment>(student.clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(from_student.items.len(), 2);
+
+        let from_course = client
+            .list_edges_from::<Enrollment>(courses[0].clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        let to_course = client
+            .list_edges_to::<Enrollment>(courses[0].clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        let expected = Enrollment {
+            student: student.clone(),
+            course: courses[0].clone(),
+        };
+        assert_eq!(from_course.items, vec![expected.clone()]);
+        assert_eq!(to_course.items, vec![expected.clone()]);
+
+        client
+            .delete_edge::<Enrollment>(expected.pk_sk())
+            .await
+            .unwrap();
+        let from_course = client
+            .list_edges_from::<Enrollment>(courses[0].clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert!(from_course.items.is_empty());
+    }
+}
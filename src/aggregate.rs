@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::{update, AttributeValue, TransactWriteItem};
+use serde_json::{Map, Value};
+
+use crate::client::{PK, SK};
+use crate::condition_check::condition_check_exists;
+use crate::{DynarustError, Resource, TransactionContext};
+
+/// Adds to the transaction context an atomic increment of numeric counters on an existing
+/// parent item, like the order count and the total spent of a customer, so that denormalized
+/// aggregates are written in the same transaction as the items they summarize and never drift
+/// from them. Counters missing on the parent are treated as zero. If the parent does not
+/// exist, the whole transaction is canceled.
+///
+/// # arguments
+///
+/// * `pk_sk` - Pk and sk pair for identifying the parent item.
+/// * `deltas` - serde Object with the amount added to each counter, negative amounts
+///   decrement them.
+/// * `transaction_context` - The transaction context to which the operation will be added.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// async {
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_create(&order, &mut context)?;
+///     dynarust::transact_aggregate::<Customer>(
+///         customer.pk_sk(),
+///         json!({ "order_count": 1, "total_spent": order.amount }),
+///         &mut context,
+///     )?;
+///     client.execute_transaction(context).await?;
+/// }
+/// ```
+pub fn transact_aggregate<T: Resource>(
+    (pk, sk): (String, String),
+    deltas: Value,
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
+    let deltas: Map<String, Value> = serde_json::from_value(deltas)?;
+    if deltas.is_empty() {
+        return Ok(());
+    }
+    if let Some(field) = T::immutable_fields()
+        .iter()
+        .find(|field| deltas.contains_key(**field))
+    {
+        return Err(DynarustError::InvalidRequestError(format!(
+            "Cannot update immutable field {field}"
+        )));
+    }
+
+    let mut add = vec![];
+    let mut names = HashMap::new();
+    let mut values = HashMap::new();
+    for (i, (k, v)) in deltas.into_iter().enumerate() {
+        let delta = match v {
+            Value::Number(delta) => delta,
+            _ => {
+                return Err(DynarustError::InvalidRequestError(format!(
+                    "The delta of aggregate {k} is not a number"
+                )))
+            }
+        };
+        let (name, placeholder) = (format!("#aggAttr{i}"), format!(":aggAttr{i}"));
+        add.push(format!("{name} {placeholder}"));
+        names.insert(name, k);
+        values.insert(placeholder, AttributeValue::N(delta.to_string()));
+    }
+
+    let mut builder = update::Builder::default()
+        .table_name(T::table())
+        .key(PK, AttributeValue::S(pk))
+        .key(SK, AttributeValue::S(sk))
+        .update_expression(format!("add {}", add.join(", ")))
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values));
+
+    builder = condition_check_exists().dump_in_update(builder);
+
+    transaction_context.push(TransactWriteItem::builder().update(builder.build()).build())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::{begin_transaction, transact_aggregate, transact_create, Client, Resource};
+
+    #[test]
+    fn rejects_non_numeric_deltas() {
+        let mut context = begin_transaction();
+        let result = transact_aggregate::<TestResource>(
+            ("pk".into(), "sk".into()),
+            json!({ "int": "one" }),
+            &mut context,
+        );
+        assert!(result.is_err());
+
+        transact_aggregate::<TestResource>(
+            ("pk".into(), "sk".into()),
+            json!({ "int": 1, "float": -2.5 }),
+            &mut context,
+        )
+        .unwrap();
+        let update = context.into_items().remove(0).update.unwrap();
+        assert_eq!(
+            update.update_expression.unwrap(),
+            "add #aggAttr0 :aggAttr0, #aggAttr1 :aggAttr1"
+        );
+    }
+
+    #[tokio::test]
+    async fn maintains_aggregates_in_transactions() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let parent = TestResource {
+            pk: "maintains_aggregates_in_transactions".to_string(),
+            sk: "parent".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        client.force_create(&parent).await.unwrap();
+
+        for i in 0..2 {
+            let child = TestResource {
+                pk: parent.pk.clone(),
+                sk: format!("child{i}"),
+                ..Default::default()
+            };
+            let mut context = begin_transaction();
+            transact_create(&child, &mut context).unwrap();
+            transact_aggregate::<TestResource>(parent.pk_sk(), json!({ "int": 2 }), &mut context)
+                .unwrap();
+            client.execute_transaction(context).await.unwrap();
+        }
+
+        let stored = client.get::<TestResource>(parent.pk_sk()).await.unwrap();
+        assert_eq!(stored.unwrap().int, 5);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use futures::future::try_join_all;
+
+use crate::client::PK;
+use crate::{Client, DynarustError, Resource};
+
+/// Maximum size that all the items sharing the same partition key can have in a table with
+/// local secondary indexes.
+pub const ITEM_COLLECTION_SIZE_LIMIT: u64 = 10 * 1024 * 1024 * 1024;
+
+/// options for analyzing the partition key distribution of a table.
+#[derive(Debug, Clone)]
+pub struct AnalyzeOptions {
+    /// number of segments in which the table is scanned in parallel, default is 4.
+    pub segments: i32,
+    /// maximum number of items read by each segment, so at most `segments * sample_limit` items
+    /// are read from the table. It must be greater than 0. If not provided the whole table is
+    /// scanned.
+    pub sample_limit: Option<usize>,
+    /// fraction of the item collection size limit from which a partition key gets flagged,
+    /// default is 0.8.
+    pub warn_ratio: f64,
+}
+
+impl Default for AnalyzeOptions {
+    fn default() -> Self {
+        Self {
+            segments: 4,
+            sample_limit: None,
+            warn_ratio: 0.8,
+        }
+    }
+}
+
+/// Item count and cumulative size of all the items under the same partition key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionStats {
+    /// The partition key.
+    pub pk: String,
+    /// Number of items found under this partition key.
+    pub item_count: u64,
+    /// Estimated cumulative size in bytes of the items found under this partition key.
+    pub size_bytes: u64,
+    /// whether the cumulative size is approaching the item collection size limit.
+    pub near_limit: bool,
+}
+
+/// Result of analyzing the partition key distribution of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionReport {
+    /// Stats per partition key, sorted by size in descending order.
+    pub partitions: Vec<PartitionStats>,
+    /// Total number of items read from the table.
+    pub scanned_items: u64,
+    /// whether the scan stopped before reading the whole table because of `sample_limit`.
+    pub sampled: bool,
+}
+
+impl PartitionReport {
+    /// Partitions that are approaching the item collection size limit.
+    pub fn flagged(&self) -> impl Iterator<Item = &PartitionStats> {
+        self.partitions.iter().filter(|p| p.near_limit)
+    }
+}
+
+fn value_size(attr: &AttributeValue) -> u64 {
+    match attr {
+        AttributeValue::S(s) => s.len() as u64,
+        AttributeValue::N(n) => {
+            let digits = n.chars().filter(char::is_ascii_digit).count() as u64;
+            digits / 2 + digits % 2 + 1
+        }
+        AttributeValue::B(b) => b.as_ref().len() as u64,
+        AttributeValue::Bool(_) | AttributeValue::Null(_) => 1,
+        AttributeValue::Ss(set) => set.iter().map(|s| s.len() as u64).sum(),
+        AttributeValue::Ns(set) => set
+            .iter()
+            .map(|n| value_size(&AttributeValue::N(n.clone())))
+            .sum(),
+        AttributeValue::Bs(set) => set.iter().map(|b| b.as_ref().len() as u64).sum(),
+        AttributeValue::L(list) => 3 + list.iter().map(|e| 1 + value_size(e)).sum::<u64>(),
+        AttributeValue::M(map) => {
+            3 + map
+                .iter()
+                .map(|(k, v)| 1 + k.len() as u64 + value_size(v))
+                .sum::<u64>()
+        }
+        _ => 0,
+    }
+}
+
+/// Estimates the size in bytes that DynamoDB accounts for an item.
+pub(crate) fn item_size(item: &HashMap<String, AttributeValue>) -> u64 {
+    item.iter()
+        .map(|(k, v)| k.len() as u64 + value_size(v))
+        .sum()
+}
+
+/// Samples the table of resource T with a parallel scan and reports item counts and
+/// cumulative sizes per partition key, flagging the ones approaching the item collection
+/// size limit. If the client is scoped to a tenant, only the partitions of that tenant are
+/// reported.
+///
+/// # arguments
+///
+/// * `client` - The client used for scanning the table.
+/// * `options` - optional analyze options.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let report = dynarust::analyze::<Car>(&client, None).await?;
+///     for partition in report.flagged() {
+///         println!("{} is {} bytes big", partition.pk, partition.size_bytes)
+///     }
+/// }
+/// ```
+pub async fn analyze<T: Resource>(
+    client: &Client,
+    options: Option<AnalyzeOptions>,
+) -> Result<PartitionReport, DynarustError> {
+    let options = options.unwrap_or_default();
+    if options.sample_limit == Some(0) {
+        return Err(DynarustError::InvalidRequestError(
+            "The sample limit must be greater than 0".into(),
+        ));
+    }
+    let segments = options.segments.max(1);
+    let table = T::table();
+
+    let results = try_join_all((0..segments).map(|segment| {
+        let table = &table;
+        async move {
+            let mut stats: HashMap<String, (u64, u64)> = HashMap::new();
+            let read = client
+                .scan_segment(table, segment, segments, options.sample_limit, |items| {
+                    for item in items {
+                        let pk = match (item.get(PK), client.tenant()) {
+                            (Some(AttributeValue::S(pk)), Some(tenant)) => tenant.strip(pk),
+                            (Some(AttributeValue::S(pk)), None) => Some(pk.as_str()),
+                            _ => None,
+                        };
+                        if let Some(pk) = pk {
+                            let entry = stats.entry(pk.to_string()).or_default();
+                            entry.0 += 1;
+                            entry.1 += item_size(item);
+                        }
+                    }
+                    Ok(())
+                })
+                .await?;
+            Ok::<_, DynarustError>((read, stats))
+        }
+    }))
+    .await?;
+
+    let mut scanned_items = 0;
+    let mut sampled = false;
+    let mut merged: HashMap<String, (u64, u64)> = HashMap::new();
+    for (read, stats) in results {
+        scanned_items += read as u64;
+        sampled |= options.sample_limit.is_some_and(|limit| read >= limit);
+        for (pk, (count, size)) in stats {
+            let entry = merged.entry(pk).or_default();
+            entry.0 += count;
+            entry.1 += size;
+        }
+    }
+
+    let threshold = (ITEM_COLLECTION_SIZE_LIMIT as f64 * options.warn_ratio) as u64;
+    let mut partitions: Vec<PartitionStats> = merged
+        .into_iter()
+        .map(|(pk, (item_count, size_bytes))| PartitionStats {
+            pk,
+            item_count,
+            size_bytes,
+            near_limit: size_bytes >= threshold,
+        })
+        .collect();
+    partitions.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then(a.pk.cmp(&b.pk)));
+
+    Ok(PartitionReport {
+        partitions,
+        scanned_items,
+        sampled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::model::AttributeValue;
+
+    use crate::analyze::{analyze, item_size, AnalyzeOptions};
+    use crate::client::tests::TestResource;
+    use crate::Client;
+
+    #[test]
+    fn estimates_item_size() {
+        let item = HashMap::from([
+            ("a".to_string(), AttributeValue::S("foo".into())),
+            ("bb".to_string(), AttributeValue::N("123".into())),
+            ("c".to_string(), AttributeValue::Bool(true)),
+            (
+                "d".to_string(),
+                AttributeValue::L(vec![AttributeValue::S("x".into())]),
+            ),
+        ]);
+        // a + foo, bb + 3 digits, c + bool, d + list overhead + element.
+        assert_eq!(item_size(&item), 4 + 5 + 2 + 6)
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_samples() {
+        let client = Client::local_on_port(12345).await;
+        let options = AnalyzeOptions {
+            sample_limit: Some(0),
+            ..Default::default()
+        };
+        let err = analyze::<TestResource>(&client, Some(options))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid request: The sample limit must be greater than 0"
+        );
+    }
+
+    #[tokio::test]
+    async fn analyzes_partition_distribution() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        for i in 0..3 {
+            let resource = TestResource {
+                pk: "analyzes_partition_distribution".to_string(),
+                sk: i.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let report = analyze::<TestResource>(
+            &client,
+            Some(AnalyzeOptions {
+                segments: 2,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stats = report
+            .partitions
+            .iter()
+            .find(|p| p.pk == "analyzes_partition_distribution")
+            .unwrap();
+        assert_eq!(stats.item_count, 3);
+        assert!(stats.size_bytes > 0);
+        assert!(!stats.near_limit);
+        assert!(!report.sampled);
+    }
+}
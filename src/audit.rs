@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::client::{strip_key_attributes, PK, SK};
+use crate::condition_check::ConditionCheckInfo;
+use crate::create::put_item;
+use crate::transaction::describe_item;
+use crate::{Client, DynarustError};
+
+/// The kind of mutation recorded in an audit record.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Put,
+    Update,
+    Delete,
+}
+
+/// A mutation performed through an audited client. The images are the item without its key
+/// attributes, and they are only present when dynamo returns them: single item puts and
+/// unconditional deletes carry the previous item, updates carry the new one and transactional
+/// puts carry the written item. Conditional deletes run as single item transactions, which do
+/// not return the deleted item, and batched deletes like `delete_where` cannot return it
+/// either, so both are recorded without images.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    pub actor: Option<String>,
+    pub operation: AuditOperation,
+    pub table: String,
+    pub pk: String,
+    pub sk: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Destination of the audit records of a client.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Records a mutation that has already been applied. A failure is returned to the caller
+    /// of the mutation, which is not rolled back.
+    async fn record(&self, record: AuditRecord) -> Result<(), DynarustError>;
+}
+
+/// Prints every record to stdout as a line of json.
+#[derive(Debug, Clone, Default)]
+pub struct StdoutSink;
+
+#[async_trait]
+impl AuditSink for StdoutSink {
+    async fn record(&self, record: AuditRecord) -> Result<(), DynarustError> {
+        println!("{}", serde_json::to_string(&record)?);
+        Ok(())
+    }
+}
+
+/// Hands every record to a user provided function.
+pub struct CallbackSink<F: Fn(AuditRecord) + Send + Sync>(pub F);
+
+#[async_trait]
+impl<F: Fn(AuditRecord) + Send + Sync> AuditSink for CallbackSink<F> {
+    async fn record(&self, record: AuditRecord) -> Result<(), DynarustError> {
+        (self.0)(record);
+        Ok(())
+    }
+}
+
+/// Writes every record as an item of another dynamo table, under the PrimaryKey
+/// `{table}#{pk}#{sk}` of the audited item and the timestamp of the mutation as SecondaryKey.
+/// The records are written with the provided client, which is not audited.
+#[derive(Clone)]
+pub struct TableSink {
+    client: Client,
+    table: String,
+}
+
+impl TableSink {
+    pub fn new(client: Client, table: impl Into<String>) -> Self {
+        Self {
+            client: Client {
+                audit: None,
+                ..client
+            },
+            table: table.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for TableSink {
+    async fn record(&self, record: AuditRecord) -> Result<(), DynarustError> {
+        let key = (
+            format!("{}#{}#{}", record.table, record.pk, record.sk),
+            record.timestamp.to_rfc3339(),
+        );
+        let item = match serde_json::to_value(record)? {
+            Value::Object(object) => object,
+            _ => Map::new(),
+        };
+        put_item(
+            &self.client,
+            &self.table,
+            key,
+            item,
+            ConditionCheckInfo::default(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Converts the attributes returned by a single item operation into an audit image.
+pub(crate) fn audit_image(
+    client: &Client,
+    attributes: Option<&HashMap<String, AttributeValue>>,
+) -> Result<Option<Value>, DynarustError> {
+    match attributes {
+        Some(attributes) => {
+            let mut value = client.decode_item(attributes)?;
+            if let Some(object) = value.as_object_mut() {
+                strip_key_attributes(object);
+            }
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+impl Client {
+    /// Returns a client that records every create, update and delete performed through it,
+    /// including the ones inside transactions, to the provided sink.
+    ///
+    /// # arguments
+    ///
+    /// * `sink` - The destination of the audit records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let client = dynarust::Client::aws()
+    ///         .await
+    ///         .with_audit(dynarust::StdoutSink)
+    ///         .with_actor("billing-service");
+    ///     client.create(&car).await?;
+    /// }
+    /// ```
+    pub fn with_audit(&self, sink: impl AuditSink + 'static) -> Client {
+        Client {
+            audit: Some(Arc::new(sink)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client whose audit records are attributed to the provided actor.
+    pub fn with_actor(&self, actor: impl Into<String>) -> Client {
+        Client {
+            actor: Some(actor.into()),
+            ..self.clone()
+        }
+    }
+
+    fn audit_record(
+        &self,
+        operation: AuditOperation,
+        table: &str,
+        (pk, sk): (String, String),
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> AuditRecord {
+        AuditRecord {
+            actor: self.actor.clone(),
+            operation,
+            table: table.to_string(),
+            pk,
+            sk,
+            before,
+            after,
+            timestamp: self.now(),
+        }
+    }
+
+    /// Sends a record to the audit sink, if there is one.
+    pub(crate) async fn audit(
+        &self,
+        operation: AuditOperation,
+        table: &str,
+        key: (String, String),
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Result<(), DynarustError> {
+        match &self.audit {
+            Some(sink) => {
+                let record = self.audit_record(operation, table, key, before, after);
+                sink.record(record).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Builds the audit records of the mutations of a transaction, before their keys are
+    /// scoped to the tenant of the client.
+    pub(crate) fn transaction_audit_records(
+        &self,
+        items: &[TransactWriteItem],
+    ) -> Result<Vec<AuditRecord>, DynarustError> {
+        if self.audit.is_none() {
+            return Ok(vec![]);
+        }
+        let mut records = vec![];
+        for item in items {
+            let (operation, table, key) = describe_item(item)?;
+            let operation = match operation {
+                "put" => AuditOperation::Put,
+                "update" => AuditOperation::Update,
+                "delete" => AuditOperation::Delete,
+                _ => continue,
+            };
+            let attr = |name: &str| match key.as_ref().and_then(|key| key.get(name)) {
+                Some(AttributeValue::S(v)) => v.clone(),
+                _ => String::new(),
+            };
+            let after = match operation {
+                AuditOperation::Put => audit_image(self, key.as_ref())?,
+                _ => None,
+            };
+            records.push(self.audit_record(operation, table, (attr(PK), attr(SK)), None, after));
+        }
+        Ok(records)
+    }
+
+    /// Sends the records of an executed transaction to the audit sink, if there is one.
+    pub(crate) async fn audit_all(&self, records: Vec<AuditRecord>) -> Result<(), DynarustError> {
+        if let Some(sink) = &self.audit {
+            for record in records {
+                sink.record(record).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{
+        begin_transaction, transact_create, transact_delete, AuditOperation, AuditRecord,
+        CallbackSink, Client, ConditionCheckInfo, Resource, SkCondition,
+    };
+
+    async fn audited_client(records: &Arc<Mutex<Vec<AuditRecord>>>) -> Client {
+        let records = records.clone();
+        Client::local()
+            .await
+            .with_audit(CallbackSink(move |record| {
+                records.lock().unwrap().push(record)
+            }))
+            .with_actor("tester")
+    }
+
+    #[tokio::test]
+    async fn describes_transaction_mutations() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let client = audited_client(&records).await;
+        let resource = TestResource {
+            pk: "describes_transaction_mutations".to_string(),
+            sk: "1".to_string(),
+            int: 3,
+            ..Default::default()
+        };
+        let mut context = begin_transaction();
+        transact_create(&resource, &mut context).unwrap();
+        transact_delete::<TestResource>(
+            ("describes_transaction_mutations".into(), "2".into()),
+            &mut context,
+        )
+        .unwrap();
+
+        let described = client
+            .transaction_audit_records(&context.into_items())
+            .unwrap();
+        assert_eq!(described.len(), 2);
+        assert_eq!(described[0].operation, AuditOperation::Put);
+        assert_eq!(described[0].actor.as_deref(), Some("tester"));
+        assert_eq!(described[0].after.as_ref().unwrap()["int"], json!(3));
+        assert_eq!(described[1].operation, AuditOperation::Delete);
+        assert_eq!(described[1].sk, "2");
+        assert_eq!(described[1].after, None);
+    }
+
+    #[tokio::test]
+    async fn records_mutations_with_images() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let client = audited_client(&records).await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = TestResource {
+            pk: "records_mutations_with_images".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        client.create(&resource).await.unwrap();
+        client.update(&resource, json!({ "int": 2 })).await.unwrap();
+        client
+            .delete::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap();
+
+        let records = records.lock().unwrap();
+        let operations: Vec<_> = records.iter().map(|r| r.operation).collect();
+        assert_eq!(
+            operations,
+            vec![
+                AuditOperation::Put,
+                AuditOperation::Update,
+                AuditOperation::Delete
+            ]
+        );
+        assert!(records.iter().all(|r| r.table == *TABLE));
+        assert!(records.iter().all(|r| r.actor.as_deref() == Some("tester")));
+        assert_eq!(records[0].before, None);
+        assert_eq!(records[1].after.as_ref().unwrap()["int"], json!(2));
+        assert_eq!(records[2].before.as_ref().unwrap()["int"], json!(2));
+    }
+
+    #[tokio::test]
+    async fn records_map_entries_and_bulk_deletes() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let client = audited_client(&records).await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "records_map_entries_and_bulk_deletes".to_string();
+        for sk in ["1", "2"] {
+            let resource = TestResource {
+                pk: pk.clone(),
+                sk: sk.to_string(),
+                ..Default::default()
+            };
+            client.force_create(&resource).await.unwrap();
+        }
+        client
+            .set_map_entry::<TestResource>(
+                (pk.clone(), "1".into()),
+                "string_2_string_hashmap",
+                "key",
+                "value",
+            )
+            .await
+            .unwrap();
+        client
+            .delete_where::<TestResource>(pk, SkCondition::Any, ConditionCheckInfo::default())
+            .await
+            .unwrap();
+
+        let records = records.lock().unwrap();
+        assert_eq!(records[2].operation, AuditOperation::Update);
+        assert_eq!(
+            records[2].after.as_ref().unwrap()["string_2_string_hashmap"]["key"],
+            json!("value")
+        );
+        let deleted: Vec<_> = records[3..].iter().map(|r| (r.operation, &*r.sk)).collect();
+        assert_eq!(
+            deleted,
+            vec![(AuditOperation::Delete, "1"), (AuditOperation::Delete, "2")]
+        );
+    }
+}
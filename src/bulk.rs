@@ -0,0 +1,663 @@
+//! Bulk loading of datasets into a table, and export of whole tables.
+//!
+//! Rows are turned into resources and written with batched puts of up to 25 items, which
+//! overwrite any existing resource with the same key, like `Client::force_create` does.
+//!
+//! ```
+//! use dynarust::bulk::{load_csv, ColumnType, CsvMapping, LoadOptions};
+//!
+//! async {
+//!     let file = std::io::BufReader::new(std::fs::File::open("countries.csv")?);
+//!     let mapping = CsvMapping::default()
+//!         .column("Country Code", "code", ColumnType::String)
+//!         .column("Population", "population", ColumnType::Number);
+//!     let loaded = load_csv::<Country, _>(&client, file, &mapping, LoadOptions {
+//!         max_items_per_second: Some(100),
+//!         on_progress: Some(Box::new(|progress| println!("{} rows", progress.items_written))),
+//!         ..Default::default()
+//!     }).await?;
+//! }
+//! ```
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use aws_sdk_dynamodb::model::{AttributeValue, PutRequest, ReturnConsumedCapacity, WriteRequest};
+use futures::future::try_join_all;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::client::{PK, SK};
+use crate::create::resource_as_item;
+use crate::stats::Operation;
+use crate::{AuditOperation, Client, DynarustError, Resource};
+
+/// Maximum number of items in a single batch write.
+const MAX_BATCH_SIZE: usize = 25;
+/// Times that the items left unprocessed by a batch write are retried.
+const MAX_BATCH_RETRIES: usize = 8;
+
+/// How the values of a csv column are parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    String,
+    Number,
+    Bool,
+    /// the value is a json document, useful for nested fields.
+    Json,
+}
+
+/// Maps the columns of a csv file to the fields of a resource. Columns without a mapping are
+/// loaded as strings into the field with the same name as the column, and empty values are
+/// left out of the resource.
+#[derive(Debug, Clone, Default)]
+pub struct CsvMapping {
+    columns: HashMap<String, (String, ColumnType)>,
+    skipped: Vec<String>,
+}
+
+impl CsvMapping {
+    /// Loads the column into the provided field, parsing its values with the provided type.
+    pub fn column(
+        mut self,
+        column: impl Into<String>,
+        field: impl Into<String>,
+        column_type: ColumnType,
+    ) -> Self {
+        self.columns
+            .insert(column.into(), (field.into(), column_type));
+        self
+    }
+
+    /// Does not load the column.
+    pub fn skip(mut self, column: impl Into<String>) -> Self {
+        self.skipped.push(column.into());
+        self
+    }
+
+    fn row_as_object(
+        &self,
+        headers: &[String],
+        row: Vec<String>,
+        line: usize,
+    ) -> Result<Value, DynarustError> {
+        let invalid =
+            |message: String| DynarustError::InvalidRequestError(format!("line {line}: {message}"));
+        if row.len() != headers.len() {
+            return Err(invalid(format!(
+                "expected {} columns, found {}",
+                headers.len(),
+                row.len()
+            )));
+        }
+        let mut object = Map::new();
+        for (column, value) in headers.iter().zip(row) {
+            if value.is_empty() || self.skipped.contains(column) {
+                continue;
+            }
+            let (field, column_type) = match self.columns.get(column) {
+                Some((field, column_type)) => (field.clone(), *column_type),
+                None => (column.clone(), ColumnType::String),
+            };
+            let value = match column_type {
+                ColumnType::String => Value::String(value),
+                ColumnType::Number => match value.parse::<i64>() {
+                    Ok(n) => Value::Number(n.into()),
+                    Err(_) => value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(Number::from_f64)
+                        .map(Value::Number)
+                        .ok_or_else(|| invalid(format!("{column} is not a number: {value}")))?,
+                },
+                ColumnType::Bool => Value::Bool(
+                    value
+                        .parse()
+                        .map_err(|_| invalid(format!("{column} is not a bool: {value}")))?,
+                ),
+                ColumnType::Json => serde_json::from_str(&value)
+                    .map_err(|err| invalid(format!("{column} is not json: {err}")))?,
+            };
+            object.insert(field, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Progress of a bulk load, reported after every batch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadProgress {
+    pub items_written: usize,
+    pub elapsed: Duration,
+}
+
+/// Options of a bulk load.
+pub struct LoadOptions {
+    /// number of items written in each batch, at most 25, which is the default.
+    pub batch_size: usize,
+    /// maximum write rate, the load is not throttled if not provided.
+    pub max_items_per_second: Option<u32>,
+    /// called after every batch.
+    pub on_progress: Option<Box<dyn Fn(LoadProgress) + Send + Sync>>,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: MAX_BATCH_SIZE,
+            max_items_per_second: None,
+            on_progress: None,
+        }
+    }
+}
+
+/// Splits a csv document into rows. Fields can be quoted, with `""` as an escaped quote, and
+/// quoted fields can span multiple lines. Returns every row along with the line where it
+/// starts.
+fn parse_csv<R: BufRead>(reader: R) -> Result<Vec<(usize, Vec<String>)>, DynarustError> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut start = 1;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+        if !quoted {
+            start = i + 1;
+            if line.is_empty() {
+                continue;
+            }
+        } else {
+            field.push('\n');
+        }
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, quoted) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                ('"', true) => quoted = false,
+                ('"', false) if field.is_empty() => quoted = true,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                (c, _) => field.push(c),
+            }
+        }
+        if !quoted {
+            row.push(std::mem::take(&mut field));
+            rows.push((start, std::mem::take(&mut row)));
+        }
+    }
+    if quoted {
+        return Err(DynarustError::InvalidRequestError(format!(
+            "line {start}: unterminated quoted field"
+        )));
+    }
+    Ok(rows)
+}
+
+/// Loads the rows of a csv document with a header row as resources of type T.
+///
+/// # arguments
+///
+/// * `client` - The client used for writing the resources.
+/// * `reader` - The csv document.
+/// * `mapping` - How the columns are loaded into the fields of the resource.
+/// * `options` - Batching, throttling and progress options.
+///
+/// Returns the number of loaded resources. Every row is parsed before writing anything, so a
+/// malformed document does not get partially loaded.
+pub async fn load_csv<T, R>(
+    client: &Client,
+    reader: R,
+    mapping: &CsvMapping,
+    options: LoadOptions,
+) -> Result<usize, DynarustError>
+where
+    T: Resource + Serialize + DeserializeOwned,
+    R: BufRead,
+{
+    let mut rows = parse_csv(reader)?.into_iter();
+    let headers = match rows.next() {
+        Some((_, headers)) => headers,
+        None => return Ok(0),
+    };
+    let mut resources = vec![];
+    for (line, row) in rows {
+        let object = mapping.row_as_object(&headers, row, line)?;
+        resources.push(parse_resource::<T>(object, line)?);
+    }
+    load_resources(client, resources, options).await
+}
+
+/// Loads a document with a json object per line as resources of type T.
+///
+/// # arguments
+///
+/// * `client` - The client used for writing the resources.
+/// * `reader` - The jsonl document.
+/// * `options` - Batching, throttling and progress options.
+///
+/// Returns the number of loaded resources. Every line is parsed before writing anything, so a
+/// malformed document does not get partially loaded.
+pub async fn load_jsonl<T, R>(
+    client: &Client,
+    reader: R,
+    options: LoadOptions,
+) -> Result<usize, DynarustError>
+where
+    T: Resource + Serialize + DeserializeOwned,
+    R: BufRead,
+{
+    let mut resources = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object = serde_json::from_str(&line)
+            .map_err(|err| DynarustError::InvalidRequestError(format!("line {}: {err}", i + 1)))?;
+        resources.push(parse_resource::<T>(object, i + 1)?);
+    }
+    load_resources(client, resources, options).await
+}
+
+fn parse_resource<T: DeserializeOwned>(object: Value, line: usize) -> Result<T, DynarustError> {
+    serde_json::from_value(object)
+        .map_err(|err| DynarustError::InvalidRequestError(format!("line {line}: {err}")))
+}
+
+pub(crate) async fn load_resources<T: Resource + Serialize + DeserializeOwned>(
+    client: &Client,
+    resources: Vec<T>,
+    options: LoadOptions,
+) -> Result<usize, DynarustError> {
+    let batch_size = options.batch_size.clamp(1, MAX_BATCH_SIZE);
+    let start = Instant::now();
+    let mut items_written = 0;
+    for batch in resources.chunks(batch_size) {
+        let mut items = vec![];
+        for resource in batch {
+            let (item, attributes) = put_request_item(client, resource)?;
+            items.push((resource.pk_sk(), Value::Object(item), attributes));
+        }
+
+        if let Some(max_items_per_second) = options.max_items_per_second {
+            let allowed_at = Duration::from_secs_f64(
+                (items_written + items.len()) as f64 / max_items_per_second.max(1) as f64,
+            );
+            if let Some(wait) = allowed_at.checked_sub(start.elapsed()) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let requests = items
+            .iter()
+            .map(|(_, _, attributes)| {
+                let put = PutRequest::builder()
+                    .set_item(Some(attributes.clone()))
+                    .build();
+                WriteRequest::builder().put_request(put).build()
+            })
+            .collect();
+        write_batch(client, &T::table(), requests).await?;
+
+        items_written += items.len();
+        for (key, item, _) in items {
+            client
+                .audit(AuditOperation::Put, &T::table(), key, None, Some(item))
+                .await?;
+        }
+        if let Some(on_progress) = &options.on_progress {
+            on_progress(LoadProgress {
+                items_written,
+                elapsed: start.elapsed(),
+            });
+        }
+    }
+    Ok(items_written)
+}
+
+/// The item of a resource and the attributes that are written for it in a batch, which are
+/// encoded, intercepted, scoped and externalized the same way as in `create`.
+#[allow(clippy::type_complexity)]
+fn put_request_item<T: Resource + Serialize>(
+    client: &Client,
+    resource: &T,
+) -> Result<(Map<String, Value>, HashMap<String, AttributeValue>), DynarustError> {
+    let item = resource_as_item(resource)?;
+    let (pk, sk) = resource.pk_sk();
+    let mut attributes = HashMap::new();
+    for (k, v) in item.iter() {
+        attributes.insert(k.clone(), client.encode_value(v)?);
+    }
+    attributes.insert(PK.to_string(), AttributeValue::S(pk));
+    attributes.insert(SK.to_string(), AttributeValue::S(sk));
+    client.intercept_item(&T::table(), &mut attributes)?;
+    client.scope_key(&mut attributes);
+    client.externalize_key(&T::table(), &mut attributes);
+    Ok((item, attributes))
+}
+
+/// Writes a batch, failing with a `DynarustError::UnprocessedWritesError` if dynamo leaves
+/// some of its items unprocessed after every retry.
+async fn write_batch(
+    client: &Client,
+    table: &str,
+    requests: Vec<WriteRequest>,
+) -> Result<(), DynarustError> {
+    let unprocessed = write_batch_with_retries(client, table, requests).await?;
+    if unprocessed.is_empty() {
+        return Ok(());
+    }
+    Err(DynarustError::UnprocessedWritesError {
+        table: table.to_string(),
+        keys: unprocessed
+            .iter()
+            .map(|request| write_request_key(client, table, request))
+            .collect(),
+    })
+}
+
+/// Writes a batch, retrying the items left unprocessed by dynamo with exponential backoff up
+/// to `MAX_BATCH_RETRIES` times. Returns the requests that were still unprocessed after the
+/// last retry.
+pub(crate) async fn write_batch_with_retries(
+    client: &Client,
+    table: &str,
+    mut requests: Vec<WriteRequest>,
+) -> Result<Vec<WriteRequest>, DynarustError> {
+    let mut backoff = Duration::from_millis(50);
+    for attempt in 0..=MAX_BATCH_RETRIES {
+        if attempt > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(5));
+        }
+        let batch_write = client
+            .dynamo(table)
+            .batch_write_item()
+            .request_items(table, requests)
+            .set_return_item_collection_metrics(client.return_collection_metrics())
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send();
+        let result = client
+            .stats
+            .measure(Operation::BatchWriteItems, async { Ok(batch_write.await?) })
+            .await?;
+        client
+            .stats
+            .add_all_capacity(Operation::BatchWriteItems, result.consumed_capacity());
+        client.report_all_collection_metrics(result.item_collection_metrics());
+        requests = result
+            .unprocessed_items()
+            .and_then(|unprocessed| unprocessed.get(table))
+            .cloned()
+            .unwrap_or_default();
+        if requests.is_empty() {
+            break;
+        }
+    }
+    Ok(requests)
+}
+
+/// The pk and sk pair of the item of a batch write request, without the tenant prefix.
+pub(crate) fn write_request_key(
+    client: &Client,
+    table: &str,
+    request: &WriteRequest,
+) -> (String, String) {
+    let key = match (&request.put_request, &request.delete_request) {
+        (Some(put), _) => put.item.as_ref(),
+        (_, Some(delete)) => delete.key.as_ref(),
+        _ => None,
+    };
+    let (pk_attr, sk_attr) = client.key_names(table);
+    let attr = |name: &str| match key.and_then(|key| key.get(name)) {
+        Some(AttributeValue::S(v)) => v.clone(),
+        _ => String::new(),
+    };
+    let pk = attr(pk_attr);
+    let pk = match client.tenant() {
+        Some(tenant) => tenant.strip(&pk).unwrap_or(&pk).to_string(),
+        None => pk,
+    };
+    (pk, attr(sk_attr))
+}
+
+/// Options of a table export.
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    /// number of segments of the parallel scan, default is 4.
+    pub segments: i32,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self { segments: 4 }
+    }
+}
+
+/// Exports every item in the table of resource T, writing one json object per line with the
+/// item as it is stored, including its key attributes, so the output can be loaded back with
+/// `load_jsonl`. The table is read with a parallel scan, so the lines are not in any
+/// particular order. If the client is scoped to a tenant, only the items of the tenant are
+/// exported. For a compressed export, wrap the writer in a compressing one.
+///
+/// # arguments
+///
+/// * `client` - The client used for scanning the table.
+/// * `writer` - Where the lines are written.
+/// * `options` - optional export options.
+///
+/// Returns the number of exported items.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let file = std::io::BufWriter::new(std::fs::File::create("cars.jsonl")?);
+///     let exported = dynarust::bulk::dump::<Car, _>(&client, file, None).await?;
+/// }
+/// ```
+pub async fn dump<T: Resource, W: Write + Send>(
+    client: &Client,
+    writer: W,
+    options: Option<DumpOptions>,
+) -> Result<usize, DynarustError> {
+    let segments = options.unwrap_or_default().segments.max(1);
+    let table = T::table();
+    let writer = Mutex::new(writer);
+
+    let written = try_join_all((0..segments).map(|segment| {
+        let (table, writer) = (&table, &writer);
+        async move {
+            let mut written = 0;
+            client
+                .scan_segment(table, segment, segments, None, |items| {
+                    let mut writer = writer.lock().unwrap();
+                    for item in items.iter().filter(|item| client.owns_item(item)) {
+                        let line = serde_json::to_string(&client.decode_item(item)?)?;
+                        writeln!(writer, "{line}")
+                            .map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+                        written += 1;
+                    }
+                    Ok(())
+                })
+                .await?;
+            Ok::<_, DynarustError>(written)
+        }
+    }))
+    .await?;
+
+    writer
+        .into_inner()
+        .unwrap()
+        .flush()
+        .map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+    Ok(written.into_iter().sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::client::tests::TestResource;
+
+    #[test]
+    fn describes_unprocessed_writes() {
+        let client = crate::ClientBuilder::new(aws_config::SdkConfig::builder().build())
+            .build()
+            .scoped("tenant")
+            .unwrap();
+        let key = HashMap::from([
+            (PK.to_string(), AttributeValue::S("tenant#pk".into())),
+            (SK.to_string(), AttributeValue::S("sk".into())),
+        ]);
+        let put = PutRequest::builder().set_item(Some(key)).build();
+        let request = WriteRequest::builder().put_request(put).build();
+        let key = write_request_key(&client, "Table", &request);
+        assert_eq!(key, ("pk".to_string(), "sk".to_string()));
+
+        let err = DynarustError::UnprocessedWritesError {
+            table: "Table".into(),
+            keys: vec![key],
+        };
+        assert_eq!(
+            err.to_string(),
+            "1 writes to table Table were left unprocessed after every retry"
+        );
+    }
+
+    #[test]
+    fn externalizes_loaded_keys() {
+        let client = crate::ClientBuilder::new(aws_config::SdkConfig::builder().build())
+            .build()
+            .with_external_table::<TestResource>("id", "version")
+            .unwrap();
+        let resource = TestResource {
+            pk: "pk".into(),
+            sk: "sk".into(),
+            ..Default::default()
+        };
+        let (_, attributes) = put_request_item(&client, &resource).unwrap();
+        assert!(!attributes.contains_key(PK) && !attributes.contains_key(SK));
+        assert_eq!(attributes["id"], AttributeValue::S("pk".into()));
+
+        let put = PutRequest::builder().set_item(Some(attributes)).build();
+        let request = WriteRequest::builder().put_request(put).build();
+        assert_eq!(
+            write_request_key(&client, &TestResource::table(), &request),
+            resource.pk_sk()
+        );
+    }
+
+    #[test]
+    fn parses_quoted_csv_fields() {
+        let csv = "pk,sk,string\n\"a,1\",1,\"say \"\"hi\"\"\"\n\nb,2,\"two\nlines\"\n";
+        let rows = parse_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (1, vec!["pk".into(), "sk".into(), "string".into()]),
+                (2, vec!["a,1".into(), "1".into(), "say \"hi\"".into()]),
+                (4, vec!["b".into(), "2".into(), "two\nlines".into()]),
+            ]
+        );
+        let err = parse_csv("a,\"b\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid request: line 1: unterminated quoted field"
+        );
+    }
+
+    #[test]
+    fn maps_csv_columns_to_fields() {
+        let mapping = CsvMapping::default()
+            .column("count", "int", ColumnType::Number)
+            .skip("ignored");
+        let headers = ["pk", "count", "ignored", "string"].map(String::from);
+        let row = ["a", "3", "x", ""].map(String::from).to_vec();
+        assert_eq!(
+            mapping.row_as_object(&headers, row, 2).unwrap(),
+            json!({ "pk": "a", "int": 3 })
+        );
+        let row = ["a", "three", "x", ""].map(String::from).to_vec();
+        assert_eq!(
+            mapping
+                .row_as_object(&headers, row, 2)
+                .unwrap_err()
+                .to_string(),
+            "Invalid request: line 2: count is not a number: three"
+        );
+    }
+
+    #[tokio::test]
+    async fn loads_csv_in_batches() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let mut csv = "pk,sk,int\n".to_string();
+        for i in 0..30 {
+            csv += &format!("loads_csv_in_batches,{i},{i}\n");
+        }
+        let progress = Arc::new(Mutex::new(vec![]));
+        let reported = progress.clone();
+        let mapping = CsvMapping::default().column("int", "int", ColumnType::Number);
+        let loaded = load_csv::<TestResource, _>(
+            &client,
+            csv.as_bytes(),
+            &mapping,
+            LoadOptions {
+                batch_size: 20,
+                on_progress: Some(Box::new(move |p| {
+                    reported.lock().unwrap().push(p.items_written)
+                })),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(loaded, 30);
+        assert_eq!(*progress.lock().unwrap(), vec![20, 30]);
+
+        let count = client
+            .count_by_pk::<TestResource>("loads_csv_in_batches".into())
+            .await
+            .unwrap();
+        assert_eq!(count, 30);
+    }
+
+    #[tokio::test]
+    async fn dumps_tenant_items_as_jsonl() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let tenant = client.scoped("dumps_tenant_items_as_jsonl").unwrap();
+        for i in 0..3 {
+            let resource = TestResource {
+                pk: "dumps_tenant_items_as_jsonl".to_string(),
+                sk: i.to_string(),
+                int: i,
+                ..Default::default()
+            };
+            tenant.force_create(&resource).await.unwrap();
+        }
+
+        let mut output = vec![];
+        let dumped = dump::<TestResource, _>(&tenant, &mut output, None)
+            .await
+            .unwrap();
+        assert_eq!(dumped, 3);
+        let lines: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line[SK].is_string()));
+    }
+}
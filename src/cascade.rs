@@ -0,0 +1,199 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    begin_transaction, condition_check_exists, transact_aggregate, transact_create,
+    transact_delete_with_checks, Client, ConditionCheckInfo, DeleteWhereSummary, DynarustError,
+    KeyOf, Resource, SkCondition,
+};
+
+/// A resource that belongs to a parent item, like the lines of an order, written with
+/// `Client::create_child` and `Client::delete_child` so the parent keeps count of its children,
+/// and deleted along with it with `Client::delete_cascade`.
+///
+/// # Examples
+///
+/// ```
+/// impl Child for OrderLine {
+///     type Parent = Order;
+///     fn parent_key(&self) -> KeyOf<Order> { Order::key("orders", &self.order_id) }
+///     fn children_of(order: &KeyOf<Order>) -> (String, SkCondition) {
+///         (format!("order#{}", order.sk), SkCondition::BeginsWith("line#".into()))
+///     }
+/// }
+/// ```
+pub trait Child: Resource {
+    type Parent: Resource;
+
+    /// The key of the parent of this resource.
+    fn parent_key(&self) -> KeyOf<Self::Parent>;
+
+    /// Where the children of a parent live: their pk and the condition on their sort keys.
+    fn children_of(parent: &KeyOf<Self::Parent>) -> (String, SkCondition);
+
+    /// Numeric field of the parent with its number of children, default is `child_count`.
+    fn child_count_field() -> &'static str {
+        "child_count"
+    }
+}
+
+impl Client {
+    /// Creates a child resource and increments the child count of its parent in the same
+    /// transaction, which is canceled if the parent does not exist or the child already does.
+    ///
+    /// # arguments
+    ///
+    /// * `child` - The resource that will be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let line = client.create_child(&OrderLine { order_id, product, amount: 2 }).await?;
+    /// }
+    /// ```
+    pub async fn create_child<'a, C: Child + Serialize>(
+        &self,
+        child: &'a C,
+    ) -> Result<&'a C, DynarustError> {
+        let mut context = begin_transaction();
+        let created = transact_create(child, &mut context)?;
+        context.label("create child");
+        transact_aggregate::<C::Parent>(
+            child.parent_key().pk_sk(),
+            child_count_delta::<C>(1),
+            &mut context,
+        )?;
+        context.label("parent exists");
+        self.execute_transaction(context).await?;
+        Ok(created)
+    }
+
+    /// Deletes a child resource and decrements the child count of its parent in the same
+    /// transaction, which is canceled if the child or the parent do not exist.
+    ///
+    /// # arguments
+    ///
+    /// * `child` - The resource that will get deleted.
+    pub async fn delete_child<C: Child>(&self, child: &C) -> Result<(), DynarustError> {
+        let mut context = begin_transaction();
+        transact_delete_with_checks::<C>(
+            child.pk_sk(),
+            vec![condition_check_exists()],
+            &mut context,
+        )?;
+        context.label("delete child");
+        transact_aggregate::<C::Parent>(
+            child.parent_key().pk_sk(),
+            child_count_delta::<C>(-1),
+            &mut context,
+        )?;
+        context.label("parent exists");
+        self.execute_transaction(context).await
+    }
+
+    /// Deletes a parent item and then every one of its children of type C, found with
+    /// `Child::children_of`. The parent goes first so that no child can be created for it
+    /// while the children are deleted, which is done with batched writes as in `delete_where`.
+    /// If the children are not all deleted, like when the call is interrupted, it can be
+    /// called again to delete the rest, even if the parent is already gone.
+    ///
+    /// # arguments
+    ///
+    /// * `parent` - The key of the parent item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let summary = client.delete_cascade::<OrderLine>(&Order::key("orders", id)).await?;
+    ///     println!("deleted {} lines", summary.deleted);
+    /// }
+    /// ```
+    pub async fn delete_cascade<C: Child>(
+        &self,
+        parent: &KeyOf<C::Parent>,
+    ) -> Result<DeleteWhereSummary, DynarustError> {
+        self.delete::<C::Parent>(parent.pk_sk()).await?;
+        let (pk, sk_condition) = C::children_of(parent);
+        self.delete_where::<C>(pk, sk_condition, ConditionCheckInfo::default())
+            .await
+    }
+}
+
+/// Deltas of `transact_aggregate` that change the child count of a parent.
+fn child_count_delta<C: Child>(delta: i64) -> Value {
+    json!({ C::child_count_field(): delta })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Child, Client, DynarustError, KeyOf, Resource, SkCondition};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Line {
+        order: String,
+        id: String,
+    }
+
+    impl Resource for Line {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.order.clone(), format!("line#{}", self.id))
+        }
+    }
+
+    impl Child for Line {
+        type Parent = TestResource;
+
+        fn parent_key(&self) -> KeyOf<TestResource> {
+            TestResource::key(self.order.clone(), "order")
+        }
+
+        fn children_of(parent: &KeyOf<TestResource>) -> (String, SkCondition) {
+            (parent.pk.clone(), SkCondition::BeginsWith("line#".into()))
+        }
+
+        fn child_count_field() -> &'static str {
+            "int"
+        }
+    }
+
+    #[tokio::test]
+    async fn cascades_children_of_parent() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let pk = "cascades_children_of_parent";
+        let line = |id: &str| Line {
+            order: pk.into(),
+            id: id.into(),
+        };
+        let parent_key = TestResource::key(pk, "order");
+        let err = client.create_child(&line("orphan")).await.unwrap_err();
+        assert!(matches!(err, DynarustError::TransactionCanceledError(_)));
+
+        let order = TestResource {
+            pk: pk.into(),
+            sk: "order".into(),
+            ..Default::default()
+        };
+        client.force_create(&order).await.unwrap();
+        for id in ["1", "2", "3"] {
+            client.create_child(&line(id)).await.unwrap();
+        }
+        client.delete_child(&line("3")).await.unwrap();
+        let parent = client.get::<TestResource>(parent_key.pk_sk()).await;
+        assert_eq!(parent.unwrap().unwrap().int, 2);
+
+        let summary = client.delete_cascade::<Line>(&parent_key).await.unwrap();
+        assert_eq!(summary.deleted, 2);
+        let remaining = client.count_by_pk::<TestResource>(pk.into()).await;
+        assert_eq!(remaining.unwrap(), 0);
+    }
+}
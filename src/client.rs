@@ -1,47 +1,246 @@
 use std::collections::HashMap;
-use std::env;
 use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 
-use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::{Map, Value};
 
-use crate::DynarustError;
+use crate::collection_metrics::CollectionMetricsHandler;
+use crate::errors::decode_error;
+use crate::gsi::is_index_attribute;
+use crate::sequence::Sequences;
+use crate::stats::{Operation, StatsRecorder};
+use crate::strict::unknown_attributes;
+use crate::table::KnownTables;
+use crate::transaction::transaction_error;
+use crate::{
+    AuditSink, Clock, Codec, Cursor, DynarustError, KeyOf, LocalOptions, ReadConsistency,
+    SecondaryIndex, SkCondition, TenantScope, TransactionContext, UnknownAttributes,
+    WriteInterceptor,
+};
 
 pub(crate) const PK: &str = "PrimaryKey";
 pub(crate) const SK: &str = "SecondaryKey";
+pub(crate) const GSI1_PK: &str = "Gsi1PrimaryKey";
+pub(crate) const GSI1_SK: &str = "Gsi1SecondaryKey";
+/// Name of the global secondary index whose keys are the SecondaryKey and the PrimaryKey.
+pub const INVERTED_INDEX: &str = "InvertedIndex";
+/// Name of the global secondary index whose keys are the ones returned by
+/// `Resource::gsi1_pk_sk`.
+pub const GSI1_INDEX: &str = "Gsi1";
+/// Attribute holding the `Resource::schema_version` an item was written with.
+pub const SCHEMA_VERSION: &str = "schema_version";
+
+/// Attributes managed by dynarust, which never belong to the serialized resource.
+pub(crate) const KEY_ATTRIBUTES: [&str; 4] = [PK, SK, GSI1_PK, GSI1_SK];
+
+/// Removes the attributes managed by dynarust from a serialized resource, so that resources
+/// with a flattened catch-all map do not capture or overwrite them.
+pub(crate) fn strip_key_attributes(object: &mut Map<String, Value>) {
+    for attr in KEY_ATTRIBUTES {
+        object.remove(attr);
+    }
+}
+
+/// The attribute of an item that makes it fail to deserialize as T: the one reported as
+/// missing, or else the first one whose removal fixes the deserialization or changes its
+/// error.
+fn offending_attribute<T: DeserializeOwned>(
+    value: &Value,
+    err: &serde_json::Error,
+) -> Option<String> {
+    let missing = |err: &serde_json::Error| {
+        let message = err.to_string();
+        let field = message.strip_prefix("missing field `")?;
+        Some(field[..field.find('`')?].to_string())
+    };
+    if let Some(field) = missing(err) {
+        return Some(field);
+    }
+    let object = value.as_object()?;
+    object
+        .keys()
+        .find(|k| {
+            let mut without = object.clone();
+            without.remove(*k);
+            match T::deserialize(&Value::Object(without)) {
+                Ok(_) => true,
+                Err(other) => other.to_string() != err.to_string(),
+            }
+        })
+        .cloned()
+}
 
 /// list options for listing resources in dynamo under the same PrimaryKey.
+#[derive(Debug, Clone)]
 pub struct ListOptions {
     /// Sort key to start from listing. If not provided it will start listing from the beginning.
     pub from: Option<String>,
+    /// condition on the sort keys of the listed items, like a prefix or a range of timestamps,
+    /// default is any sort key. It cannot be combined with `from`.
+    pub sk_condition: SkCondition,
     /// maximum number of items to list in a single page, default is 25.
     pub limit: i32,
     /// whether to list in ascending order or in descending order, default is false.
     pub sort_desc: bool,
+    /// Cursor returned by a previous page for continuing the listing where it stopped. It must
+    /// come from a listing of the same pk.
+    pub cursor: Option<Cursor>,
+    /// consistency of the query, if not provided the one of the client is used.
+    pub consistency: Option<ReadConsistency>,
 }
 
 impl Default for ListOptions {
     fn default() -> Self {
         Self {
             from: None,
+            sk_condition: SkCondition::Any,
             limit: 25,
             sort_desc: false,
+            cursor: None,
+            consistency: None,
+        }
+    }
+}
+
+/// How the attributes of a resource are written to dynamo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WritePolicy {
+    /// every attribute is written.
+    #[default]
+    WriteAll,
+    /// attributes equal to their default value (null, empty strings, arrays and maps, zero and
+    /// false) are not written, and are removed by updates that set them to that value. This
+    /// keeps items sparse, which is required for sparse indexes, as empty string index keys
+    /// are not valid. The resource must be able to deserialize without those attributes, for
+    /// example using `#[serde(default)]`.
+    SkipDefaults,
+}
+
+impl WritePolicy {
+    pub(crate) fn skips(&self, value: &Value) -> bool {
+        match self {
+            WritePolicy::WriteAll => false,
+            WritePolicy::SkipDefaults => match value {
+                Value::Null => true,
+                Value::Bool(b) => !b,
+                Value::Number(n) => n.as_f64() == Some(0.0),
+                Value::String(s) => s.is_empty(),
+                Value::Array(a) => a.is_empty(),
+                Value::Object(o) => o.is_empty(),
+            },
         }
     }
 }
 
 /// All the resources that dynarust uses must implement this trait.
+///
+/// Resources can use `#[serde(flatten)]`, also nested, and a flattened catch-all map never
+/// receives the key attributes managed by dynarust. Note that serde hands every unknown field
+/// to all the flattened members of a struct, so a catch-all map should not sit next to other
+/// flattened structs.
 pub trait Resource {
     /// DynamoDB's table name for this resource.
     fn table() -> String;
     /// Rules for forming the PrimaryKey and SecondaryKey based on the resource object.
     fn pk_sk(&self) -> (String, String);
+    /// Rules for forming the PrimaryKey and SecondaryKey of the first global secondary index
+    /// based on the resource object, which is only created if `SecondaryIndex::GSI1` is listed
+    /// in `secondary_indexes`. They are written as extra attributes on every create and
+    /// update, so the index keys are always consistent with the resource. If None is returned,
+    /// the resource is not indexed.
+    fn gsi1_pk_sk(&self) -> Option<(String, String)> {
+        None
+    }
+    /// Fields that cannot be modified once the resource is created. Update requests that try
+    /// to change any of them are rejected.
+    fn immutable_fields() -> &'static [&'static str] {
+        &[]
+    }
+    /// How the attributes of this resource are written, by default all of them are.
+    fn write_policy() -> WritePolicy {
+        WritePolicy::WriteAll
+    }
+    /// Lifecycle hook run before the resource gets written by create, update, the transact
+    /// builders and bulk loads, useful for normalizing fields or filling derived ones. Returns
+    /// the copy that is written instead of the resource, or None if it is written as it is.
+    /// The copy must keep the pk and sk of the resource.
+    fn before_save(&self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    /// Lifecycle hook run on every resource after it gets read, by get, batch_get, list or any
+    /// other operation that returns stored resources.
+    fn after_load(&mut self) {}
+    /// Version of the shape of this resource. If greater than zero, it is written in the
+    /// `schema_version` attribute of every created resource, and items written with an older
+    /// version go through `migrate` when they are read. Updates keep the version of the stored
+    /// item, as they only write some of its attributes.
+    fn schema_version() -> u32 {
+        0
+    }
+    /// Migrates an item written with an older schema version to the shape of the current one,
+    /// right before deserializing it in get, batch_get or list. Items without the
+    /// `schema_version` attribute are version 0.
+    fn migrate(_version: u32, value: Value) -> Value {
+        value
+    }
+    /// How the attributes of stored items that are not fields of this resource are handled
+    /// when reading them, by default they are ignored.
+    fn unknown_attributes() -> UnknownAttributes {
+        UnknownAttributes::Ignore
+    }
+    /// Attribute with the expiration time of the items of this resource, in epoch seconds, if
+    /// TTL is enabled on its table. Declare it with `resource_ttl`, which checks that the field
+    /// is an integer.
+    fn ttl_attribute() -> Option<&'static str> {
+        None
+    }
+    /// Global secondary indexes of the table of this resource. They are created by
+    /// `create_table` and queried with `query_index`. Resources stored as adjacency list edges
+    /// list `SecondaryIndex::INVERTED` here, and the ones with `gsi1_pk_sk` list
+    /// `SecondaryIndex::GSI1`.
+    fn secondary_indexes() -> &'static [SecondaryIndex] {
+        &[]
+    }
+    /// Keys of this resource in its `secondary_indexes`, as pairs of key attribute and value.
+    /// They are written as extra attributes on every create and update, like the GSI1 keys but
+    /// without the tenant prefix of scoped clients, and the resource is left out of the indexes
+    /// whose keys are not returned.
+    fn index_keys(&self) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+    /// The key of the resource of this type with the provided pk and sk, for operations that
+    /// only need the key, like `Client::delete_key`.
+    fn key(pk: impl Into<String>, sk: impl Into<String>) -> KeyOf<Self>
+    where
+        Self: Sized,
+    {
+        KeyOf::new(pk, sk)
+    }
 }
 
 /// Client that holds the connection to dynamo.
+#[derive(Clone)]
 pub struct Client {
     pub(crate) client: aws_sdk_dynamodb::Client,
+    pub(crate) routes: Arc<HashMap<String, aws_sdk_dynamodb::Client>>,
+    pub(crate) tenant: Option<TenantScope>,
+    pub(crate) audit: Option<Arc<dyn AuditSink>>,
+    pub(crate) actor: Option<String>,
+    pub(crate) stats: Arc<StatsRecorder>,
+    pub(crate) collection_metrics: Option<CollectionMetricsHandler>,
+    pub(crate) external_tables: Arc<HashMap<String, (String, String)>>,
+    pub(crate) read_consistency: ReadConsistency,
+    pub(crate) write_interceptors: Vec<Arc<dyn WriteInterceptor>>,
+    pub(crate) sequences: Option<Arc<Sequences>>,
+    pub(crate) codec: Option<Arc<dyn Codec>>,
+    pub(crate) clock: Option<Arc<dyn Clock>>,
+    pub(crate) known_tables: Arc<KnownTables>,
 }
 
 impl Client {
@@ -53,6 +252,19 @@ impl Client {
         let cfg = aws_config::from_env().load().await;
         Client {
             client: aws_sdk_dynamodb::Client::new(&cfg),
+            routes: Default::default(),
+            tenant: None,
+            audit: None,
+            actor: None,
+            stats: Default::default(),
+            collection_metrics: None,
+            external_tables: Default::default(),
+            read_consistency: Default::default(),
+            write_interceptors: vec![],
+            sequences: None,
+            codec: None,
+            clock: None,
+            known_tables: Default::default(),
         }
     }
 
@@ -65,17 +277,11 @@ impl Client {
 
     /// Connect against a local version of DynamoDB running on the specified port.
     pub async fn local_on_port(port: u16) -> Self {
-        env::set_var("AWS_REGION", "us-east-1");
-        env::set_var("AWS_ACCESS_KEY_ID", ".");
-        env::set_var("AWS_SECRET_ACCESS_KEY", ".");
-        let cfg = aws_config::from_env().load().await;
-        Client {
-            client: aws_sdk_dynamodb::Client::from_conf(
-                aws_sdk_dynamodb::config::Builder::from(&cfg)
-                    .endpoint_url(format!("http://localhost:{port}"))
-                    .build(),
-            ),
-        }
+        Self::local_with(LocalOptions {
+            port,
+            ..Default::default()
+        })
+        .await
     }
 
     pub(crate) fn resource_as_object<T: Resource + Serialize>(
@@ -94,6 +300,93 @@ impl Client {
                 )
             })
             .cloned()
+            .map(|mut object| {
+                strip_key_attributes(&mut object);
+                object
+            })
+    }
+
+    /// Converts a raw item into json. An attribute that cannot be converted results in a
+    /// `DynarustError::DecodeError` without table, which readers fill with `in_table`.
+    pub(crate) fn item_as_value(
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<Value, DynarustError> {
+        Self::item_as_value_with(item, Self::attr2value)
+    }
+
+    /// Converts a raw item into json, converting each attribute with `decode`.
+    pub(crate) fn item_as_value_with(
+        item: &HashMap<String, AttributeValue>,
+        decode: impl Fn(&AttributeValue) -> Result<Value, DynarustError>,
+    ) -> Result<Value, DynarustError> {
+        let mut object = Value::Object(Map::new());
+        for (k, v) in item {
+            object[k] = decode(v).map_err(|err| {
+                let attr = |name: &str| match item.get(name) {
+                    Some(AttributeValue::S(v)) => v.clone(),
+                    _ => String::new(),
+                };
+                decode_error(
+                    "",
+                    (attr(PK), attr(SK)),
+                    Some(k.clone()),
+                    format!("{v:?}"),
+                    err,
+                )
+            })?
+        }
+        Ok(object)
+    }
+
+    pub(crate) fn value_as_resource<T: Resource + DeserializeOwned>(
+        mut value: Value,
+    ) -> Result<T, DynarustError> {
+        let attr = |name: &str| match value.get(name) {
+            Some(Value::String(v)) => v.clone(),
+            _ => String::new(),
+        };
+        let key = (attr(PK), attr(SK));
+        if let Some(object) = value.as_object_mut() {
+            strip_key_attributes(object);
+        }
+        if T::schema_version() > 0 {
+            let version = value
+                .as_object_mut()
+                .and_then(|object| object.remove(SCHEMA_VERSION))
+                .and_then(|version| version.as_u64())
+                .unwrap_or_default() as u32;
+            if version < T::schema_version() {
+                value = T::migrate(version, value);
+            }
+        }
+        let mode = T::unknown_attributes();
+        if !matches!(mode, UnknownAttributes::Ignore) {
+            let mut attributes = unknown_attributes::<T>(&value);
+            attributes.retain(|attr| !is_index_attribute::<T>(attr));
+            match mode {
+                _ if attributes.is_empty() => {}
+                UnknownAttributes::Report(report) => report(&T::table(), &attributes),
+                _ => {
+                    return Err(DynarustError::UnknownAttributesError {
+                        table: T::table(),
+                        attributes,
+                    })
+                }
+            }
+        }
+        let mut resource = match T::deserialize(&value) {
+            Ok(resource) => resource,
+            Err(err) => {
+                let attribute = offending_attribute::<T>(&value, &err);
+                let snippet = match &attribute {
+                    Some(attribute) => value[attribute].to_string(),
+                    None => value.to_string(),
+                };
+                return Err(decode_error(&T::table(), key, attribute, snippet, err));
+            }
+        };
+        resource.after_load();
+        Ok(resource)
     }
 
     pub(crate) fn value2attr(v: &Value) -> Result<AttributeValue, DynarustError> {
@@ -156,7 +449,8 @@ impl Client {
         }
     }
 
-    /// Executes a transaction given the transaction context.
+    /// Executes a transaction given the transaction context. An empty transaction context is a
+    /// no-op that succeeds without sending any request.
     ///
     /// # arguments
     ///
@@ -175,14 +469,42 @@ impl Client {
     /// ```
     pub async fn execute_transaction(
         &self,
-        transaction_context: Vec<TransactWriteItem>,
+        transaction_context: TransactionContext,
     ) -> Result<(), DynarustError> {
-        let mut builder = self.client.transact_write_items();
-        for transaction in transaction_context {
-            builder = builder.transact_items(transaction.clone())
+        if transaction_context.is_empty() {
+            return Ok(());
+        }
+        let operations = transaction_context.operations();
+        let mut items = transaction_context.into_items();
+        for item in items.iter_mut() {
+            self.recode_transaction_item(item)?;
+        }
+        let records = self.transaction_audit_records(&items)?;
+        let mut builder = self
+            .dynamo_for_items(&items)?
+            .transact_write_items()
+            .set_return_item_collection_metrics(self.return_collection_metrics())
+            .return_consumed_capacity(ReturnConsumedCapacity::Total);
+        for mut transaction in items {
+            self.intercept_transaction_item(&mut transaction)?;
+            self.scope_transaction_item(&mut transaction);
+            self.externalize_transaction_item(&mut transaction);
+            builder = builder.transact_items(transaction)
         }
-        builder.send().await?;
-        Ok(())
+        let transaction = async {
+            builder
+                .send()
+                .await
+                .map_err(|err| transaction_error(err, operations))
+        };
+        let result = self
+            .stats
+            .measure(Operation::Transaction, transaction)
+            .await?;
+        self.stats
+            .add_all_capacity(Operation::Transaction, result.consumed_capacity());
+        self.report_all_collection_metrics(result.item_collection_metrics());
+        self.audit_all(records).await
     }
 }
 
@@ -199,11 +521,12 @@ impl Client {
 ///     client.execute_transaction(context).await?;
 /// }
 /// ```
-pub fn begin_transaction() -> Vec<TransactWriteItem> {
-    vec![]
+pub fn begin_transaction() -> TransactionContext {
+    TransactionContext::new()
 }
 
 /// Dynamo operator for comparing values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DynamoOperator {
     /// Equals.
     Eq,
@@ -234,21 +557,45 @@ impl Display for DynamoOperator {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use aws_sdk_dynamodb::model::AttributeValue;
     use lazy_static::lazy_static;
     use rand::distributions::Alphanumeric;
-    use rand::Rng;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use serde::{Deserialize, Serialize};
-    use serde_json::json;
+    use serde_json::{json, Value};
     use std::collections::HashMap;
 
-    use crate::{Client, Resource};
+    use crate::client::{PK, SCHEMA_VERSION, SK};
+    use crate::create::resource_as_item;
+    use crate::update::apply_update_request;
+    use crate::{Client, DynarustError, Resource, UnknownAttributes};
 
     lazy_static! {
-        pub(crate) static ref TABLE: String = rand::thread_rng()
+        /// Seed of the name of the test table, random unless provided in the
+        /// `DYNARUST_TEST_SEED` environment variable, so that a failing run can be repeated
+        /// against the same table.
+        pub(crate) static ref SEED: u64 = std::env::var("DYNARUST_TEST_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        pub(crate) static ref TABLE: String = table_name(*SEED);
+    }
+
+    /// Random table name generated from a seed.
+    fn table_name(seed: u64) -> String {
+        StdRng::seed_from_u64(seed)
             .sample_iter(&Alphanumeric)
             .take(7)
             .map(char::from)
-            .collect();
+            .collect()
+    }
+
+    #[test]
+    fn names_test_table_from_seed() {
+        assert_eq!(table_name(*SEED), *TABLE);
+        assert_eq!(table_name(7), table_name(7));
+        assert_ne!(table_name(7), table_name(8));
     }
     #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
     pub(crate) struct Nested {
@@ -364,4 +711,230 @@ pub(crate) mod tests {
 
         assert_eq!(expected, updated)
     }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Audit {
+        version: i64,
+        updated_by: String,
+    }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Envelope {
+        tenant: String,
+        #[serde(flatten)]
+        audit: Audit,
+    }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Flattened {
+        id: String,
+        #[serde(flatten)]
+        envelope: Envelope,
+    }
+
+    impl Resource for Flattened {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.envelope.tenant.clone(), self.id.clone())
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Loose {
+        id: String,
+        #[serde(flatten)]
+        extra: HashMap<String, Value>,
+    }
+
+    impl Resource for Loose {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("loose".to_string(), self.id.clone())
+        }
+    }
+
+    #[test]
+    fn handles_nested_flattened_fields() {
+        let resource = Flattened {
+            id: "1".into(),
+            envelope: Envelope {
+                tenant: "handles_nested_flattened_fields".into(),
+                audit: Audit {
+                    version: 1,
+                    updated_by: "foo".into(),
+                },
+            },
+        };
+
+        let mut item = Client::resource_as_object(&resource).unwrap();
+        assert_eq!(item.get("version"), Some(&json!(1)));
+
+        item.insert(PK.into(), json!(resource.envelope.tenant));
+        item.insert(SK.into(), json!(resource.id));
+        let loaded: Flattened = Client::value_as_resource(Value::Object(item)).unwrap();
+        assert_eq!(loaded, resource);
+
+        let (updated, update) = apply_update_request(&loaded, json!({ "version": 2 })).unwrap();
+        assert_eq!(updated.envelope.audit.version, 2);
+        assert_eq!(update.set, vec![("version".to_string(), json!(2))]);
+
+        let err = apply_update_request(&loaded, json!({ "tenant": "other" })).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid request: Cannot update PK and/or SK"
+        );
+    }
+
+    #[test]
+    fn keeps_key_attributes_out_of_flattened_maps() {
+        let item = json!({ PK: "loose", SK: "1", "id": "1", "color": "red" });
+        let loaded: Loose = Client::value_as_resource(item).unwrap();
+        assert_eq!(
+            loaded.extra,
+            HashMap::from([("color".to_string(), json!("red"))])
+        );
+
+        let mut resource = loaded.clone();
+        resource.extra.insert(PK.into(), json!("other"));
+        let object = Client::resource_as_object(&resource).unwrap();
+        assert_eq!(object.get(PK), None);
+
+        let (updated, update) = apply_update_request(&loaded, json!({ "size": 3 })).unwrap();
+        assert_eq!(updated.extra.get("size"), Some(&json!(3)));
+        assert_eq!(update.set, vec![("size".to_string(), json!(3))]);
+    }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Versioned {
+        id: String,
+        full_name: String,
+    }
+
+    impl Resource for Versioned {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("versioned".to_string(), self.id.clone())
+        }
+
+        fn schema_version() -> u32 {
+            2
+        }
+
+        fn migrate(version: u32, mut value: Value) -> Value {
+            if version < 1 {
+                value["name"] = value["title"].take();
+            }
+            value["full_name"] = value["name"].take();
+            value
+        }
+    }
+
+    #[test]
+    fn migrates_old_schema_versions() {
+        let resource = Versioned {
+            id: "1".into(),
+            full_name: "Ada Lovelace".into(),
+        };
+        let item = resource_as_item(&resource).unwrap();
+        assert_eq!(item.get(SCHEMA_VERSION), Some(&json!(2)));
+        let loaded: Versioned = Client::value_as_resource(Value::Object(item)).unwrap();
+        assert_eq!(loaded, resource);
+
+        let v0 = json!({ "id": "1", "title": "Ada Lovelace" });
+        let v1 = json!({ "id": "1", "name": "Ada Lovelace", SCHEMA_VERSION: 1 });
+        for item in [v0, v1] {
+            let loaded: Versioned = Client::value_as_resource(item).unwrap();
+            assert_eq!(loaded, resource);
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+    struct Strict {
+        id: String,
+    }
+
+    impl Resource for Strict {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("strict".to_string(), self.id.clone())
+        }
+
+        fn unknown_attributes() -> UnknownAttributes {
+            UnknownAttributes::Reject
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_attributes() {
+        let item = json!({ PK: "strict", SK: "1", "id": "1" });
+        let loaded: Strict = Client::value_as_resource(item).unwrap();
+        assert_eq!(loaded.id, "1");
+
+        let item = json!({ PK: "strict", SK: "1", "id": "1", "color": "red" });
+        let err = Client::value_as_resource::<Strict>(item).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Item of table {} has attributes that are not fields of the resource: color",
+                *TABLE
+            )
+        );
+    }
+
+    #[test]
+    fn reports_undecodable_items() {
+        let item = json!({ PK: "decode", SK: "1", "pk": "decode", "sk": "1", "int": "NaN" });
+        let err = Client::value_as_resource::<TestResource>(item).unwrap_err();
+        let DynarustError::DecodeError(failure) = &err else {
+            panic!("unexpected error {err}")
+        };
+        assert_eq!(failure.table, *TABLE);
+        assert_eq!((failure.pk.as_str(), failure.sk.as_str()), ("decode", "1"));
+        assert_eq!(failure.attribute.as_deref(), Some("int"));
+        assert_eq!(failure.value, "\"NaN\"");
+
+        let item = HashMap::from([
+            (PK.to_string(), AttributeValue::S("decode".into())),
+            (SK.to_string(), AttributeValue::S("2".into())),
+            ("blob".to_string(), AttributeValue::Ss(vec!["a".into()])),
+        ]);
+        let err = Client::item_as_value(&item).unwrap_err().in_table("Table");
+        assert!(err
+            .to_string()
+            .starts_with("Could not decode item (decode, 2) of table Table, attribute blob"));
+    }
+
+    #[tokio::test]
+    async fn creates_updates_gets_flattened_resource() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = Flattened {
+            id: "1".into(),
+            envelope: Envelope {
+                tenant: "creates_updates_gets_flattened_resource".into(),
+                audit: Audit::default(),
+            },
+        };
+        client.create(&resource).await.unwrap();
+
+        let updated = client
+            .update(&resource, json!({ "version": 2 }))
+            .await
+            .unwrap();
+        let loaded = client.get::<Flattened>(resource.pk_sk()).await.unwrap();
+        assert_eq!(loaded, Some(updated));
+    }
 }
@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::Client;
+
+/// Source of the current time for the operations that stamp or compare timestamps, like the
+/// visibility of queue messages, the windows of rate limits, the expiration of swept items,
+/// audit records and backup schedules. Clients use `SystemClock` unless another one is set
+/// with `Client::with_clock`, usually a `ManualClock` in tests.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The default clock, which reads the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to, so tests can freeze and advance time deterministically
+/// instead of sleeping. Clones share the same time, so a clone can be kept to move the time
+/// of the client it was passed to.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl ManualClock {
+    /// A clock frozen at the provided time.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Moves the clock to the provided time, which can be in the past.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward by the provided duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    /// A clock frozen at the current system time.
+    fn default() -> Self {
+        Self::new(Utc::now())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+impl Client {
+    /// Returns a client that reads the current time from the provided clock instead of the
+    /// system one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let clock = ManualClock::default();
+    ///     let client = Client::local().await.with_clock(clock.clone());
+    ///     client.dequeue::<Email>(&emails).await?;
+    ///     clock.advance(Duration::minutes(5));
+    ///     let redelivered = client.dequeue::<Email>(&emails).await?;
+    /// }
+    /// ```
+    pub fn with_clock(&self, clock: impl Clock + 'static) -> Client {
+        Client {
+            clock: Some(Arc::new(clock)),
+            ..self.clone()
+        }
+    }
+
+    /// The current time according to the clock of the client.
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{Client, ManualClock};
+
+    #[tokio::test]
+    async fn reads_time_from_the_client_clock() {
+        let start = Utc.with_ymd_and_hms(2023, 5, 10, 12, 0, 0).unwrap();
+        let clock = ManualClock::new(start);
+        let client = Client::local().await.with_clock(clock.clone());
+        assert_eq!(client.now(), start);
+
+        clock.advance(Duration::minutes(5));
+        assert_eq!(client.now(), start + Duration::minutes(5));
+        clock.set(start);
+        assert_eq!(client.clone().now(), start);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+use serde_json::Value;
+
+use crate::client::KEY_ATTRIBUTES;
+use crate::{Client, DynarustError};
+
+/// Conversion between the json values in which resources are serialized and the attribute
+/// values stored in dynamo. Clients use `JsonCodec` unless another one is set with
+/// `Client::with_codec`, for example for storing arrays of strings as string sets, or for
+/// reading items written by a legacy format.
+///
+/// The codec is applied to the attributes of every item written and read by the client,
+/// including the ones in transactions, and to the values of update and condition expressions.
+/// Key attributes are always stored as strings.
+pub trait Codec: Send + Sync {
+    /// Converts a json value into the attribute value stored in dynamo.
+    fn encode(&self, value: &Value) -> Result<AttributeValue, DynarustError>;
+    /// Converts a stored attribute value back into json.
+    fn decode(&self, attr: &AttributeValue) -> Result<Value, DynarustError>;
+}
+
+/// The default codec: strings, numbers, booleans and nulls are stored as their dynamo
+/// counterparts, arrays as lists and objects as maps. Sets and binaries are read as arrays of
+/// strings or numbers and as base64 strings respectively.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode(&self, value: &Value) -> Result<AttributeValue, DynarustError> {
+        Client::value2attr(value)
+    }
+
+    fn decode(&self, attr: &AttributeValue) -> Result<Value, DynarustError> {
+        Client::attr2value(attr)
+    }
+}
+
+impl Client {
+    /// Returns a client that converts attribute values with the provided codec instead of the
+    /// default `JsonCodec`. Items written with one codec must be read with a compatible one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// struct SetAwareCodec;
+    ///
+    /// impl Codec for SetAwareCodec {
+    ///     fn encode(&self, value: &Value) -> Result<AttributeValue, DynarustError> {
+    ///         match value.as_array() {
+    ///             Some(arr) if !arr.is_empty() && arr.iter().all(Value::is_string) => Ok(
+    ///                 AttributeValue::Ss(arr.iter().filter_map(|v| v.as_str().map(String::from)).collect()),
+    ///             ),
+    ///             _ => JsonCodec.encode(value),
+    ///         }
+    ///     }
+    ///
+    ///     fn decode(&self, attr: &AttributeValue) -> Result<Value, DynarustError> {
+    ///         JsonCodec.decode(attr)
+    ///     }
+    /// }
+    ///
+    /// async {
+    ///     let client = Client::aws().await.with_codec(SetAwareCodec);
+    /// }
+    /// ```
+    pub fn with_codec(&self, codec: impl Codec + 'static) -> Client {
+        Client {
+            codec: Some(Arc::new(codec)),
+            ..self.clone()
+        }
+    }
+
+    /// Converts a json value into an attribute value with the codec of the client.
+    pub(crate) fn encode_value(&self, value: &Value) -> Result<AttributeValue, DynarustError> {
+        match &self.codec {
+            Some(codec) => codec.encode(value),
+            None => Client::value2attr(value),
+        }
+    }
+
+    /// Converts a raw item into json with the codec of the client, see `Client::item_as_value`.
+    pub(crate) fn decode_item(
+        &self,
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<Value, DynarustError> {
+        match &self.codec {
+            Some(codec) => Client::item_as_value_with(item, |attr| codec.decode(attr)),
+            None => Client::item_as_value(item),
+        }
+    }
+
+    /// Re-encodes with the codec of the client attribute values that were encoded with the
+    /// default one, like the ones built by the transaction functions, which have no client.
+    /// Key attributes are left as they are.
+    pub(crate) fn recode_attributes(
+        &self,
+        attributes: &mut HashMap<String, AttributeValue>,
+    ) -> Result<(), DynarustError> {
+        let Some(codec) = &self.codec else {
+            return Ok(());
+        };
+        for (k, v) in attributes.iter_mut() {
+            if !KEY_ATTRIBUTES.contains(&k.as_str()) {
+                *v = codec.encode(&Client::attr2value(v)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-encodes the attributes and expression values of a transaction item with the codec of
+    /// the client.
+    pub(crate) fn recode_transaction_item(
+        &self,
+        item: &mut TransactWriteItem,
+    ) -> Result<(), DynarustError> {
+        if self.codec.is_none() {
+            return Ok(());
+        }
+        let mut maps = vec![];
+        if let Some(put) = item.put.as_mut() {
+            maps.extend([put.item.as_mut(), put.expression_attribute_values.as_mut()]);
+        }
+        if let Some(update) = item.update.as_mut() {
+            maps.push(update.expression_attribute_values.as_mut());
+        }
+        if let Some(delete) = item.delete.as_mut() {
+            maps.push(delete.expression_attribute_values.as_mut());
+        }
+        if let Some(check) = item.condition_check.as_mut() {
+            maps.push(check.expression_attribute_values.as_mut());
+        }
+        for attributes in maps.into_iter().flatten() {
+            self.recode_attributes(attributes)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_config::SdkConfig;
+    use aws_sdk_dynamodb::model::AttributeValue;
+    use serde_json::{json, Value};
+
+    use crate::client::tests::TestResource;
+    use crate::{Client, ClientBuilder, Codec, DynarustError, JsonCodec, Resource};
+
+    /// Stores arrays of strings as string sets.
+    struct SetCodec;
+
+    impl Codec for SetCodec {
+        fn encode(&self, value: &Value) -> Result<AttributeValue, DynarustError> {
+            match value.as_array() {
+                Some(arr) if !arr.is_empty() && arr.iter().all(Value::is_string) => {
+                    Ok(AttributeValue::Ss(
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect(),
+                    ))
+                }
+                _ => JsonCodec.encode(value),
+            }
+        }
+
+        fn decode(&self, attr: &AttributeValue) -> Result<Value, DynarustError> {
+            match attr {
+                AttributeValue::Ss(set) => Ok(json!(set)),
+                _ => JsonCodec.decode(attr),
+            }
+        }
+    }
+
+    #[test]
+    fn recodes_attributes() {
+        let client = ClientBuilder::new(SdkConfig::builder().build())
+            .build()
+            .with_codec(SetCodec);
+        let mut attributes = [
+            ("PrimaryKey", json!("pk")),
+            ("tags", json!(["a", "b"])),
+            ("int", json!(1)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), Client::value2attr(&v).unwrap()))
+        .collect();
+        client.recode_attributes(&mut attributes).unwrap();
+        assert_eq!(
+            attributes["tags"],
+            AttributeValue::Ss(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(attributes["int"], AttributeValue::N("1".into()));
+        assert_eq!(attributes["PrimaryKey"], AttributeValue::S("pk".into()));
+    }
+
+    #[tokio::test]
+    async fn writes_with_custom_codecs() {
+        let client = Client::local().await.with_codec(SetCodec);
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "writes_with_custom_codecs".to_string(),
+            sk: "1".to_string(),
+            string_arr: vec!["a".into(), "b".into()],
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let stored = client
+            .client
+            .get_item()
+            .table_name(TestResource::table())
+            .key("PrimaryKey", AttributeValue::S(resource.pk.clone()))
+            .key("SecondaryKey", AttributeValue::S(resource.sk.clone()))
+            .send()
+            .await
+            .unwrap();
+        assert!(matches!(
+            stored.item().unwrap()["string_arr"],
+            AttributeValue::Ss(_)
+        ));
+        let read = client.get::<TestResource>(resource.pk_sk()).await.unwrap();
+        assert_eq!(read, Some(resource));
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::model::{AttributeValue, ItemCollectionMetrics, ReturnItemCollectionMetrics};
+
+use crate::client::PK;
+use crate::Client;
+
+/// Size of the item collection of a PrimaryKey affected by a write. Item collections of
+/// tables with local secondary indexes cannot grow beyond 10GB.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionMetrics {
+    pub table: String,
+    /// PrimaryKey of the item collection, as stored in dynamo.
+    pub pk: String,
+    /// lower and upper bounds of the size estimate of the collection, in GB.
+    pub size_estimate_range_gb: (f64, f64),
+}
+
+pub(crate) type CollectionMetricsHandler = Arc<dyn Fn(CollectionMetrics) + Send + Sync>;
+
+impl Client {
+    /// Returns a client that requests the item collection metrics of every write, including
+    /// transactions and bulk loads, and passes them to the provided handler. Dynamo only returns
+    /// them for tables with local secondary indexes.
+    ///
+    /// # arguments
+    ///
+    /// * `handler` - called with the metrics of every affected item collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let client = client.with_collection_metrics(|metrics| {
+    ///     if metrics.size_estimate_range_gb.1 > 8.0 {
+    ///         log::warn!("item collection {} is close to the size limit", metrics.pk);
+    ///     }
+    /// });
+    /// ```
+    pub fn with_collection_metrics(
+        &self,
+        handler: impl Fn(CollectionMetrics) + Send + Sync + 'static,
+    ) -> Client {
+        Client {
+            collection_metrics: Some(Arc::new(handler)),
+            ..self.clone()
+        }
+    }
+
+    /// The value for the ReturnItemCollectionMetrics parameter of writes.
+    pub(crate) fn return_collection_metrics(&self) -> Option<ReturnItemCollectionMetrics> {
+        self.collection_metrics
+            .is_some()
+            .then_some(ReturnItemCollectionMetrics::Size)
+    }
+
+    /// Passes the metrics returned by a single item write to the handler.
+    pub(crate) fn report_collection_metrics(
+        &self,
+        table: &str,
+        metrics: Option<&ItemCollectionMetrics>,
+    ) {
+        if let (Some(handler), Some(metrics)) = (&self.collection_metrics, metrics) {
+            if let Some(metrics) = collection_metrics(table, metrics) {
+                handler(metrics)
+            }
+        }
+    }
+
+    /// Passes the metrics returned by a multi item write to the handler.
+    pub(crate) fn report_all_collection_metrics(
+        &self,
+        metrics: Option<&HashMap<String, Vec<ItemCollectionMetrics>>>,
+    ) {
+        for (table, metrics) in metrics.into_iter().flatten() {
+            for metrics in metrics {
+                self.report_collection_metrics(table, Some(metrics));
+            }
+        }
+    }
+}
+
+fn collection_metrics(table: &str, metrics: &ItemCollectionMetrics) -> Option<CollectionMetrics> {
+    let pk = match metrics.item_collection_key()?.get(PK)? {
+        AttributeValue::S(pk) => pk.clone(),
+        _ => return None,
+    };
+    let range = metrics.size_estimate_range_gb()?;
+    Some(CollectionMetrics {
+        table: table.to_string(),
+        pk,
+        size_estimate_range_gb: (*range.first()?, *range.get(1)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_collection_metrics_to_handler() {
+        let reported = Arc::new(Mutex::new(vec![]));
+        let sink = reported.clone();
+        let client = Client::local_on_port(12345)
+            .await
+            .with_collection_metrics(move |metrics| sink.lock().unwrap().push(metrics));
+        assert_eq!(
+            client.return_collection_metrics(),
+            Some(ReturnItemCollectionMetrics::Size)
+        );
+
+        let metrics = ItemCollectionMetrics::builder()
+            .item_collection_key(PK, AttributeValue::S("user#1".into()))
+            .size_estimate_range_gb(1.0)
+            .size_estimate_range_gb(2.0)
+            .build();
+        client.report_all_collection_metrics(Some(&HashMap::from([(
+            "Users".to_string(),
+            vec![metrics],
+        )])));
+
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![CollectionMetrics {
+                table: "Users".into(),
+                pk: "user#1".into(),
+                size_estimate_range_gb: (1.0, 2.0),
+            }]
+        );
+    }
+}
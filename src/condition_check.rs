@@ -1,16 +1,84 @@
 use std::collections::HashMap;
 
-use aws_sdk_dynamodb::client::fluent_builders::{DeleteItem, PutItem, UpdateItem};
+use aws_sdk_dynamodb::client::fluent_builders::{PutItem, UpdateItem};
 use aws_sdk_dynamodb::model::{
     condition_check, delete, put, update, AttributeValue, TransactWriteItem,
 };
-use rand::distributions::Alphanumeric;
-use rand::Rng;
+
+use serde_json::Value;
 
 use crate::client::{PK, SK};
-use crate::{DynamoOperator, Resource};
+use crate::{Client, DynamoOperator, DynarustError, Resource, TransactionContext};
+
+/// Predicate on an attribute for `condition_check`, covering both the comparison operators and
+/// the functions of dynamo condition expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// the attribute compared with the operator against the value.
+    Compare(DynamoOperator, Value),
+    /// the attribute is between both values, inclusive.
+    Between(Value, Value),
+    /// the attribute is equal to any of the values.
+    In(Vec<Value>),
+    /// the string attribute starts with the prefix.
+    BeginsWith(String),
+    /// the string attribute contains the substring, or the set or list attribute contains the
+    /// element.
+    Contains(Value),
+    /// the size of the attribute compared with the operator against the number, see
+    /// `condition_check_size`.
+    Size(DynamoOperator, i64),
+    /// the attribute exists.
+    Exists,
+    /// the attribute does not exist.
+    NotExists,
+}
 
-#[derive(Default)]
+impl Condition {
+    /// The expression that checks the condition on the attribute behind the placeholder, along
+    /// with its values, named from `:cond0`.
+    fn expression(
+        self,
+        attr: &str,
+    ) -> Result<(String, HashMap<String, AttributeValue>), DynarustError> {
+        let mut values = HashMap::new();
+        let mut value = |v: &Value| -> Result<String, DynarustError> {
+            let placeholder = format!(":cond{}", values.len());
+            values.insert(placeholder.clone(), Client::value2attr(v)?);
+            Ok(placeholder)
+        };
+        let expression = match self {
+            Condition::Compare(operator, v) => format!("{attr} {operator} {}", value(&v)?),
+            Condition::Between(low, high) => {
+                format!("{attr} between {} and {}", value(&low)?, value(&high)?)
+            }
+            Condition::In(options) => {
+                if options.is_empty() {
+                    return Err(DynarustError::InvalidRequestError(
+                        "The in condition needs at least one value".into(),
+                    ));
+                }
+                let mut placeholders = vec![];
+                for v in options.iter() {
+                    placeholders.push(value(v)?);
+                }
+                format!("{attr} in ({})", placeholders.join(", "))
+            }
+            Condition::BeginsWith(prefix) => {
+                format!("begins_with({attr}, {})", value(&Value::String(prefix))?)
+            }
+            Condition::Contains(v) => format!("contains({attr}, {})", value(&v)?),
+            Condition::Size(operator, size) => {
+                format!("size({attr}) {operator} {}", value(&Value::from(size))?)
+            }
+            Condition::Exists => format!("attribute_exists({attr})"),
+            Condition::NotExists => format!("attribute_not_exists({attr})"),
+        };
+        Ok((expression, values))
+    }
+}
+
+#[derive(Default, Clone)]
 pub struct ConditionCheckInfo {
     expression: String,
     names: HashMap<String, String>,
@@ -18,6 +86,16 @@ pub struct ConditionCheckInfo {
 }
 
 impl ConditionCheckInfo {
+    /// The condition expression, empty if there is nothing to check.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// whether this condition check has no expression, so it always passes.
+    pub fn is_empty(&self) -> bool {
+        self.expression.is_empty()
+    }
+
     pub(crate) fn condition_expression(mut self, input: impl Into<String>) -> Self {
         self.expression = input.into();
         self
@@ -41,20 +119,104 @@ impl ConditionCheckInfo {
         self
     }
 
-    pub(crate) fn merge(mut self, others: Vec<ConditionCheckInfo>) -> Self {
+    /// Negates the check, so it passes when the original one fails. An empty check always
+    /// passes, so it is left as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     // children can be deleted, but never the root record of the partition
+    ///     let not_root = dynarust::condition_check_sk_begins_with("root").negated();
+    /// }
+    /// ```
+    pub fn negated(mut self) -> Self {
+        if !self.expression.is_empty() {
+            self.expression = format!("not ({})", self.expression);
+        }
+        self
+    }
+
+    /// Renames the placeholders defined by this check, but the key ones, to `#condN` and
+    /// `:condN`, numbered from `start` in order of appearance in the expression. Returns the
+    /// next free number.
+    fn renumbered(mut self, start: usize) -> (Self, usize) {
+        let mut renames: HashMap<String, String> = HashMap::new();
+        let expression = rename_placeholders(&self.expression, |prefix, key| {
+            let defined = match prefix {
+                '#' => self.names.contains_key(&format!("#{key}")),
+                _ => self.values.contains_key(&format!(":{key}")),
+            };
+            if !defined || key == "pk" || key == "sk" {
+                return None;
+            }
+            let next = format!("cond{}", start + renames.len());
+            let renamed = renames.entry(key.to_string()).or_insert(next);
+            Some(format!("{prefix}{renamed}"))
+        });
+        let rename = |placeholder: String| match renames.get(&placeholder[1..]) {
+            Some(key) => format!("{}{key}", &placeholder[..1]),
+            None => placeholder,
+        };
+        self.names = self
+            .names
+            .into_iter()
+            .map(|(k, v)| (rename(k), v))
+            .collect();
+        self.values = self
+            .values
+            .into_iter()
+            .map(|(k, v)| (rename(k), v))
+            .collect();
+        self.expression = expression;
+        (self, start + renames.len())
+    }
+
+    /// Points the key attributes referenced by the check to the real key attributes of the table.
+    pub(crate) fn externalized(mut self, client: &Client, table: &str) -> Self {
+        client.externalize_names(table, &mut self.names);
+        self
+    }
+
+    /// Re-encodes the values of the check with the codec of the client.
+    pub(crate) fn recoded(mut self, client: &Client) -> Result<Self, DynarustError> {
+        client.recode_attributes(&mut self.values)?;
+        Ok(self)
+    }
+
+    /// Splits the check into its expression, attribute names and attribute values.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn into_parts(
+        self,
+    ) -> (
+        String,
+        HashMap<String, String>,
+        HashMap<String, AttributeValue>,
+    ) {
+        (self.expression, self.names, self.values)
+    }
+
+    /// Joins the checks with `and`. The placeholders of the merged checks are renumbered after
+    /// the ones of this check, so they never clash and the result is always the same.
+    pub(crate) fn merge(self, others: Vec<ConditionCheckInfo>) -> Self {
+        let (mut merged, mut next) = self.renumbered(0);
         for other in others {
-            self.names.extend(other.names);
-            self.values.extend(other.values);
+            let (other, other_next) = other.renumbered(next);
+            next = other_next;
+            merged.names.extend(other.names);
+            merged.values.extend(other.values);
 
-            if self.expression.is_empty() {
-                self.expression = other.expression;
+            if other.expression.is_empty() {
                 continue;
-            } else if !self.expression.starts_with('(') || !self.expression.ends_with(')') {
-                self.expression = format!("({})", self.expression)
+            } else if merged.expression.is_empty() {
+                merged.expression = other.expression;
+                continue;
+            } else if !merged.expression.starts_with('(') || !merged.expression.ends_with(')') {
+                merged.expression = format!("({})", merged.expression)
             }
-            self.expression += &format!(" and ({})", other.expression);
+            merged.expression += &format!(" and ({})", other.expression);
         }
-        self
+        merged
     }
 
     pub(crate) fn dump_in_condition_check(
@@ -143,28 +305,35 @@ impl ConditionCheckInfo {
         }
         builder
     }
+}
 
-    pub(crate) fn dump_in_delete_item(self, mut builder: DeleteItem) -> DeleteItem {
-        if self.expression.is_empty() {
-            return builder;
+/// Replaces the `#name` and `:name` placeholders of an expression for which `rename` returns
+/// a replacement, which includes the prefix. Whole placeholders are matched, so `#cond1` is
+/// never mistaken for `#cond10`.
+pub(crate) fn rename_placeholders(
+    expression: &str,
+    mut rename: impl FnMut(char, &str) -> Option<String>,
+) -> String {
+    let mut result = String::with_capacity(expression.len());
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '#' && c != ':' {
+            result.push(c);
+            continue;
         }
-        builder = builder.condition_expression(&self.expression);
-        for (k, v) in self.names {
-            builder = builder.expression_attribute_names(k, v);
+        let mut key = String::new();
+        while let Some(next) = chars.next_if(|n| n.is_ascii_alphanumeric() || *n == '_') {
+            key.push(next);
         }
-        for (k, v) in self.values {
-            builder = builder.expression_attribute_values(k, v);
+        match rename(c, &key) {
+            Some(replacement) => result.push_str(&replacement),
+            None => {
+                result.push(c);
+                result.push_str(&key);
+            }
         }
-        builder
     }
-}
-
-fn seed() -> String {
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(7)
-        .map(char::from)
-        .collect()
+    result
 }
 
 /// Creates a condition check that checks if the referenced resource exists.
@@ -192,14 +361,14 @@ pub fn condition_check_not_exists() -> ConditionCheckInfo {
 /// * `operator` - The operator for comparing the field to the value.
 /// * `value` - The numeric value.
 pub fn condition_check_number(
-    attr: &str,
+    attr: impl AsRef<str>,
     operator: DynamoOperator,
     value: i64,
 ) -> ConditionCheckInfo {
-    let key = seed();
+    let key = "cond0";
     ConditionCheckInfo::default()
         .condition_expression(format!("#{} {} :{}", key, operator, key))
-        .expression_attribute_names(format!("#{}", key), attr)
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
         .expression_attribute_values(format!(":{}", key), AttributeValue::N(value.to_string()))
 }
 
@@ -210,17 +379,180 @@ pub fn condition_check_number(
 /// * `operator` - The operator for comparing the field to the value.
 /// * `value` - The string value.
 pub fn condition_check_string(
-    attr: &str,
+    attr: impl AsRef<str>,
     operator: DynamoOperator,
     value: &str,
 ) -> ConditionCheckInfo {
-    let key = seed();
+    let key = "cond0";
     ConditionCheckInfo::default()
         .condition_expression(format!("#{} {} :{}", key, operator, key))
-        .expression_attribute_names(format!("#{}", key), attr)
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
         .expression_attribute_values(format!(":{}", key), AttributeValue::S(value.to_string()))
 }
 
+/// Creates a condition check that passes if a string set attribute contains the provided
+/// member. It also works with list attributes, checking if any of the elements is the member.
+///
+/// # arguments
+/// * `attr` - The set field in the resource that should be checked.
+/// * `member` - The string that must be in the set.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     client.update_with_checks(
+///         &team,
+///         json!({ "name": "new name" }),
+///         vec![dynarust::condition_check_set_contains("roles", "admin")],
+///     ).await?;
+/// }
+/// ```
+pub fn condition_check_set_contains(attr: impl AsRef<str>, member: &str) -> ConditionCheckInfo {
+    let key = "cond0";
+    ConditionCheckInfo::default()
+        .condition_expression(format!("contains(#{}, :{})", key, key))
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
+        .expression_attribute_values(format!(":{}", key), AttributeValue::S(member.to_string()))
+}
+
+/// Creates a condition check that passes if a number set attribute contains the provided
+/// member. It also works with list attributes, checking if any of the elements is the member.
+///
+/// # arguments
+/// * `attr` - The set field in the resource that should be checked.
+/// * `member` - The number that must be in the set.
+pub fn condition_check_set_contains_number(
+    attr: impl AsRef<str>,
+    member: i64,
+) -> ConditionCheckInfo {
+    let key = "cond0";
+    ConditionCheckInfo::default()
+        .condition_expression(format!("contains(#{}, :{})", key, key))
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
+        .expression_attribute_values(format!(":{}", key), AttributeValue::N(member.to_string()))
+}
+
+/// Creates a condition check that passes if a string attribute starts with the provided prefix.
+///
+/// # arguments
+/// * `attr` - The field in the resource that should be checked.
+/// * `prefix` - The prefix that the field must start with.
+pub fn condition_check_begins_with(attr: impl AsRef<str>, prefix: &str) -> ConditionCheckInfo {
+    let key = "cond0";
+    ConditionCheckInfo::default()
+        .condition_expression(format!("begins_with(#{}, :{})", key, key))
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
+        .expression_attribute_values(format!(":{}", key), AttributeValue::S(prefix.to_string()))
+}
+
+/// Creates a condition check that passes if the SecondaryKey of the item starts with the
+/// provided prefix. Unlike passing the key attribute to `condition_check_begins_with`, it
+/// references the key the same way dynarust does, so it also works on tables with their own
+/// key attribute names.
+///
+/// # arguments
+/// * `prefix` - The prefix that the SecondaryKey must start with.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     // only children of the order can be deleted this way
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_delete_with_checks::<OrderLine>(
+///         line.pk_sk(),
+///         vec![dynarust::condition_check_sk_begins_with("line#")],
+///         &mut context,
+///     )?;
+/// }
+/// ```
+pub fn condition_check_sk_begins_with(prefix: &str) -> ConditionCheckInfo {
+    ConditionCheckInfo::default()
+        .condition_expression("begins_with(#sk, :cond0)")
+        .expression_attribute_names("#sk", SK)
+        .expression_attribute_values(":cond0", AttributeValue::S(prefix.to_string()))
+}
+
+/// Creates a condition check for checking the size of an attribute: the length of a string,
+/// the number of bytes of a binary or the number of elements of a set, list or map.
+///
+/// # arguments
+/// * `attr` - The field in the resource that should be checked.
+/// * `operator` - The operator for comparing the size to the value.
+/// * `size` - The size against which the attribute is compared.
+pub fn condition_check_size(
+    attr: impl AsRef<str>,
+    operator: DynamoOperator,
+    size: i64,
+) -> ConditionCheckInfo {
+    let key = "cond0";
+    ConditionCheckInfo::default()
+        .condition_expression(format!("size(#{}) {} :{}", key, operator, key))
+        .expression_attribute_names(format!("#{}", key), attr.as_ref())
+        .expression_attribute_values(format!(":{}", key), AttributeValue::N(size.to_string()))
+}
+
+/// Creates a condition check for checking the length of the SecondaryKey of the item,
+/// referencing the key the same way dynarust does.
+///
+/// # arguments
+/// * `operator` - The operator for comparing the length to the value.
+/// * `size` - The length against which the SecondaryKey is compared.
+pub fn condition_check_sk_size(operator: DynamoOperator, size: i64) -> ConditionCheckInfo {
+    ConditionCheckInfo::default()
+        .condition_expression(format!("size(#sk) {} :cond0", operator))
+        .expression_attribute_names("#sk", SK)
+        .expression_attribute_values(":cond0", AttributeValue::N(size.to_string()))
+}
+
+/// Creates a condition check that passes if the attribute matches the condition, which can be
+/// a comparison or any of the functions supported by dynamo.
+///
+/// # arguments
+/// * `attr` - The field in the resource that should be checked.
+/// * `condition` - The condition that the field must match.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     client.update_with_checks(
+///         &team,
+///         json!({ "name": "new name" }),
+///         vec![
+///             dynarust::condition_check("name", Condition::BeginsWith("team-".into()))?,
+///             dynarust::condition_check("members", Condition::Size(DynamoOperator::Ls, 10))?,
+///         ],
+///     ).await?;
+/// }
+/// ```
+pub fn condition_check(
+    attr: impl AsRef<str>,
+    condition: Condition,
+) -> Result<ConditionCheckInfo, DynarustError> {
+    let (expression, values) = condition.expression("#cond0")?;
+    let mut check = ConditionCheckInfo::default()
+        .condition_expression(expression)
+        .expression_attribute_names("#cond0", attr.as_ref());
+    check.values = values;
+    Ok(check)
+}
+
+/// Creates a condition check that passes if the SecondaryKey of the item matches the
+/// condition, referencing the key the same way dynarust does.
+///
+/// # arguments
+/// * `condition` - The condition that the SecondaryKey must match.
+pub fn condition_check_sk(condition: Condition) -> Result<ConditionCheckInfo, DynarustError> {
+    let (expression, values) = condition.expression("#sk")?;
+    let mut check = ConditionCheckInfo::default()
+        .condition_expression(expression)
+        .expression_attribute_names("#sk", SK);
+    check.values = values;
+    Ok(check)
+}
+
 /// Takes a Condition check and adds it as a standalone check to a transaction.
 /// Useful for when a condition check must be made in a transaction but any of previous the items
 /// in the transaction refer to the item that wants to be checked.
@@ -240,15 +572,15 @@ pub fn condition_check_string(
 ///         ("non".into(), "existing".into()),
 ///         dynarust::condition_check_not_exists(),
 ///         &mut context,
-///     );
+///     )?;
 ///     let err = client.execute_transaction(context).await?;
 /// }
 /// ```
 pub fn transact_condition_check<T: Resource>(
     (pk, sk): (String, String),
     info: ConditionCheckInfo,
-    transaction_context: &mut Vec<TransactWriteItem>,
-) {
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
     let builder = condition_check::Builder::default()
         .table_name(T::table())
         .key(PK, AttributeValue::S(pk))
@@ -256,15 +588,113 @@ pub fn transact_condition_check<T: Resource>(
 
     let check = info.dump_in_condition_check(builder).build();
 
-    transaction_context.push(TransactWriteItem::builder().condition_check(check).build());
+    transaction_context.push(TransactWriteItem::builder().condition_check(check).build())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::client::tests::TestResource;
-    use crate::condition_check::{condition_check_exists, transact_condition_check};
+    use serde_json::json;
+
+    use crate::condition_check::{
+        condition_check, condition_check_exists, condition_check_number,
+        condition_check_set_contains, condition_check_size, condition_check_sk,
+        condition_check_sk_begins_with, condition_check_sk_size, condition_check_string,
+        transact_condition_check,
+    };
     use crate::create::transact_create;
-    use crate::{begin_transaction, Client};
+    use crate::{
+        assert_conditional_failure, begin_transaction, transact_delete_with_checks, Client,
+        Condition, DynamoOperator, Resource,
+    };
+
+    #[test]
+    fn names_merged_placeholders_deterministically() {
+        let merge = || {
+            condition_check_exists().merge(vec![
+                condition_check_number("int", DynamoOperator::Gt, 1),
+                condition_check_string("string", DynamoOperator::Eq, "a"),
+                condition_check_number("int", DynamoOperator::Ls, 9)
+                    .merge(vec![condition_check_set_contains("set", "b")]),
+            ])
+        };
+        let check = merge();
+        assert_eq!(
+            check.expression(),
+            "(attribute_exists(#pk) and attribute_exists(#sk)) and (#cond0 > :cond0) \
+             and (#cond1 = :cond1) and ((#cond2 < :cond2) and (contains(#cond3, :cond3)))"
+        );
+        assert_eq!(check.expression(), merge().expression());
+        let (_, names, values) = check.into_parts();
+        assert_eq!(names.len(), 6);
+        assert_eq!(names["#cond3"], "set");
+        assert_eq!(values.len(), 4);
+    }
+
+    #[test]
+    fn references_key_attributes_in_functions() {
+        let check = condition_check_exists().merge(vec![
+            condition_check_sk_begins_with("child#").negated(),
+            condition_check_sk_size(DynamoOperator::Gt, 3),
+            condition_check_size("string_arr", DynamoOperator::LsEq, 2),
+        ]);
+        assert_eq!(
+            check.expression(),
+            "(attribute_exists(#pk) and attribute_exists(#sk)) \
+             and (not (begins_with(#sk, :cond0))) and (size(#sk) > :cond1) \
+             and (size(#cond2) <= :cond2)"
+        );
+        let (_, names, values) = check.into_parts();
+        assert_eq!(names.len(), 3);
+        assert_eq!(values.len(), 3);
+    }
+
+    #[test]
+    fn builds_checks_from_conditions() {
+        let check = condition_check("int", Condition::In(vec![json!(1), json!(2)]))
+            .unwrap()
+            .merge(vec![
+                condition_check("string", Condition::BeginsWith("a".into())).unwrap(),
+                condition_check_sk(Condition::Between(json!("a"), json!("b"))).unwrap(),
+                condition_check("nullable", Condition::NotExists).unwrap(),
+            ]);
+        assert_eq!(
+            check.expression(),
+            "(#cond0 in (:cond0, :cond1)) and (begins_with(#cond2, :cond2)) \
+             and (#sk between :cond3 and :cond4) and (attribute_not_exists(#cond5))"
+        );
+        let (_, names, values) = check.into_parts();
+        assert_eq!(names.len(), 4);
+        assert_eq!(values.len(), 5);
+        assert!(condition_check("int", Condition::In(vec![])).is_err());
+    }
+
+    #[tokio::test]
+    async fn deletes_only_children() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = |sk: &str| TestResource {
+            pk: "deletes_only_children".to_string(),
+            sk: sk.to_string(),
+            ..Default::default()
+        };
+        for sk in ["root", "child#1"] {
+            client.force_create(&resource(sk)).await.unwrap();
+        }
+
+        for (sk, deleted) in [("root", false), ("child#1", true)] {
+            let mut context = begin_transaction();
+            transact_delete_with_checks::<TestResource>(
+                resource(sk).pk_sk(),
+                vec![condition_check_sk_begins_with("root").negated()],
+                &mut context,
+            )
+            .unwrap();
+            let result = client.execute_transaction(context).await;
+            assert_eq!(result.is_ok(), deleted);
+        }
+    }
 
     #[tokio::test]
     async fn creates_only_if_other_exists() {
@@ -283,9 +713,43 @@ mod tests {
             ("non".into(), "existing".into()),
             condition_check_exists(),
             &mut context,
-        );
+        )
+        .unwrap();
         let err = client.execute_transaction(context).await.unwrap_err();
 
-        assert_eq!(err.to_string(), "Transaction cancelled, please refer cancellation reasons for specific reasons [None, ConditionalCheckFailed]")
+        assert_conditional_failure(&err, 1);
+    }
+
+    #[tokio::test]
+    async fn updates_only_if_set_contains_member() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = TestResource {
+            pk: "updates_only_if_set_contains_member".to_string(),
+            sk: "1".to_string(),
+            string_arr: vec!["admin".to_string()],
+            ..Default::default()
+        };
+        client.create(&resource).await.unwrap();
+
+        client
+            .update_with_checks(
+                &resource,
+                json!({ "int": 1 }),
+                vec![condition_check_set_contains("string_arr", "admin")],
+            )
+            .await
+            .unwrap();
+
+        let err = client
+            .update_with_checks(
+                &resource,
+                json!({ "int": 2 }),
+                vec![condition_check_set_contains("string_arr", "owner")],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "The conditional request failed")
     }
 }
@@ -0,0 +1,219 @@
+use std::time::{Duration, Instant};
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::{Client, DynarustError, Resource};
+
+/// Options of a `ConfigStore`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOptions {
+    /// time after which the cached configuration is read again, default is 60 seconds.
+    pub refresh_interval: Duration,
+    /// attribute of the configuration item that changes on every write, like a version number
+    /// or an update timestamp. If provided, refreshes first read only this attribute, and the
+    /// whole item is read only if it changed, which is cheaper for large items. By default the
+    /// whole item is read on every refresh.
+    pub version_attribute: Option<String>,
+}
+
+impl Default for ConfigOptions {
+    fn default() -> Self {
+        Self {
+            refresh_interval: Duration::from_secs(60),
+            version_attribute: None,
+        }
+    }
+}
+
+/// The configuration as it was last read.
+struct CachedConfig<T> {
+    config: Option<T>,
+    version: Option<Value>,
+    read_at: Instant,
+}
+
+/// In-process cache of a single configuration item, like the feature flags of an application,
+/// which is read from dynamo again once the refresh interval elapses. Build it with
+/// `Client::config_store` and share it, for example behind an `Arc`.
+pub struct ConfigStore<T> {
+    client: Client,
+    pk_sk: (String, String),
+    options: ConfigOptions,
+    cached: RwLock<Option<CachedConfig<T>>>,
+}
+
+impl Client {
+    /// Returns a store that caches the configuration item identified by the provided key.
+    /// Nothing is read until the configuration is first requested.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the configuration item.
+    /// * `options` - optional cache options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let flags = client.config_store::<FeatureFlags>(
+    ///         ("config".into(), "flags".into()),
+    ///         Some(ConfigOptions {
+    ///             refresh_interval: Duration::from_secs(30),
+    ///             version_attribute: Some("version".into()),
+    ///         }),
+    ///     );
+    ///     if flags.get_current().await?.is_some_and(|flags| flags.new_checkout) {
+    ///         println!("new checkout enabled");
+    ///     }
+    /// }
+    /// ```
+    pub fn config_store<T: Resource + DeserializeOwned + Clone>(
+        &self,
+        pk_sk: (String, String),
+        options: Option<ConfigOptions>,
+    ) -> ConfigStore<T> {
+        ConfigStore {
+            client: self.clone(),
+            pk_sk,
+            options: options.unwrap_or_default(),
+            cached: RwLock::new(None),
+        }
+    }
+}
+
+impl<T: Resource + DeserializeOwned + Clone> ConfigStore<T> {
+    /// Returns the cached configuration, reading it first if it was never read or if the
+    /// refresh interval elapsed. Returns None if the configuration item does not exist.
+    pub async fn get_current(&self) -> Result<Option<T>, DynarustError> {
+        if let Some(cached) = self.cached.read().await.as_ref() {
+            if cached.read_at.elapsed() < self.options.refresh_interval {
+                return Ok(cached.config.clone());
+            }
+        }
+        self.reload(false).await
+    }
+
+    /// Reads the configuration right away, regardless of the refresh interval, and returns it.
+    /// Handy after writing it, or when notified that it changed. If reading fails, the error
+    /// is returned and the cached configuration is kept.
+    pub async fn refresh(&self) -> Result<Option<T>, DynarustError> {
+        self.reload(true).await
+    }
+
+    async fn reload(&self, force: bool) -> Result<Option<T>, DynarustError> {
+        let mut cached = self.cached.write().await;
+        // another caller might have reloaded it while this one waited for the lock.
+        if let Some(current) = cached.as_ref() {
+            if !force && current.read_at.elapsed() < self.options.refresh_interval {
+                return Ok(current.config.clone());
+            }
+        }
+
+        let version = match &self.options.version_attribute {
+            Some(attribute) => Some(self.read_version(attribute).await?),
+            None => None,
+        };
+        if let (Some(version), Some(current)) = (&version, cached.as_mut()) {
+            if current.version.as_ref() == Some(version) {
+                current.read_at = Instant::now();
+                return Ok(current.config.clone());
+            }
+        }
+
+        let config = self.client.get::<T>(self.pk_sk.clone()).await?;
+        *cached = Some(CachedConfig {
+            config: config.clone(),
+            version,
+            read_at: Instant::now(),
+        });
+        Ok(config)
+    }
+
+    /// Reads only the version attribute of the configuration item, which is null if the item
+    /// or the attribute do not exist.
+    async fn read_version(&self, attribute: &str) -> Result<Value, DynarustError> {
+        let table = T::table();
+        let (pk, sk) = self.pk_sk.clone();
+        let (pk_attr, sk_attr) = self.client.key_names(&table);
+        let result = self
+            .client
+            .dynamo(&table)
+            .get_item()
+            .table_name(&table)
+            .key(pk_attr, AttributeValue::S(self.client.scope_pk(pk)))
+            .key(sk_attr, AttributeValue::S(sk))
+            .projection_expression("#version")
+            .expression_attribute_names("#version", attribute)
+            .consistent_read(self.client.consistent_read(None))
+            .send()
+            .await?;
+        match result.item().and_then(|item| item.get(attribute)) {
+            Some(version) => Client::attr2value(version),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, ConfigOptions, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Flags {
+        name: String,
+        version: u32,
+        new_checkout: bool,
+    }
+
+    impl Resource for Flags {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("caches_configuration".into(), self.name.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_configuration() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let flags = Flags {
+            name: "flags".into(),
+            version: 1,
+            new_checkout: false,
+        };
+        client.force_create(&flags).await.unwrap();
+
+        let store = client.config_store::<Flags>(
+            flags.pk_sk(),
+            Some(ConfigOptions {
+                refresh_interval: Duration::from_secs(3600),
+                version_attribute: Some("version".into()),
+            }),
+        );
+        assert_eq!(store.get_current().await.unwrap(), Some(flags.clone()));
+
+        let updated = client
+            .update(&flags, json!({ "version": 2, "new_checkout": true }))
+            .await
+            .unwrap();
+        assert_eq!(store.get_current().await.unwrap(), Some(flags));
+        assert_eq!(store.refresh().await.unwrap(), Some(updated.clone()));
+        assert_eq!(store.get_current().await.unwrap(), Some(updated));
+
+        let missing =
+            client.config_store::<Flags>(("caches_configuration".into(), "missing".into()), None);
+        assert_eq!(missing.get_current().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{Client, DynarustError, ListOptions};
+
+/// Consistency of the reads performed by `get`, `batch_get`, `list` and index queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadConsistency {
+    /// reads might not reflect the writes of the last second, at half the read capacity cost.
+    #[default]
+    Eventual,
+    /// reads reflect every write that succeeded before them. Global secondary indexes do not
+    /// support them.
+    Strong,
+}
+
+impl Client {
+    /// Returns a client whose reads have the provided consistency by default. Queries on global
+    /// secondary indexes are always eventually consistent, as dynamo does not support strongly
+    /// consistent reads on them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let client = client.with_read_consistency(ReadConsistency::Strong);
+    ///     let car = client.get::<Car>(("car".into(), "1".into())).await?;
+    /// }
+    /// ```
+    pub fn with_read_consistency(&self, consistency: ReadConsistency) -> Client {
+        Client {
+            read_consistency: consistency,
+            ..self.clone()
+        }
+    }
+
+    /// The value for the ConsistentRead parameter of gets and of queries on the base table.
+    pub(crate) fn consistent_read(&self, options: Option<&ListOptions>) -> bool {
+        let consistency = options
+            .and_then(|options| options.consistency)
+            .unwrap_or(self.read_consistency);
+        consistency == ReadConsistency::Strong
+    }
+
+    /// The value for the ConsistentRead parameter of queries on global secondary indexes,
+    /// failing if the options explicitly request strongly consistent reads.
+    pub(crate) fn index_consistent_read(
+        &self,
+        options: &ListOptions,
+    ) -> Result<bool, DynarustError> {
+        match options.consistency {
+            Some(ReadConsistency::Strong) => Err(DynarustError::InvalidRequestError(
+                "Strongly consistent reads are not supported on global secondary indexes".into(),
+            )),
+            _ => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_config::SdkConfig;
+
+    use crate::{ClientBuilder, ListOptions, ReadConsistency};
+
+    #[test]
+    fn resolves_read_consistency() {
+        let client = ClientBuilder::new(SdkConfig::builder().build()).build();
+        let strong = ListOptions {
+            consistency: Some(ReadConsistency::Strong),
+            ..Default::default()
+        };
+        assert!(!client.consistent_read(None));
+        assert!(client.consistent_read(Some(&strong)));
+        assert!(client.index_consistent_read(&strong).is_err());
+
+        let client = client.with_read_consistency(ReadConsistency::Strong);
+        let eventual = ListOptions {
+            consistency: Some(ReadConsistency::Eventual),
+            ..Default::default()
+        };
+        assert!(client.consistent_read(None));
+        assert!(!client.consistent_read(Some(&eventual)));
+        assert!(!client
+            .index_consistent_read(&ListOptions::default())
+            .unwrap());
+    }
+}
@@ -0,0 +1,265 @@
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnValue};
+use serde_json::{json, Value};
+
+use crate::condition_check::{condition_check_exists, ConditionCheckInfo};
+use crate::stats::Operation;
+use crate::update::update_item;
+use crate::{AuditOperation, Client, DynarustError, ItemUpdate, Resource};
+
+/// Bound that a counter must respect after being modified.
+enum Bound {
+    Max(i64),
+    Min(i64),
+}
+
+impl Client {
+    /// Atomically adds a delta, which can be negative, to a numeric field of an existing
+    /// resource, returning its new value. A missing field is treated as zero. As in `patch`,
+    /// immutable fields and index keys are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The numeric field that is modified.
+    /// * `delta` - The amount added to the field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let views = client.incr::<Article>(article.pk_sk(), "views", 1).await?;
+    /// }
+    /// ```
+    pub async fn incr<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        delta: i64,
+    ) -> Result<i64, DynarustError> {
+        self.add_to_counter::<T>(pk_sk, field, delta, None).await
+    }
+
+    /// Atomically increments a numeric field of an existing resource only if its new value does
+    /// not exceed the provided maximum, returning its new value. A missing field is treated as
+    /// zero. If the maximum would be exceeded, nothing is written and a
+    /// `DynarustError::ConditionalCheckFailedError` is returned, so concurrent increments can
+    /// never go over the limit.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The numeric field that is incremented.
+    /// * `delta` - The amount added to the field.
+    /// * `max` - The maximum value that the field can reach.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     match client.incr_with_max::<Quota>(quota.pk_sk(), "used", 1, 100).await {
+    ///         Err(DynarustError::ConditionalCheckFailedError(_)) => println!("quota exceeded"),
+    ///         result => println!("used {}", result?),
+    ///     }
+    /// }
+    /// ```
+    pub async fn incr_with_max<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        delta: i64,
+        max: i64,
+    ) -> Result<i64, DynarustError> {
+        self.add_to_counter::<T>(pk_sk, field, delta, Some(Bound::Max(max)))
+            .await
+    }
+
+    /// Atomically decrements a numeric field of an existing resource only if its new value does
+    /// not go below the provided minimum, returning its new value. A missing field is treated
+    /// as zero. If the value would go below the minimum, nothing is written and a
+    /// `DynarustError::ConditionalCheckFailedError` is returned.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The numeric field that is decremented.
+    /// * `delta` - The amount subtracted from the field.
+    /// * `min` - The minimum value that the field can reach.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let stock = client.decr_with_min::<Product>(product.pk_sk(), "stock", 2, 0).await?;
+    /// }
+    /// ```
+    pub async fn decr_with_min<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        delta: i64,
+        min: i64,
+    ) -> Result<i64, DynarustError> {
+        let delta = delta
+            .checked_neg()
+            .ok_or_else(|| DynarustError::InvalidRequestError("Counter delta overflows".into()))?;
+        self.add_to_counter::<T>(pk_sk, field, delta, Some(Bound::Min(min)))
+            .await
+    }
+
+    async fn add_to_counter<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        delta: i64,
+        bound: Option<Bound>,
+    ) -> Result<i64, DynarustError> {
+        let table = T::table();
+        self.check_patched_field::<T>(&table, field)?;
+        let condition_check = match bound {
+            Some(bound) => {
+                condition_check_exists().merge(vec![counter_bound(field, delta, bound)?])
+            }
+            None => condition_check_exists(),
+        };
+
+        // adding to a missing number attribute sets it to the delta.
+        let update = update_item(
+            self,
+            &table,
+            pk_sk.clone(),
+            ItemUpdate::default().with_add(field, json!(delta)),
+            condition_check,
+            Some(ReturnValue::AllNew),
+        );
+        let after = self.stats.measure(Operation::UpdateItem, update).await?;
+        self.audit(AuditOperation::Update, &table, pk_sk, None, after.clone())
+            .await?;
+
+        match after.as_ref().and_then(|after| after.get(field)) {
+            Some(Value::Number(n)) => n.as_i64().ok_or_else(|| {
+                DynarustError::AttributeParseError(format!("{field} is not an integer: {n}"))
+            }),
+            _ => Err(DynarustError::UnexpectedError(format!(
+                "The new value of {field} was not returned"
+            ))),
+        }
+    }
+}
+
+/// Condition check that passes if adding delta to the counter respects the bound. Dynamo
+/// conditions cannot do arithmetic, so the delta is subtracted from the bound beforehand.
+fn counter_bound(
+    field: &str,
+    delta: i64,
+    bound: Bound,
+) -> Result<ConditionCheckInfo, DynarustError> {
+    let overflow = || DynarustError::InvalidRequestError("Counter bound overflows".into());
+    let (operator, limit, missing_allowed) = match bound {
+        Bound::Max(max) => (
+            "<=",
+            max.checked_sub(delta).ok_or_else(overflow)?,
+            delta <= max,
+        ),
+        Bound::Min(min) => (
+            ">=",
+            min.checked_sub(delta).ok_or_else(overflow)?,
+            delta >= min,
+        ),
+    };
+    let mut expression = format!("#counter {operator} :limit");
+    if missing_allowed {
+        expression = format!("attribute_not_exists(#counter) or {expression}");
+    }
+    Ok(ConditionCheckInfo::default()
+        .condition_expression(expression)
+        .expression_attribute_names("#counter", field)
+        .expression_attribute_values(":limit", AttributeValue::N(limit.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::counter::{counter_bound, Bound};
+    use crate::{Client, DynarustError, Resource};
+
+    struct Ledger;
+
+    impl Resource for Ledger {
+        fn table() -> String {
+            "Ledger".to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("ledger".to_string(), "1".to_string())
+        }
+
+        fn immutable_fields() -> &'static [&'static str] {
+            &["opening_balance"]
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_counter_updates() {
+        let client = Client::local_on_port(12345).await;
+        let err = client
+            .incr::<Ledger>(Ledger.pk_sk(), "opening_balance", 1)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid request: Cannot patch opening_balance"
+        );
+        let err = client
+            .decr_with_min::<Ledger>(Ledger.pk_sk(), "balance", i64::MIN, 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+    }
+
+    #[test]
+    fn subtracts_delta_from_bound() {
+        let check = counter_bound("int", 3, Bound::Max(10)).unwrap();
+        assert_eq!(
+            check.expression(),
+            "attribute_not_exists(#counter) or #counter <= :limit"
+        );
+        let check = counter_bound("int", -3, Bound::Min(0)).unwrap();
+        assert_eq!(check.expression(), "#counter >= :limit");
+        assert!(counter_bound("int", 1, Bound::Max(i64::MIN)).is_err());
+    }
+
+    #[tokio::test]
+    async fn enforces_counter_bounds() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "enforces_counter_bounds".to_string(),
+            sk: "1".to_string(),
+            int: 8,
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let incr = |delta| client.incr_with_max::<TestResource>(resource.pk_sk(), "int", delta, 10);
+        assert_eq!(incr(2).await.unwrap(), 10);
+        assert!(matches!(
+            incr(1).await,
+            Err(DynarustError::ConditionalCheckFailedError(_))
+        ));
+
+        let decr = |delta| client.decr_with_min::<TestResource>(resource.pk_sk(), "int", delta, 0);
+        assert_eq!(decr(7).await.unwrap(), 3);
+        assert!(decr(4).await.is_err());
+        assert_eq!(
+            client
+                .incr::<TestResource>(resource.pk_sk(), "int", -3)
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(client
+            .incr::<TestResource>(("enforces_counter_bounds".into(), "2".into()), "int", 1)
+            .await
+            .is_err());
+    }
+}
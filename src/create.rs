@@ -1,8 +1,21 @@
-use aws_sdk_dynamodb::model::{put, AttributeValue, TransactWriteItem};
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::{
+    put, AttributeValue, ReturnConsumedCapacity, ReturnValue, ReturnValuesOnConditionCheckFailure,
+    TransactWriteItem,
+};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::{Map, Value};
 
+use crate::audit::audit_image;
+use crate::client::{GSI1_PK, GSI1_SK, PK, SCHEMA_VERSION, SK};
 use crate::condition_check::{condition_check_not_exists, ConditionCheckInfo};
-use crate::{Client, DynarustError, Resource};
+use crate::stats::Operation;
+use crate::{
+    begin_transaction, AuditOperation, Client, DynamoStore, DynarustError, Resource,
+    TransactionContext,
+};
 
 impl Client {
     /// Creates a new resource, if the resource already exists, this operation fails.
@@ -39,25 +52,8 @@ impl Client {
         resource: &'a T,
         condition_checks: Vec<ConditionCheckInfo>,
     ) -> Result<&'a T, DynarustError> {
-        let object = Self::resource_as_object(resource)?;
-
-        let mut builder = self.client.put_item().table_name(T::table());
-
-        for (k, v) in object {
-            builder = builder.item(k, Self::value2attr(&v)?)
-        }
-
-        let condition_checks = condition_check_not_exists().merge(condition_checks);
-
-        builder = condition_checks.dump_in_put_item(builder);
-
-        let (pk, sk) = resource.pk_sk();
-        builder
-            .item(crate::PK, AttributeValue::S(pk))
-            .item(crate::SK, AttributeValue::S(sk))
-            .send()
-            .await?;
-
+        let condition_check = condition_check_not_exists().merge(condition_checks);
+        put_resource(self, resource, condition_check).await?;
         Ok(resource)
     }
 
@@ -70,21 +66,175 @@ impl Client {
         &self,
         resource: &'a T,
     ) -> Result<&'a T, DynarustError> {
-        let object = Self::resource_as_object(resource)?;
+        put_resource(self, resource, ConditionCheckInfo::default()).await?;
+        Ok(resource)
+    }
 
-        let mut builder = self.client.put_item().table_name(T::table());
-        for (k, v) in object {
-            builder = builder.item(k, Self::value2attr(&v)?)
+    /// Creates a new resource, overwriting a previously existing resource only if the condition
+    /// checks pass on it. If there is no previous resource the checks are evaluated against an
+    /// empty item, so checks on its attributes fail unless they allow missing attributes.
+    ///
+    /// # arguments
+    ///
+    /// * `resource` - The resource that will be created.
+    /// * `condition_checks` - The condition checks that the overwritten resource must pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     // overwrite the document only if nobody wrote a newer version meanwhile
+    ///     client.force_create_with_checks(
+    ///         &document,
+    ///         vec![dynarust::condition_check_number("version", DynamoOperator::Eq, read_version)],
+    ///     ).await?;
+    /// }
+    /// ```
+    pub async fn force_create_with_checks<'a, T: Resource + Serialize>(
+        &self,
+        resource: &'a T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<&'a T, DynarustError> {
+        let condition_check = ConditionCheckInfo::default().merge(condition_checks);
+        put_resource(self, resource, condition_check).await?;
+        Ok(resource)
+    }
+
+    /// Creates a new resource only if it does not exist yet, telling whether it was created or
+    /// returning the existing one otherwise, so callers do not need to inspect the failed
+    /// conditional check.
+    ///
+    /// # arguments
+    ///
+    /// * `resource` - The resource that will be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     match client.create_if_not_exists(&user).await? {
+    ///         CreateOutcome::Created(user) => welcome(user),
+    ///         CreateOutcome::AlreadyExists(existing) => return conflict(existing),
+    ///     }
+    /// }
+    /// ```
+    pub async fn create_if_not_exists<'a, T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        resource: &'a T,
+    ) -> Result<CreateOutcome<'a, T>, DynarustError> {
+        let mut context = begin_transaction();
+        let created = transact_create(resource, &mut context)?;
+        let mut item = context.into_items().remove(0);
+        if let Some(put) = item.put.as_mut() {
+            put.return_values_on_condition_check_failure =
+                Some(ReturnValuesOnConditionCheckFailure::AllOld);
+        }
+        self.scope_transaction_item(&mut item);
+
+        match self.execute_single_item(Operation::PutItem, item).await {
+            Ok(()) => {
+                self.audit(
+                    AuditOperation::Put,
+                    &T::table(),
+                    created.pk_sk(),
+                    None,
+                    None,
+                )
+                .await?;
+                Ok(CreateOutcome::Created(created))
+            }
+            Err(DynarustError::ConditionalCheckFailedError(Some(existing))) => Ok(
+                CreateOutcome::AlreadyExists(Client::value_as_resource(existing)?),
+            ),
+            Err(err) => Err(err),
         }
-        let (pk, sk) = resource.pk_sk();
-        builder
-            .item(crate::PK, AttributeValue::S(pk))
-            .item(crate::SK, AttributeValue::S(sk))
-            .send()
-            .await?;
+    }
+}
 
-        Ok(resource)
+/// Outcome of `create_if_not_exists`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CreateOutcome<'a, T> {
+    /// the resource was created.
+    Created(&'a T),
+    /// a resource with the same key already existed, and it was left untouched.
+    AlreadyExists(T),
+}
+
+/// Returns the attributes that will be written for the resource after its `before_save`
+/// hook, including its index keys.
+pub(crate) fn resource_as_item<T: Resource + Serialize>(
+    resource: &T,
+) -> Result<Map<String, Value>, DynarustError> {
+    let saved = resource.before_save();
+    let resource = saved.as_ref().unwrap_or(resource);
+    let mut object = Client::resource_as_object(resource)?;
+    if let Some((gsi1_pk, gsi1_sk)) = resource.gsi1_pk_sk() {
+        object.insert(GSI1_PK.to_string(), Value::String(gsi1_pk));
+        object.insert(GSI1_SK.to_string(), Value::String(gsi1_sk));
     }
+    for (attr, value) in resource.index_keys() {
+        object.insert(attr.to_string(), Value::String(value));
+    }
+    object.retain(|_, v| !T::write_policy().skips(v));
+    if T::schema_version() > 0 {
+        object.insert(SCHEMA_VERSION.to_string(), T::schema_version().into());
+    }
+    Ok(object)
+}
+
+pub(crate) async fn put_resource<S, T>(
+    store: &S,
+    resource: &T,
+    condition_check: ConditionCheckInfo,
+) -> Result<(), DynarustError>
+where
+    S: DynamoStore + ?Sized,
+    T: Resource + Serialize,
+{
+    let item = resource_as_item(resource)?;
+    store
+        .put_item(&T::table(), resource.pk_sk(), item, condition_check)
+        .await
+}
+
+pub(crate) async fn put_item(
+    client: &Client,
+    table: &str,
+    (pk, sk): (String, String),
+    item: Map<String, Value>,
+    condition_check: ConditionCheckInfo,
+) -> Result<Option<Value>, DynarustError> {
+    let mut attributes = HashMap::new();
+    for (k, v) in item {
+        attributes.insert(k, client.encode_value(&v)?);
+    }
+    attributes.insert(PK.to_string(), AttributeValue::S(pk));
+    attributes.insert(SK.to_string(), AttributeValue::S(sk));
+    client.intercept_item(table, &mut attributes)?;
+    client.scope_key(&mut attributes);
+    client.externalize_key(table, &mut attributes);
+
+    let mut builder = client
+        .dynamo(table)
+        .put_item()
+        .table_name(table)
+        .set_item(Some(attributes))
+        .set_return_values(client.audit.is_some().then_some(ReturnValue::AllOld))
+        .set_return_item_collection_metrics(client.return_collection_metrics())
+        .return_consumed_capacity(ReturnConsumedCapacity::Total);
+
+    builder = condition_check
+        .recoded(client)?
+        .externalized(client, table)
+        .dump_in_put_item(builder);
+
+    let result = builder.send().await?;
+    client.stats.add_capacity(
+        Operation::PutItem,
+        result.consumed_capacity().and_then(|c| c.capacity_units()),
+    );
+    client.report_collection_metrics(table, result.item_collection_metrics());
+    audit_image(client, result.attributes())
 }
 
 /// Adds a create operation to the transaction context.
@@ -106,7 +256,7 @@ impl Client {
 /// ```
 pub fn transact_create<'a, T: Resource + Serialize>(
     resource: &'a T,
-    transaction_context: &mut Vec<TransactWriteItem>,
+    transaction_context: &mut TransactionContext,
 ) -> Result<&'a T, DynarustError> {
     transact_create_with_checks(resource, vec![], transaction_context)
 }
@@ -135,13 +285,13 @@ pub fn transact_create<'a, T: Resource + Serialize>(
 pub fn transact_create_with_checks<'a, T: Resource + Serialize>(
     resource: &'a T,
     condition_checks: Vec<ConditionCheckInfo>,
-    transaction_context: &mut Vec<TransactWriteItem>,
+    transaction_context: &mut TransactionContext,
 ) -> Result<&'a T, DynarustError> {
-    let object = Client::resource_as_object(resource)?;
+    let item = resource_as_item(resource)?;
 
     let mut builder = put::Builder::default().table_name(T::table());
 
-    for (k, v) in object {
+    for (k, v) in item {
         builder = builder.item(k, Client::value2attr(&v)?)
     }
 
@@ -149,20 +299,161 @@ pub fn transact_create_with_checks<'a, T: Resource + Serialize>(
 
     let (pk, sk) = resource.pk_sk();
     let mut put = builder
-        .item(crate::PK, AttributeValue::S(pk))
-        .item(crate::SK, AttributeValue::S(sk));
+        .item(PK, AttributeValue::S(pk))
+        .item(SK, AttributeValue::S(sk));
 
     put = condition_checks.dump_in_put(put);
 
-    transaction_context.push(TransactWriteItem::builder().put(put.build()).build());
+    transaction_context.push(TransactWriteItem::builder().put(put.build()).build())?;
     Ok(resource)
 }
 
+/// A group of resources, possibly of different types, that can be created together in a single
+/// transaction. It is implemented for tuples of up to 8 resource references and for slices of
+/// resources of the same type.
+pub trait CreateAll {
+    /// The created resources, in the same shape as the group.
+    type Output;
+
+    /// Adds a create operation for every resource in the group to the transaction context.
+    fn transact_create_all(
+        self,
+        transaction_context: &mut TransactionContext,
+    ) -> Result<Self::Output, DynarustError>;
+}
+
+macro_rules! impl_create_all {
+    ($($t:ident),+) => {
+        impl<'a, $($t: Resource + Serialize),+> CreateAll for ($(&'a $t,)+) {
+            type Output = ($(&'a $t,)+);
+
+            #[allow(non_snake_case)]
+            fn transact_create_all(
+                self,
+                transaction_context: &mut TransactionContext,
+            ) -> Result<Self::Output, DynarustError> {
+                let ($($t,)+) = self;
+                Ok(($(transact_create($t, transaction_context)?,)+))
+            }
+        }
+    };
+}
+
+impl_create_all!(A);
+impl_create_all!(A, B);
+impl_create_all!(A, B, C);
+impl_create_all!(A, B, C, D);
+impl_create_all!(A, B, C, D, E);
+impl_create_all!(A, B, C, D, E, F);
+impl_create_all!(A, B, C, D, E, F, G);
+impl_create_all!(A, B, C, D, E, F, G, H);
+
+impl<'a, T: Resource + Serialize> CreateAll for &'a [T] {
+    type Output = Vec<&'a T>;
+
+    fn transact_create_all(
+        self,
+        transaction_context: &mut TransactionContext,
+    ) -> Result<Self::Output, DynarustError> {
+        self.iter()
+            .map(|resource| transact_create(resource, transaction_context))
+            .collect()
+    }
+}
+
+impl Client {
+    /// Creates multiple resources, possibly of different types, in a single transaction. If
+    /// any of them already exists, none of them is created. Returns the created resources in
+    /// the same order.
+    ///
+    /// # arguments
+    ///
+    /// * `resources` - Tuple of references to the resources that will be created, or a slice
+    ///   of resources of the same type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let (user, profile) = client.create_all((&user, &profile)).await?;
+    /// }
+    /// ```
+    pub async fn create_all<R: CreateAll>(&self, resources: R) -> Result<R::Output, DynarustError> {
+        let mut context = begin_transaction();
+        let created = resources.transact_create_all(&mut context)?;
+        self.execute_transaction(context).await?;
+        Ok(created)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::client::tests::TestResource;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
     use crate::create::transact_create;
-    use crate::{begin_transaction, Client, Resource};
+    use crate::{
+        assert_conditional_failure, begin_transaction, condition_check_number, Client,
+        CreateOutcome, DynamoOperator, DynarustError, Resource,
+    };
+
+    #[tokio::test]
+    async fn overwrites_only_if_checks_pass() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "overwrites_only_if_checks_pass".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let overwritten = TestResource {
+            int: 2,
+            ..resource.clone()
+        };
+        for succeeds in [true, false] {
+            let checks = vec![condition_check_number("int", DynamoOperator::Eq, 1)];
+            let result = client.force_create_with_checks(&overwritten, checks).await;
+            match succeeds {
+                true => assert!(result.is_ok()),
+                false => assert!(matches!(
+                    result,
+                    Err(DynarustError::ConditionalCheckFailedError(_))
+                )),
+            }
+        }
+        let stored = client.get::<TestResource>(resource.pk_sk()).await.unwrap();
+        assert_eq!(stored.unwrap().int, 2);
+    }
+
+    #[tokio::test]
+    async fn creates_if_not_exists() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "creates_if_not_exists".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        client
+            .delete::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap();
+
+        let outcome = client.create_if_not_exists(&resource).await.unwrap();
+        assert_eq!(outcome, CreateOutcome::Created(&resource));
+
+        let other = TestResource {
+            int: 2,
+            ..resource.clone()
+        };
+        let outcome = client.create_if_not_exists(&other).await.unwrap();
+        assert_eq!(outcome, CreateOutcome::AlreadyExists(resource));
+    }
 
     #[tokio::test]
     async fn is_able_to_create_table() {
@@ -244,4 +535,92 @@ mod tests {
             .unwrap();
         assert_eq!(retrieved_2, Some(resource_2))
     }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct User {
+        id: String,
+        email: String,
+        #[serde(skip)]
+        domain: String,
+    }
+
+    impl Resource for User {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("users".to_string(), self.id.clone())
+        }
+
+        fn before_save(&self) -> Option<Self> {
+            let mut user = self.clone();
+            user.email = user.email.to_lowercase();
+            user.after_load();
+            Some(user)
+        }
+
+        fn after_load(&mut self) {
+            self.domain = self
+                .email
+                .split('@')
+                .next_back()
+                .unwrap_or_default()
+                .to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_lifecycle_hooks() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let user = User {
+            id: "runs_lifecycle_hooks".to_string(),
+            email: "Foo@Bar.com".to_string(),
+            domain: "".to_string(),
+        };
+        client.force_create(&user).await.unwrap();
+        let retrieved = client.get::<User>(user.pk_sk()).await.unwrap().unwrap();
+        assert_eq!(retrieved.email, "foo@bar.com");
+        assert_eq!(retrieved.domain, "bar.com");
+
+        let updated = client
+            .update(&retrieved, json!({ "email": "Bar@Baz.com" }))
+            .await
+            .unwrap();
+        assert_eq!(updated.email, "bar@baz.com");
+        assert_eq!(updated.domain, "baz.com");
+
+        let listed = client
+            .list::<User>("users".into(), &Default::default())
+            .await
+            .unwrap();
+        let listed = listed.items.iter().find(|u| u.id == user.id).unwrap();
+        assert_eq!(listed, &updated);
+    }
+
+    #[tokio::test]
+    async fn creates_heterogeneous_resources_together() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = TestResource {
+            pk: "creates_heterogeneous_resources_together".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        let user = User {
+            id: "creates_heterogeneous_resources_together".to_string(),
+            email: "foo@bar.com".to_string(),
+            domain: "".to_string(),
+        };
+
+        let (created_resource, created_user) = client.create_all((&resource, &user)).await.unwrap();
+        assert_eq!(created_resource, &resource);
+        assert_eq!(created_user, &user);
+
+        let err = client.create_all((&resource,)).await.unwrap_err();
+        assert_conditional_failure(&err, 0);
+    }
 }
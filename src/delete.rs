@@ -1,8 +1,14 @@
-use aws_sdk_dynamodb::model::{delete, AttributeValue, TransactWriteItem};
+use aws_sdk_dynamodb::model::{
+    delete, AttributeValue, ReturnConsumedCapacity, ReturnValue,
+    ReturnValuesOnConditionCheckFailure, TransactWriteItem,
+};
+use serde_json::Value;
 
+use crate::audit::audit_image;
 use crate::client::{PK, SK};
 use crate::condition_check::ConditionCheckInfo;
-use crate::{Client, DynarustError, Resource};
+use crate::stats::Operation;
+use crate::{Client, DynamoStore, DynarustError, Resource, TransactionContext};
 
 /// Adds a delete operation to a transaction context.
 ///
@@ -11,8 +17,8 @@ use crate::{Client, DynarustError, Resource};
 /// * `transaction_context` - The transaction context to which the delete operation will be added.
 pub fn transact_delete<T: Resource>(
     pk_sk: (String, String),
-    transaction_context: &mut Vec<TransactWriteItem>,
-) {
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
     transact_delete_with_checks::<T>(pk_sk, vec![], transaction_context)
 }
 
@@ -26,8 +32,8 @@ pub fn transact_delete<T: Resource>(
 pub fn transact_delete_with_checks<T: Resource>(
     (pk, sk): (String, String),
     condition_checks: Vec<ConditionCheckInfo>,
-    transaction_context: &mut Vec<TransactWriteItem>,
-) {
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
     let mut delete = delete::Builder::default()
         .table_name(T::table())
         .key(PK, AttributeValue::S(pk))
@@ -37,7 +43,7 @@ pub fn transact_delete_with_checks<T: Resource>(
         .merge(condition_checks)
         .dump_in_delete(delete);
 
-    transaction_context.push(TransactWriteItem::builder().delete(delete.build()).build());
+    transaction_context.push(TransactWriteItem::builder().delete(delete.build()).build())
 }
 
 impl Client {
@@ -50,32 +56,91 @@ impl Client {
         self.delete_with_checks::<T>(pk_sk, vec![]).await
     }
 
-    /// Deletes a resource with additional condition checks.
+    /// Deletes a resource with additional condition checks. If any of the condition checks
+    /// fails, a `DynarustError::ConditionalCheckFailedError` is returned carrying the item as it
+    /// was stored in dynamo, which can be retrieved with `DynarustError::current_item`.
+    /// Conditional deletes are issued as single item transactions in order to get the item
+    /// back, so they consume twice the write capacity of a plain delete. As transactions do
+    /// not return the deleted item, audited clients record these deletes without it.
     ///
     /// # arguments
     ///
     /// * `pk_sk` - Pk and sk pair for identifying the resource that will get deleted.
     /// * `condition_checks` - The condition checks that will be added to the transaction item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let result = client.delete_with_checks::<Person>(
+    ///         person.pk_sk(),
+    ///         vec![dynarust::condition_check_number("age", dynarust::DynamoOperator::Gt, 21)],
+    ///     ).await;
+    ///     if let Err(err) = result {
+    ///         let current: Option<Person> = err.current_item();
+    ///     }
+    /// }
+    /// ```
     pub async fn delete_with_checks<T: Resource>(
         &self,
-        (pk, sk): (String, String),
+        pk_sk: (String, String),
         condition_checks: Vec<ConditionCheckInfo>,
     ) -> Result<(), DynarustError> {
-        let mut builder = self
-            .client
-            .delete_item()
-            .table_name(T::table())
-            .key(PK, AttributeValue::S(pk))
-            .key(SK, AttributeValue::S(sk));
-
-        builder = ConditionCheckInfo::default()
-            .merge(condition_checks)
-            .dump_in_delete_item(builder);
+        delete_resource::<_, T>(self, pk_sk, condition_checks).await
+    }
+}
 
-        builder.send().await?;
+pub(crate) async fn delete_resource<S: DynamoStore + ?Sized, T: Resource>(
+    store: &S,
+    pk_sk: (String, String),
+    condition_checks: Vec<ConditionCheckInfo>,
+) -> Result<(), DynarustError> {
+    let condition_check = ConditionCheckInfo::default().merge(condition_checks);
+    store.delete_item(&T::table(), pk_sk, condition_check).await
+}
 
-        Ok(())
+pub(crate) async fn delete_item(
+    client: &Client,
+    table: &str,
+    (pk, sk): (String, String),
+    condition_check: ConditionCheckInfo,
+) -> Result<Option<Value>, DynarustError> {
+    if condition_check.is_empty() {
+        let (pk_attr, sk_attr) = client.key_names(table);
+        let result = client
+            .dynamo(table)
+            .delete_item()
+            .table_name(table)
+            .key(pk_attr, AttributeValue::S(client.scope_pk(pk)))
+            .key(sk_attr, AttributeValue::S(sk))
+            .set_return_values(client.audit.is_some().then_some(ReturnValue::AllOld))
+            .set_return_item_collection_metrics(client.return_collection_metrics())
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send()
+            .await?;
+        client.stats.add_capacity(
+            Operation::DeleteItem,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+        client.report_collection_metrics(table, result.item_collection_metrics());
+        return audit_image(client, result.attributes());
     }
+
+    let mut delete = delete::Builder::default()
+        .table_name(table)
+        .key(PK, AttributeValue::S(client.scope_pk(pk)))
+        .key(SK, AttributeValue::S(sk))
+        .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld);
+
+    delete = condition_check.dump_in_delete(delete);
+
+    client
+        .execute_single_item(
+            Operation::DeleteItem,
+            TransactWriteItem::builder().delete(delete.build()).build(),
+        )
+        .await?;
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -129,6 +194,7 @@ mod tests {
             .await
             .unwrap_err();
 
-        assert_eq!(err.to_string(), "The conditional request failed")
+        assert_eq!(err.to_string(), "The conditional request failed");
+        assert_eq!(err.current_item::<TestResource>(), Some(resource))
     }
 }
@@ -0,0 +1,151 @@
+use aws_sdk_dynamodb::model::{DeleteRequest, WriteRequest};
+
+use crate::bulk::{write_batch_with_retries, write_request_key};
+use crate::{AuditOperation, Client, ConditionCheckInfo, DynarustError, Resource, SkCondition};
+
+/// Maximum number of items in a single BatchWriteItem request.
+const BATCH_SIZE: usize = 25;
+
+/// Outcome of a `delete_where` call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeleteWhereSummary {
+    /// number of deleted items.
+    pub deleted: usize,
+    /// pk and sk pairs of the matching items that dynamo left unprocessed after every retry.
+    pub failed: Vec<(String, String)>,
+}
+
+impl Client {
+    /// Deletes every item under a pk whose sort key and attributes match the provided
+    /// conditions. The matching keys are enumerated with a keys-only query, and deleted with
+    /// batched writes of up to 25 items, retrying the deletes that dynamo leaves unprocessed.
+    /// Batched deletes cannot be conditional, so an item modified between the query and its
+    /// deletion is deleted anyway. Audited clients record a delete without a previous image
+    /// for every deleted item.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the deleted items live.
+    /// * `sk_condition` - The condition on the sort key of the deleted items.
+    /// * `filter` - The condition on the attributes of the deleted items, built with the same
+    ///   functions as condition checks. An empty filter matches every item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let summary = client
+    ///         .delete_where::<Session>(
+    ///             "user#1".into(),
+    ///             SkCondition::Any,
+    ///             dynarust::condition_check_string("state", DynamoOperator::Eq, "revoked"),
+    ///         )
+    ///         .await?;
+    ///     println!("deleted {}, failed {:?}", summary.deleted, summary.failed);
+    /// }
+    /// ```
+    pub async fn delete_where<T: Resource>(
+        &self,
+        pk: String,
+        sk_condition: SkCondition,
+        filter: ConditionCheckInfo,
+    ) -> Result<DeleteWhereSummary, DynarustError> {
+        let table = T::table();
+        let query = self
+            .query_where(&table, pk, &sk_condition, filter)
+            .projection_expression("#pk, #sk");
+
+        let mut summary = DeleteWhereSummary::default();
+        let mut exclusive_start_key = None;
+        loop {
+            let page = query.clone().set_exclusive_start_key(exclusive_start_key);
+            let result = self.send_query_where(page).await?;
+
+            let keys = result.items().unwrap_or_default();
+            for batch in keys.chunks(BATCH_SIZE) {
+                let requests: Vec<WriteRequest> = batch
+                    .iter()
+                    .map(|key| {
+                        let delete = DeleteRequest::builder().set_key(Some(key.clone())).build();
+                        WriteRequest::builder().delete_request(delete).build()
+                    })
+                    .collect();
+                let batch_keys: Vec<_> = requests
+                    .iter()
+                    .map(|request| write_request_key(self, &table, request))
+                    .collect();
+                let unprocessed = write_batch_with_retries(self, &table, requests).await?;
+                let failed: Vec<_> = unprocessed
+                    .iter()
+                    .map(|request| write_request_key(self, &table, request))
+                    .collect();
+                for key in batch_keys.into_iter().filter(|key| !failed.contains(key)) {
+                    self.audit(AuditOperation::Delete, &table, key, None, None)
+                        .await?;
+                }
+                summary.deleted += batch.len() - failed.len();
+                summary.failed.extend(failed);
+            }
+
+            exclusive_start_key = result.last_evaluated_key().cloned();
+            if exclusive_start_key.is_none() {
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::{
+        condition_check_number, Client, DeleteWhereSummary, DynamoOperator, ListOptions,
+        SkCondition,
+    };
+
+    #[tokio::test]
+    async fn deletes_matching_items() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "deletes_matching_items".to_string();
+        for i in 0..30 {
+            let resource = TestResource {
+                pk: pk.clone(),
+                sk: format!("{i:02}"),
+                int: i % 3,
+                ..Default::default()
+            };
+            client.force_create(&resource).await.unwrap();
+        }
+
+        let summary = client
+            .delete_where::<TestResource>(
+                pk.clone(),
+                SkCondition::Lt("27".into()),
+                condition_check_number("int", DynamoOperator::Eq, 0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            DeleteWhereSummary {
+                deleted: 9,
+                failed: vec![]
+            }
+        );
+
+        let listed = client
+            .list::<TestResource>(
+                pk,
+                &ListOptions {
+                    limit: 100,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.items.len(), 21);
+        assert!(listed.items.iter().any(|r| r.sk == "27"));
+    }
+}
@@ -0,0 +1,205 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client::strip_key_attributes;
+use crate::{Client, DynamoStore, DynarustError, ListOptions, Page, Resource};
+
+/// Registry of the entity types that share the partitions of a table in a single-table design,
+/// each one identified by the prefix of its sort keys.
+///
+/// # Examples
+///
+/// ```
+/// let registry = EntityRegistry::new("Shop")
+///     .register("customer", "PROFILE")
+///     .register("order", "ORDER#")
+///     .register("invoice", "INVOICE#");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityRegistry {
+    table: String,
+    entities: Vec<(String, String)>,
+}
+
+impl EntityRegistry {
+    /// Starts a registry for the entities stored in the provided table.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            entities: vec![],
+        }
+    }
+
+    /// Registers an entity type, identified by the prefix of its sort keys.
+    pub fn register(
+        mut self,
+        entity_type: impl Into<String>,
+        sk_prefix: impl Into<String>,
+    ) -> Self {
+        self.entities.push((entity_type.into(), sk_prefix.into()));
+        self
+    }
+
+    /// The type of the entity with the provided sort key. If several prefixes match, the
+    /// longest one wins.
+    pub fn entity_type(&self, sk: &str) -> Option<&str> {
+        self.entities
+            .iter()
+            .filter(|(_, prefix)| sk.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len())
+            .map(|(entity_type, _)| entity_type.as_str())
+    }
+}
+
+/// An item of a partition shared by several entity types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    /// The registered type of the item, None if its sort key matches no registered prefix.
+    pub entity_type: Option<String>,
+    /// The sort key of the item.
+    pub sk: String,
+    /// The item without the key attributes managed by dynarust.
+    pub value: Value,
+}
+
+impl Entity {
+    /// Whether this item is of the provided entity type.
+    pub fn is(&self, entity_type: &str) -> bool {
+        self.entity_type.as_deref() == Some(entity_type)
+    }
+
+    /// Decodes the item as the resource T.
+    pub fn decode<T: Resource + DeserializeOwned>(&self) -> Result<T, DynarustError> {
+        Client::value_as_resource(self.value.clone())
+    }
+}
+
+impl Client {
+    /// Lists all the items under the same pk, whatever their entity type is, tagging each one
+    /// of them with the type registered for its sort key. Unlike `list`, items of different
+    /// types can be read with a single query, and then decoded with `Entity::decode`.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the listed items live.
+    /// * `registry` - The entity types stored in the table.
+    /// * `options` - pagination options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let page = client
+    ///         .list_entities("customer#1".into(), &registry, &ListOptions::default())
+    ///         .await?;
+    ///     for entity in page.items {
+    ///         match entity.entity_type.as_deref() {
+    ///             Some("order") => orders.push(entity.decode::<Order>()?),
+    ///             Some("invoice") => invoices.push(entity.decode::<Invoice>()?),
+    ///             _ => {}
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn list_entities(
+        &self,
+        pk: String,
+        registry: &EntityRegistry,
+        options: &ListOptions,
+    ) -> Result<Page<Entity>, DynarustError> {
+        let table = &registry.table;
+        let page = DynamoStore::query_items(self, table, pk, options).await?;
+        let (_, sk_attr) = self.key_names(table);
+
+        let mut items = vec![];
+        for mut value in page.items {
+            let sk = match value.get(sk_attr) {
+                Some(Value::String(sk)) => sk.clone(),
+                _ => String::new(),
+            };
+            if let Some(object) = value.as_object_mut() {
+                strip_key_attributes(object);
+            }
+            items.push(Entity {
+                entity_type: registry.entity_type(&sk).map(str::to_string),
+                sk,
+                value,
+            })
+        }
+        Ok(Page {
+            items,
+            next: page.next,
+            scanned_count: page.scanned_count,
+            count: page.count,
+            consumed_capacity: page.consumed_capacity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, EntityRegistry, ListOptions, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Note {
+        owner: String,
+        id: String,
+        text: String,
+    }
+
+    impl Resource for Note {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.owner.clone(), format!("NOTE#{}", self.id))
+        }
+    }
+
+    #[test]
+    fn matches_longest_prefix() {
+        let registry = EntityRegistry::new("Table")
+            .register("order", "ORDER#")
+            .register("order_line", "ORDER#LINE#");
+        assert_eq!(registry.entity_type("ORDER#1"), Some("order"));
+        assert_eq!(registry.entity_type("ORDER#LINE#1"), Some("order_line"));
+        assert_eq!(registry.entity_type("PROFILE"), None);
+    }
+
+    #[tokio::test]
+    async fn lists_mixed_partitions() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "lists_mixed_partitions".to_string();
+        let resource = TestResource {
+            pk: pk.clone(),
+            sk: "RESOURCE#1".into(),
+            ..Default::default()
+        };
+        let note = Note {
+            owner: pk.clone(),
+            id: "1".into(),
+            text: "hi".into(),
+        };
+        client.force_create(&resource).await.unwrap();
+        client.force_create(&note).await.unwrap();
+
+        let registry = EntityRegistry::new(TABLE.to_string())
+            .register("resource", "RESOURCE#")
+            .register("note", "NOTE#");
+        let page = client
+            .list_entities(pk, &registry, &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert!(page.items[0].is("note"));
+        assert_eq!(page.items[0].decode::<Note>().unwrap(), note);
+        assert!(page.items[1].is("resource"));
+        assert_eq!(page.items[1].decode::<TestResource>().unwrap(), resource);
+    }
+}
@@ -1,12 +1,18 @@
 use std::fmt::Debug;
 
 use aws_sdk_dynamodb::error::{
-    BatchGetItemError, CreateTableError, DeleteItemError, GetItemError, PutItemError, QueryError,
-    TransactWriteItemsError, UpdateItemError,
+    BatchGetItemError, BatchWriteItemError, CreateBackupError, CreateTableError, DeleteBackupError,
+    DeleteItemError, DescribeTableError, DescribeTimeToLiveError, GetItemError, ListBackupsError,
+    PutItemError, QueryError, ScanError, TransactWriteItemsError, UpdateItemError,
+    UpdateTableError,
 };
 use aws_sdk_dynamodb::types::SdkError;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use thiserror::Error;
 
+use crate::client::strip_key_attributes;
+
 #[derive(Error, Debug)]
 pub enum DynarustError {
     #[error("Connection error: could not connect to dynamo")]
@@ -30,10 +36,139 @@ pub enum DynarustError {
     #[error("Error while deserializing resource: {0}")]
     ResourceDeserializeError(#[from] serde_json::Error),
 
+    #[error("The conditional request failed")]
+    ConditionalCheckFailedError(Option<Value>),
+
+    #[error("Compare and set failed, the current value is {actual:?}")]
+    CasFailed { actual: Option<Value> },
+
+    #[error("Invalid state transition, the current state is {actual:?}")]
+    InvalidTransition { actual: Option<String> },
+
+    #[error("Transaction contains more than one operation on item ({pk}, {sk}) of table {table}: {first} and {second}")]
+    DuplicateTransactionItemError {
+        table: String,
+        pk: String,
+        sk: String,
+        first: String,
+        second: String,
+    },
+
+    #[error("{} writes to table {table} were left unprocessed after every retry", keys.len())]
+    UnprocessedWritesError {
+        table: String,
+        /// pk and sk pairs of the unprocessed writes.
+        keys: Vec<(String, String)>,
+    },
+
+    #[error("{0}")]
+    DecodeError(Box<DecodeFailure>),
+
+    #[error("Item of table {table} has attributes that are not fields of the resource: {}", attributes.join(", "))]
+    UnknownAttributesError {
+        table: String,
+        attributes: Vec<String>,
+    },
+
+    #[error("The transactions of {} partitions failed, the ones of {} partitions were committed", failed.len(), committed.len())]
+    PartialTransactionError {
+        /// pks whose transaction was committed.
+        committed: Vec<String>,
+        /// pks whose transaction failed, along with the reason.
+        failed: Vec<(String, DynarustError)>,
+    },
+
+    #[error("Transaction canceled: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    TransactionCanceledError(Vec<CanceledOperation>),
+
     #[error("{0}")]
     DynamoError(String),
 }
 
+/// An operation of a transaction that made dynamo cancel it.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{} on item ({pk}, {sk}) of table {table} failed with {code}", .label.as_deref().unwrap_or(.operation))]
+pub struct CanceledOperation {
+    /// position of the operation in the transaction.
+    pub index: usize,
+    /// label given to the operation with `TransactionContext::label`.
+    pub label: Option<String>,
+    /// kind of operation: put, update, delete or condition check.
+    pub operation: String,
+    pub table: String,
+    pub pk: String,
+    pub sk: String,
+    /// cancellation reason reported by dynamo, like `ConditionalCheckFailed`.
+    pub code: String,
+    pub message: Option<String>,
+    /// the item as it was stored in dynamo, if the operation requested it.
+    pub item: Option<Value>,
+}
+
+/// Details of a stored item that could not be decoded.
+#[derive(Error, Debug)]
+#[error("Could not decode item ({pk}, {sk}) of table {table}, attribute {}: {reason}. Stored value: {value}", .attribute.as_deref().unwrap_or("unknown"))]
+pub struct DecodeFailure {
+    pub table: String,
+    pub pk: String,
+    pub sk: String,
+    /// the attribute that could not be decoded, if it could be found.
+    pub attribute: Option<String>,
+    /// a snippet of the stored value of the attribute, or of the whole item.
+    pub value: String,
+    pub reason: String,
+}
+
+impl DynarustError {
+    /// If this error is a failed conditional check that returned the item as it was stored in
+    /// dynamo at the moment of the failure, it returns that item.
+    pub fn current_item<T: DeserializeOwned>(&self) -> Option<T> {
+        match self {
+            DynarustError::ConditionalCheckFailedError(Some(item)) => {
+                let mut item = item.clone();
+                if let Some(object) = item.as_object_mut() {
+                    strip_key_attributes(object);
+                }
+                serde_json::from_value(item).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets the table of decode errors raised while reading items of that table.
+    pub(crate) fn in_table(mut self, table: &str) -> Self {
+        if let DynarustError::DecodeError(failure) = &mut self {
+            failure.table = table.to_string();
+        }
+        self
+    }
+}
+
+/// Maximum length of the stored values shown in decode errors.
+const DECODE_SNIPPET_LEN: usize = 120;
+
+/// Builds a decode error for an item, with a snippet of the offending attribute if known.
+pub(crate) fn decode_error(
+    table: &str,
+    (pk, sk): (String, String),
+    attribute: Option<String>,
+    value: String,
+    reason: impl ToString,
+) -> DynarustError {
+    let value = match value.char_indices().nth(DECODE_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value,
+    };
+    DynarustError::DecodeError(Box::new(DecodeFailure {
+        table: table.to_string(),
+        pk,
+        sk,
+        attribute,
+        value,
+        reason: reason.to_string(),
+    }))
+}
+
 macro_rules! impl_dynamo_error {
     ($t: ty) => {
         impl From<SdkError<$t>> for DynarustError {
@@ -53,13 +188,43 @@ macro_rules! impl_dynamo_error {
     };
 }
 
+macro_rules! impl_conditional_dynamo_error {
+    ($t: ty) => {
+        impl From<SdkError<$t>> for DynarustError {
+            fn from(value: SdkError<$t>) -> Self {
+                if let SdkError::DispatchFailure(_) = value {
+                    return DynarustError::ConnectionError("".to_string());
+                };
+                let service_error = value.into_service_error();
+                if service_error.is_conditional_check_failed_exception() {
+                    return DynarustError::ConditionalCheckFailedError(None);
+                }
+                DynarustError::DynamoError(
+                    service_error
+                        .message()
+                        .unwrap_or("unknown error")
+                        .to_string(),
+                )
+            }
+        }
+    };
+}
+
 impl_dynamo_error!(BatchGetItemError);
+impl_dynamo_error!(BatchWriteItemError);
 impl_dynamo_error!(GetItemError);
-impl_dynamo_error!(PutItemError);
 impl_dynamo_error!(TransactWriteItemsError);
 impl_dynamo_error!(QueryError);
-impl_dynamo_error!(UpdateItemError);
-impl_dynamo_error!(DeleteItemError);
+impl_dynamo_error!(ScanError);
+impl_dynamo_error!(UpdateTableError);
+impl_dynamo_error!(CreateBackupError);
+impl_dynamo_error!(ListBackupsError);
+impl_dynamo_error!(DeleteBackupError);
+impl_dynamo_error!(DescribeTableError);
+impl_dynamo_error!(DescribeTimeToLiveError);
+impl_conditional_dynamo_error!(PutItemError);
+impl_conditional_dynamo_error!(UpdateItemError);
+impl_conditional_dynamo_error!(DeleteItemError);
 
 impl From<SdkError<CreateTableError>> for DynarustError {
     fn from(value: SdkError<CreateTableError>) -> Self {
@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::client::{PK, SK};
+use crate::condition_check::{
+    condition_check_exists, condition_check_not_exists, rename_placeholders,
+};
+use crate::create::resource_as_item;
+use crate::list::list_key_condition;
+use crate::transaction::describe_item;
+use crate::update::apply_update_request;
+use crate::{Client, ConditionCheckInfo, DynarustError, ListOptions, Resource, TransactionContext};
+
+/// The request that an operation would send to dynamo, rendered without sending it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Explanation {
+    pub table: String,
+    /// key of the item, for single item operations.
+    pub key: Option<(String, String)>,
+    /// item with all its attributes, for put operations.
+    pub item: Option<Value>,
+    pub key_condition_expression: Option<String>,
+    pub update_expression: Option<String>,
+    pub condition_expression: Option<String>,
+    pub expression_attribute_names: BTreeMap<String, String>,
+    pub expression_attribute_values: BTreeMap<String, Value>,
+}
+
+impl Explanation {
+    fn new(table: String) -> Self {
+        Self {
+            table,
+            ..Default::default()
+        }
+    }
+
+    fn names(mut self, names: HashMap<String, String>) -> Self {
+        self.expression_attribute_names.extend(names);
+        self
+    }
+
+    fn values(mut self, values: HashMap<String, AttributeValue>) -> Result<Self, DynarustError> {
+        for (k, v) in values {
+            self.expression_attribute_values
+                .insert(k, Client::attr2value(&v)?);
+        }
+        Ok(self)
+    }
+
+    fn condition(self, condition_check: ConditionCheckInfo) -> Result<Self, DynarustError> {
+        let (expression, names, values) = condition_check.into_parts();
+        let mut explanation = self.names(names).values(values)?;
+        explanation.condition_expression = Some(expression).filter(|e| !e.is_empty());
+        Ok(explanation)
+    }
+
+    /// The expression with its placeholders replaced by the attribute names and the values
+    /// they stand for, for humans to read.
+    pub fn resolve(&self, expression: &str) -> String {
+        rename_placeholders(expression, |prefix, key| match prefix {
+            '#' => self
+                .expression_attribute_names
+                .get(&format!("#{key}"))
+                .cloned(),
+            _ => self
+                .expression_attribute_values
+                .get(&format!(":{key}"))
+                .map(Value::to_string),
+        })
+    }
+}
+
+/// An operation queued in a transaction, as rendered by `TransactionContext::describe`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedOperation {
+    /// position of the operation in the transaction.
+    pub index: usize,
+    /// label given to the operation with `TransactionContext::label`.
+    pub label: Option<String>,
+    /// kind of operation: put, update, delete or condition check.
+    pub operation: String,
+    pub explanation: Explanation,
+}
+
+impl Display for QueuedOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}. {}", self.index, self.operation)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")?;
+        }
+        let (pk, sk) = self.explanation.key.clone().unwrap_or_default();
+        write!(
+            f,
+            " on item ({pk}, {sk}) of table {}",
+            self.explanation.table
+        )?;
+        if let Some(expression) = &self.explanation.update_expression {
+            write!(f, ", update: {}", self.explanation.resolve(expression))?;
+        }
+        if let Some(expression) = &self.explanation.condition_expression {
+            write!(f, ", condition: {}", self.explanation.resolve(expression))?;
+        }
+        Ok(())
+    }
+}
+
+/// The operations queued in a transaction, as rendered by `TransactionContext::describe`.
+/// Displaying it prints one operation per line.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransactionDescription {
+    pub operations: Vec<QueuedOperation>,
+}
+
+impl Display for TransactionDescription {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lines: Vec<_> = self.operations.iter().map(ToString::to_string).collect();
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+impl TransactionContext {
+    /// Renders the operations queued in the transaction, with their tables, keys and
+    /// expressions, so they can be logged or asserted on before executing the transaction.
+    /// Keys are rendered as queued, before the client scopes them to its tenant, and the
+    /// attributes of write interceptors are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut context = dynarust::begin_transaction();
+    /// dynarust::transact_create(&user, &mut context)?;
+    /// context.label("create user");
+    /// println!("{}", context.describe()?);
+    /// // 0. put (create user) on item (users, 1) of table Users, condition: attribute_not_exists(PrimaryKey) and attribute_not_exists(SecondaryKey)
+    /// ```
+    pub fn describe(&self) -> Result<TransactionDescription, DynarustError> {
+        let mut operations = vec![];
+        for (index, (item, label)) in self.items().iter().zip(self.labels()).enumerate() {
+            let (operation, table, key) = describe_item(item)?;
+            let mut explanation = Explanation::new(table.to_string());
+            let (expression, names, values) = if let Some(put) = &item.put {
+                if let Some(item) = &put.item {
+                    explanation.item = Some(Client::item_as_value(item)?);
+                }
+                (
+                    &put.condition_expression,
+                    &put.expression_attribute_names,
+                    &put.expression_attribute_values,
+                )
+            } else if let Some(update) = &item.update {
+                explanation.update_expression = update.update_expression.clone();
+                (
+                    &update.condition_expression,
+                    &update.expression_attribute_names,
+                    &update.expression_attribute_values,
+                )
+            } else if let Some(delete) = &item.delete {
+                (
+                    &delete.condition_expression,
+                    &delete.expression_attribute_names,
+                    &delete.expression_attribute_values,
+                )
+            } else if let Some(check) = &item.condition_check {
+                (
+                    &check.condition_expression,
+                    &check.expression_attribute_names,
+                    &check.expression_attribute_values,
+                )
+            } else {
+                continue;
+            };
+            let mut explanation = explanation
+                .names(names.clone().unwrap_or_default())
+                .values(values.clone().unwrap_or_default())?;
+            explanation.condition_expression = expression.clone().filter(|e| !e.is_empty());
+            let attr = |name: &str| match key.as_ref().and_then(|key| key.get(name)) {
+                Some(AttributeValue::S(v)) => v.clone(),
+                _ => String::new(),
+            };
+            explanation.key = Some((attr(PK), attr(SK)));
+            operations.push(QueuedOperation {
+                index,
+                label: label.clone(),
+                operation: operation.to_string(),
+                explanation,
+            });
+        }
+        Ok(TransactionDescription { operations })
+    }
+}
+
+impl Client {
+    /// Renders the request that `create_with_checks` would send, without sending it.
+    ///
+    /// # arguments
+    ///
+    /// * `resource` - The resource that would be created.
+    /// * `condition_checks` - Additional condition checks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let explanation = client.explain_create(&car, vec![])?;
+    /// assert_eq!(
+    ///     explanation.condition_expression.as_deref(),
+    ///     Some("attribute_not_exists(#pk) and attribute_not_exists(#sk)")
+    /// );
+    /// ```
+    pub fn explain_create<T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        resource: &T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<Explanation, DynarustError> {
+        let item = resource_as_item(resource)?;
+        let (pk, sk) = resource.pk_sk();
+        let mut attributes = HashMap::new();
+        for (k, v) in item {
+            attributes.insert(k, Client::value2attr(&v)?);
+        }
+        attributes.insert(PK.to_string(), AttributeValue::S(pk.clone()));
+        attributes.insert(SK.to_string(), AttributeValue::S(sk.clone()));
+        self.scope_key(&mut attributes);
+        self.externalize_key(&T::table(), &mut attributes);
+
+        let mut explanation = Explanation::new(T::table()).condition(
+            condition_check_not_exists()
+                .merge(condition_checks)
+                .externalized(self, &T::table()),
+        )?;
+        explanation.key = Some((self.scope_pk(pk), sk));
+        explanation.item = Some(Client::item_as_value(&attributes)?);
+        Ok(explanation)
+    }
+
+    /// Renders the request that `update_with_checks` would send, without sending it. The
+    /// update expression is None if the request does not change anything.
+    ///
+    /// # arguments
+    ///
+    /// * `resource` - The resource that would be updated.
+    /// * `request` - serde Object with the info for updating the request.
+    /// * `condition_checks` - Additional condition checks.
+    pub fn explain_update<T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        resource: &T,
+        request: Value,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<Explanation, DynarustError> {
+        let (_, mut update) = apply_update_request(resource, request)?;
+        self.scope_update(&mut update);
+        let (pk_attr, sk_attr) = self.key_names(&T::table());
+        update.set.retain(|(k, _)| k != pk_attr && k != sk_attr);
+        let (expression, names, values) = update.expression()?;
+        let (pk, sk) = resource.pk_sk();
+
+        let mut explanation = Explanation::new(T::table())
+            .names(names)
+            .values(values)?
+            .condition(
+                condition_check_exists()
+                    .merge(condition_checks)
+                    .externalized(self, &T::table()),
+            )?;
+        explanation.key = Some((self.scope_pk(pk), sk));
+        explanation.update_expression = Some(expression).filter(|e| !e.is_empty());
+        Ok(explanation)
+    }
+
+    /// Renders the request that `delete_with_checks` would send, without sending it.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource that would get deleted.
+    /// * `condition_checks` - The condition checks of the deletion.
+    pub fn explain_delete<T: Resource>(
+        &self,
+        (pk, sk): (String, String),
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<Explanation, DynarustError> {
+        let mut explanation = Explanation::new(T::table()).condition(
+            ConditionCheckInfo::default()
+                .merge(condition_checks)
+                .externalized(self, &T::table()),
+        )?;
+        explanation.key = Some((self.scope_pk(pk), sk));
+        Ok(explanation)
+    }
+
+    /// Renders the query that `list` would send, without sending it.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the listed resources live.
+    /// * `options` - pagination options.
+    pub fn explain_list<T: Resource>(
+        &self,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Explanation, DynarustError> {
+        let (expression, mut values) = list_key_condition(options)?;
+        let (pk_attr, sk_attr) = self.key_names(&T::table());
+        let mut names = HashMap::from([("#pk".to_string(), pk_attr.to_string())]);
+        if !values.is_empty() {
+            names.insert("#sk".to_string(), sk_attr.to_string());
+        }
+        values.insert(":pk".to_string(), AttributeValue::S(self.scope_pk(pk)));
+        let mut explanation = Explanation::new(T::table()).names(names).values(values)?;
+        explanation.key_condition_expression = Some(expression);
+        Ok(explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{
+        begin_transaction, condition_check_number, transact_delete, transact_update_with_checks,
+        Client, DynamoOperator, ListOptions,
+    };
+
+    #[tokio::test]
+    async fn explains_update_deterministically() {
+        let client = Client::local().await.scoped("acme").unwrap();
+        let resource = TestResource {
+            pk: "explains_update_deterministically".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+
+        let explain = || {
+            client
+                .explain_update(
+                    &resource,
+                    json!({ "int": 2 }),
+                    vec![condition_check_number("int", DynamoOperator::Ls, 2)],
+                )
+                .unwrap()
+        };
+        let explanation = explain();
+        assert_eq!(explanation, explain());
+        assert_eq!(explanation.table, *TABLE);
+        assert_eq!(
+            explanation.key,
+            Some((
+                "acme#explains_update_deterministically".to_string(),
+                "1".to_string()
+            ))
+        );
+        assert_eq!(
+            explanation.update_expression.as_deref(),
+            Some("set #updateAttr0 = :updateAttr0")
+        );
+        assert_eq!(
+            explanation.condition_expression.as_deref(),
+            Some("(attribute_exists(#pk) and attribute_exists(#sk)) and (#cond0 < :cond0)")
+        );
+        assert_eq!(explanation.expression_attribute_names["#cond0"], "int");
+        assert_eq!(explanation.expression_attribute_values[":cond0"], json!(2));
+        assert_eq!(
+            explanation.expression_attribute_values[":updateAttr0"],
+            json!(2)
+        );
+    }
+
+    #[test]
+    fn describes_transactions() {
+        let resource = TestResource {
+            pk: "describes_transactions".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        let mut context = begin_transaction();
+        transact_update_with_checks(
+            &resource,
+            json!({ "int": 2 }),
+            vec![condition_check_number("int", DynamoOperator::Ls, 2)],
+            &mut context,
+        )
+        .unwrap();
+        context.label("bump int");
+        transact_delete::<TestResource>(
+            ("describes_transactions".into(), "2".into()),
+            &mut context,
+        )
+        .unwrap();
+
+        let description = context.describe().unwrap();
+        assert_eq!(description.operations[0].label.as_deref(), Some("bump int"));
+        assert_eq!(
+            description.operations[1].explanation.key,
+            Some(("describes_transactions".to_string(), "2".to_string()))
+        );
+        assert_eq!(
+            description.to_string(),
+            format!(
+                "0. update (bump int) on item (describes_transactions, 1) of table {table}, \
+                 update: set int = 2, condition: (attribute_exists(PrimaryKey) and \
+                 attribute_exists(SecondaryKey)) and (int < 2)\n\
+                 1. delete on item (describes_transactions, 2) of table {table}",
+                table = *TABLE
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn explains_list() {
+        let client = Client::local().await;
+        let explanation = client
+            .explain_list::<TestResource>(
+                "explains_list".into(),
+                &ListOptions {
+                    from: Some("5".into()),
+                    sort_desc: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            explanation.key_condition_expression.as_deref(),
+            Some("#pk = :pk and #sk < :sk")
+        );
+        assert_eq!(explanation.expression_attribute_values[":sk"], json!("5"));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+
+use crate::client::{PK, SK};
+use crate::{Client, DynarustError, Resource};
+
+impl Client {
+    /// Returns a client that reads and writes the table of the resource T as an external table,
+    /// created outside of dynarust, for example by another team or by Terraform. The keys of
+    /// the items are stored directly in the provided fields of the resource instead of in the
+    /// PrimaryKey and SecondaryKey attributes, so the table does not need any schema change.
+    ///
+    /// The key attributes must be strings, and `Resource::pk_sk` must return the values of the
+    /// provided fields unchanged. The inverted index and the GSI1 index are not available for
+    /// external tables unless the table defines them, and tenant scoping is not available at
+    /// all, so calling this on a tenant scoped client returns an `InvalidRequestError`.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_field` - The field of the resource that is the partition key of the table.
+    /// * `sk_field` - The field of the resource that is the sort key of the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let client = client.with_external_table::<Invoice>("customer_id", "invoice_id")?;
+    ///     let invoice = client.get::<Invoice>(("c-1".into(), "i-42".into())).await?;
+    /// }
+    /// ```
+    pub fn with_external_table<T: Resource>(
+        &self,
+        pk_field: impl Into<String>,
+        sk_field: impl Into<String>,
+    ) -> Result<Client, DynarustError> {
+        if let Some(tenant) = &self.tenant {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "External table {} cannot be used by the scoped client of tenant {}",
+                T::table(),
+                tenant.tenant()
+            )));
+        }
+        let mut external_tables = self.external_tables.as_ref().clone();
+        external_tables.insert(T::table(), (pk_field.into(), sk_field.into()));
+        Ok(Client {
+            external_tables: Arc::new(external_tables),
+            ..self.clone()
+        })
+    }
+
+    /// The names of the partition and sort key attributes of the provided table.
+    pub(crate) fn key_names(&self, table: &str) -> (&str, &str) {
+        match self.external_tables.get(table) {
+            Some((pk, sk)) => (pk, sk),
+            None => (PK, SK),
+        }
+    }
+
+    /// Renames the PrimaryKey and SecondaryKey attributes of an item or key to the real key
+    /// attributes of the table.
+    pub(crate) fn externalize_key(&self, table: &str, key: &mut HashMap<String, AttributeValue>) {
+        let (pk, sk) = self.key_names(table);
+        for (from, to) in [(PK, pk), (SK, sk)] {
+            if from == to {
+                continue;
+            }
+            if let Some(value) = key.remove(from) {
+                key.insert(to.to_string(), value);
+            }
+        }
+    }
+
+    /// Points the expression attribute names that reference the PrimaryKey and SecondaryKey to
+    /// the real key attributes of the table.
+    pub(crate) fn externalize_names(&self, table: &str, names: &mut HashMap<String, String>) {
+        let (pk, sk) = self.key_names(table);
+        for attr in names.values_mut() {
+            if attr == PK {
+                *attr = pk.to_string()
+            } else if attr == SK {
+                *attr = sk.to_string()
+            }
+        }
+    }
+
+    /// Rewrites the key attributes referenced by a transaction item for its table.
+    pub(crate) fn externalize_transaction_item(&self, item: &mut TransactWriteItem) {
+        if self.external_tables.is_empty() {
+            return;
+        }
+        if let Some(put) = item.put.as_mut() {
+            let table = put.table_name.clone().unwrap_or_default();
+            if let Some(key) = put.item.as_mut() {
+                self.externalize_key(&table, key)
+            }
+            if let Some(names) = put.expression_attribute_names.as_mut() {
+                self.externalize_names(&table, names)
+            }
+        }
+        if let Some(update) = item.update.as_mut() {
+            let table = update.table_name.clone().unwrap_or_default();
+            if let Some(key) = update.key.as_mut() {
+                self.externalize_key(&table, key)
+            }
+            if let Some(names) = update.expression_attribute_names.as_mut() {
+                self.externalize_names(&table, names)
+            }
+        }
+        if let Some(delete) = item.delete.as_mut() {
+            let table = delete.table_name.clone().unwrap_or_default();
+            if let Some(key) = delete.key.as_mut() {
+                self.externalize_key(&table, key)
+            }
+            if let Some(names) = delete.expression_attribute_names.as_mut() {
+                self.externalize_names(&table, names)
+            }
+        }
+        if let Some(check) = item.condition_check.as_mut() {
+            let table = check.table_name.clone().unwrap_or_default();
+            if let Some(key) = check.key.as_mut() {
+                self.externalize_key(&table, key)
+            }
+            if let Some(names) = check.expression_attribute_names.as_mut() {
+                self.externalize_names(&table, names)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_config::SdkConfig;
+    use aws_sdk_dynamodb::model::{
+        AttributeDefinition, BillingMode, KeySchemaElement, KeyType, ScalarAttributeType,
+    };
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::TABLE;
+    use crate::{
+        begin_transaction, transact_create, Client, ClientBuilder, DynarustError, ListOptions,
+        Resource,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+    struct Invoice {
+        customer_id: String,
+        invoice_id: String,
+        amount: i64,
+    }
+
+    impl Resource for Invoice {
+        fn table() -> String {
+            format!("{}External", *TABLE)
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.customer_id.clone(), self.invoice_id.clone())
+        }
+    }
+
+    #[test]
+    fn renames_key_attributes() {
+        let client = ClientBuilder::new(SdkConfig::builder().build())
+            .build()
+            .with_external_table::<Invoice>("customer_id", "invoice_id")
+            .unwrap();
+        let invoice = Invoice {
+            customer_id: "c-1".into(),
+            invoice_id: "i-1".into(),
+            amount: 10,
+        };
+        let mut context = begin_transaction();
+        transact_create(&invoice, &mut context).unwrap();
+        let mut item = context.into_items().remove(0);
+        client.externalize_transaction_item(&mut item);
+
+        let put = item.put.unwrap();
+        let mut keys: Vec<_> = put.item.unwrap().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["amount", "customer_id", "invoice_id"]);
+        let mut names: Vec<_> = put
+            .expression_attribute_names
+            .unwrap()
+            .into_values()
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["customer_id", "invoice_id"]);
+        assert_eq!(client.key_names(&TABLE), ("PrimaryKey", "SecondaryKey"));
+    }
+
+    #[test]
+    fn rejects_tenant_scoping_of_external_tables() {
+        let client = ClientBuilder::new(SdkConfig::builder().build()).build();
+        let scoped = client.scoped("tenant").unwrap();
+        assert!(matches!(
+            scoped.with_external_table::<Invoice>("customer_id", "invoice_id"),
+            Err(DynarustError::InvalidRequestError(_))
+        ));
+
+        let external = client
+            .with_external_table::<Invoice>("customer_id", "invoice_id")
+            .unwrap();
+        assert!(matches!(
+            external.scoped("tenant"),
+            Err(DynarustError::InvalidRequestError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_external_tables() {
+        let client = Client::local().await;
+        let _ = client
+            .dynamo(&Invoice::table())
+            .create_table()
+            .table_name(Invoice::table())
+            .attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name("customer_id")
+                    .attribute_type(ScalarAttributeType::S)
+                    .build(),
+            )
+            .attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name("invoice_id")
+                    .attribute_type(ScalarAttributeType::S)
+                    .build(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name("customer_id")
+                    .key_type(KeyType::Hash)
+                    .build(),
+            )
+            .key_schema(
+                KeySchemaElement::builder()
+                    .attribute_name("invoice_id")
+                    .key_type(KeyType::Range)
+                    .build(),
+            )
+            .billing_mode(BillingMode::PayPerRequest)
+            .send()
+            .await;
+        let client = client
+            .with_external_table::<Invoice>("customer_id", "invoice_id")
+            .unwrap();
+
+        let invoice = Invoice {
+            customer_id: "reads_and_writes_external_tables".into(),
+            invoice_id: "1".into(),
+            amount: 10,
+        };
+        client.create(&invoice).await.unwrap();
+        assert!(client.create(&invoice).await.is_err());
+
+        let updated = client
+            .update(&invoice, json!({ "amount": 20 }))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.get::<Invoice>(invoice.pk_sk()).await.unwrap(),
+            Some(updated.clone())
+        );
+
+        let mut context = begin_transaction();
+        transact_create(
+            &Invoice {
+                invoice_id: "2".into(),
+                ..invoice.clone()
+            },
+            &mut context,
+        )
+        .unwrap();
+        client.execute_transaction(context).await.unwrap();
+
+        let listed = client
+            .list::<Invoice>(invoice.customer_id.clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.items.len(), 2);
+
+        client.delete::<Invoice>(invoice.pk_sk()).await.unwrap();
+        assert_eq!(client.get::<Invoice>(invoice.pk_sk()).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Typed handle to the attribute `name` of resource T, whose values are of type V. Handles
+/// are generated with the `resource_fields!` macro, which checks at compile time that the
+/// field exists in the resource with that type, so they can be used in update requests and
+/// condition checks instead of string literals.
+pub struct Field<T, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, V)>,
+}
+
+impl<T, V> Field<T, V> {
+    /// Creates a handle without any validation, prefer the `resource_fields!` macro.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Name of the attribute in dynamo.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T, V: Serialize> Field<T, V> {
+    /// Builds an update request that sets this attribute to the provided value.
+    pub fn set(&self, value: V) -> Value {
+        json!({ self.name: value })
+    }
+}
+
+impl<T, V> Clone for Field<T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for Field<T, V> {}
+
+impl<T, V> Debug for Field<T, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Field").field(&self.name).finish()
+    }
+}
+
+impl<T, V> AsRef<str> for Field<T, V> {
+    fn as_ref(&self) -> &str {
+        self.name
+    }
+}
+
+/// Generates a struct of typed field handles for a resource and a `fields()` function on the
+/// resource that returns it. A field that does not exist in the resource, or whose type does
+/// not match, is a compile error. The generated items take the visibility that precedes the
+/// resource name. Fields renamed with serde attributes are not supported, as
+/// the attribute name is the name of the rust field.
+///
+/// # Examples
+///
+/// ```
+/// dynarust::resource_fields!(pub User => UserFields { name: String, age: u32 });
+///
+/// async {
+///     client.update_with_checks(
+///         &user,
+///         User::fields().age.set(31),
+///         vec![dynarust::condition_check_number(User::fields().age, DynamoOperator::Eq, 30)],
+///     ).await?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! resource_fields {
+    ($vis:vis $resource:ident => $fields:ident { $($field:ident : $type:ty),* $(,)? }) => {
+        #[allow(dead_code)]
+        #[derive(Debug, Clone, Copy)]
+        $vis struct $fields {
+            $($vis $field: $crate::Field<$resource, $type>,)*
+        }
+
+        impl $resource {
+            /// Typed handles to the fields of this resource.
+            $vis const fn fields() -> $fields {
+                const _: fn(&$resource) = |resource| {
+                    $(let _: &$type = &resource.$field;)*
+                };
+                $fields {
+                    $($field: $crate::Field::new(stringify!($field)),)*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{condition_check_number, Client, DynamoOperator, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+    struct Player {
+        name: String,
+        level: i64,
+    }
+
+    impl Resource for Player {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("players".into(), self.name.clone())
+        }
+    }
+
+    resource_fields!(Player => PlayerFields { name: String, level: i64 });
+
+    #[test]
+    fn builds_update_requests_from_fields() {
+        assert_eq!(Player::fields().level.name(), "level");
+        assert_eq!(Player::fields().level.set(3), json!({ "level": 3 }));
+    }
+
+    #[tokio::test]
+    async fn updates_through_typed_fields() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let player = Player {
+            name: "updates_through_typed_fields".into(),
+            level: 1,
+        };
+        client.create(&player).await.unwrap();
+
+        let level = Player::fields().level;
+        let updated = client
+            .update_with_checks(
+                &player,
+                level.set(2),
+                vec![condition_check_number(level, DynamoOperator::Eq, 1)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.level, 2);
+
+        let err = client
+            .update_with_checks(
+                &player,
+                level.set(3),
+                vec![condition_check_number(level, DynamoOperator::Eq, 1)],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "The conditional request failed");
+    }
+}
@@ -0,0 +1,118 @@
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::{Client, ConditionCheckInfo, DynarustError, Resource};
+
+impl Client {
+    /// Finds every resource of type T that matches the provided filter, streaming them as
+    /// they are read.
+    ///
+    /// **This scans the whole table.** Dynamo applies the filter after reading the items, so
+    /// every item of the table is read and billed, including the ones that do not match and
+    /// the ones of other resources sharing the table, no matter how few resources match. It is
+    /// meant for admin and debug tooling on tables without a suitable index, never for request
+    /// paths. Prefer `list` or an index query whenever the access pattern allows it.
+    ///
+    /// Items that cannot be deserialized as T are skipped.
+    ///
+    /// # arguments
+    ///
+    /// * `filter` - The condition that the found resources must match, built with the same
+    ///   functions as condition checks. An empty filter finds every resource.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// async {
+    ///     let filter = dynarust::condition_check_string("status", DynamoOperator::Eq, "pending");
+    ///     let stuck: Vec<Order> = client
+    ///         .find_where::<Order>(filter)
+    ///         .try_collect()
+    ///         .await?;
+    /// }
+    /// ```
+    pub fn find_where<T: Resource + DeserializeOwned>(
+        &self,
+        filter: ConditionCheckInfo,
+    ) -> impl Stream<Item = Result<T, DynarustError>> + '_ {
+        let table = T::table();
+        let (expression, names, values) = filter.into_parts();
+        let filtered = !expression.is_empty();
+
+        let pages = stream::try_unfold(Some(None), move |exclusive_start_key| {
+            let (table, expression, names, values) = (
+                table.clone(),
+                expression.clone(),
+                names.clone(),
+                values.clone(),
+            );
+            async move {
+                let Some(exclusive_start_key) = exclusive_start_key else {
+                    return Ok(None);
+                };
+                let mut builder = self
+                    .dynamo(&table)
+                    .scan()
+                    .table_name(&table)
+                    .set_exclusive_start_key(exclusive_start_key);
+                if filtered {
+                    builder = builder
+                        .filter_expression(expression)
+                        .set_expression_attribute_names(Some(names))
+                        .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()));
+                }
+                let result = builder.send().await?;
+
+                let mut resources = vec![];
+                for item in result.items().unwrap_or_default() {
+                    if !self.owns_item(item) {
+                        continue;
+                    }
+                    if let Ok(resource) = Client::value_as_resource(self.decode_item(item)?) {
+                        resources.push(Ok(resource));
+                    }
+                }
+                let next = result.last_evaluated_key().cloned().map(Some);
+                Ok::<_, DynarustError>(Some((resources, next)))
+            }
+        });
+
+        pages
+            .map_ok(|resources: Vec<Result<T, DynarustError>>| stream::iter(resources))
+            .try_flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use crate::client::tests::TestResource;
+    use crate::{condition_check_number, Client, DynamoOperator};
+
+    #[tokio::test]
+    async fn finds_resources_matching_a_filter() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        for i in 0..5 {
+            let resource = TestResource {
+                pk: "finds_resources_matching_a_filter".to_string(),
+                sk: i.to_string(),
+                int: 9_000_000 + i % 2,
+                ..Default::default()
+            };
+            client.force_create(&resource).await.unwrap();
+        }
+
+        let mut found: Vec<TestResource> = client
+            .find_where(condition_check_number("int", DynamoOperator::Eq, 9_000_001))
+            .try_collect()
+            .await
+            .unwrap();
+        found.sort_by(|a, b| a.sk.cmp(&b.sk));
+        let sks: Vec<_> = found.iter().map(|r| r.sk.as_str()).collect();
+        assert_eq!(sks, vec!["1", "3"]);
+    }
+}
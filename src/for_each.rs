@@ -0,0 +1,181 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use futures::future::try_join_all;
+use serde::de::DeserializeOwned;
+
+use crate::{Client, DynarustError, Resource};
+
+/// Progress of a `for_each_item` run, reported after every scanned page.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ForEachProgress {
+    /// items read from the table, only the ones of the tenant for scoped clients.
+    pub read: usize,
+    /// resources on which the callback was invoked.
+    pub processed: usize,
+    /// items that could not be read as the resource, like the ones of other resources sharing
+    /// the table.
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+/// Options of a `for_each_item` run.
+#[derive(Default)]
+pub struct ForEachOptions {
+    /// called after every scanned page, from any of the segments.
+    pub on_progress: Option<Box<dyn Fn(ForEachProgress) + Send + Sync>>,
+}
+
+impl Client {
+    /// Invokes an async callback on every resource of type T, reading its table with a
+    /// parallel scan of `concurrency` segments. Each segment awaits the callback on its
+    /// resources one at a time, so at most `concurrency` callbacks run at once. This is the
+    /// backbone of backfills, integrity checks and re-indexing jobs. The first error returned
+    /// by the callback stops the run. Items that cannot be deserialized as T are skipped.
+    ///
+    /// # arguments
+    ///
+    /// * `concurrency` - number of segments of the scan, and of callbacks that run at once.
+    /// * `f` - callback invoked with every resource.
+    /// * `options` - optional progress options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let progress = client
+    ///         .for_each_item::<User, _, _>(
+    ///             8,
+    ///             |user| async move {
+    ///                 search.index(&user).await?;
+    ///                 Ok(())
+    ///             },
+    ///             Some(ForEachOptions {
+    ///                 on_progress: Some(Box::new(|p| println!("{} users indexed", p.processed))),
+    ///             }),
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn for_each_item<T, F, Fut>(
+        &self,
+        concurrency: usize,
+        f: F,
+        options: Option<ForEachOptions>,
+    ) -> Result<ForEachProgress, DynarustError>
+    where
+        T: Resource + DeserializeOwned,
+        F: Fn(T) -> Fut + Sync,
+        Fut: Future<Output = Result<(), DynarustError>>,
+    {
+        let options = options.unwrap_or_default();
+        let segments = concurrency.clamp(1, i32::MAX as usize) as i32;
+        let table = T::table();
+        let start = Instant::now();
+        let progress = Mutex::new(ForEachProgress::default());
+
+        let report = |read: usize, processed: usize, skipped: usize| {
+            let Ok(mut progress) = progress.lock() else {
+                return;
+            };
+            progress.read += read;
+            progress.processed += processed;
+            progress.skipped += skipped;
+            progress.elapsed = start.elapsed();
+            if let Some(on_progress) = &options.on_progress {
+                on_progress(*progress);
+            }
+        };
+
+        try_join_all((0..segments).map(|segment| {
+            let (table, f, report) = (&table, &f, &report);
+            async move {
+                let mut exclusive_start_key = None;
+                loop {
+                    let (items, last_evaluated_key) = self
+                        .scan_segment_page(table, segment, segments, None, exclusive_start_key)
+                        .await?;
+                    let (mut read, mut processed) = (0, 0);
+                    for item in items.iter().filter(|item| self.owns_item(item)) {
+                        read += 1;
+                        if let Ok(resource) =
+                            Client::value_as_resource::<T>(self.decode_item(item)?)
+                        {
+                            f(resource).await?;
+                            processed += 1;
+                        }
+                    }
+                    report(read, processed, read - processed);
+
+                    exclusive_start_key = last_evaluated_key;
+                    if exclusive_start_key.is_none() {
+                        return Ok::<_, DynarustError>(());
+                    }
+                }
+            }
+        }))
+        .await?;
+
+        let mut progress = progress
+            .into_inner()
+            .map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+        progress.elapsed = start.elapsed();
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use crate::client::tests::TestResource;
+    use crate::{Client, DynarustError, ForEachOptions};
+
+    #[tokio::test]
+    async fn visits_every_resource() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let tenant = client.scoped("visits_every_resource").unwrap();
+        for i in 1..=5 {
+            let resource = TestResource {
+                pk: "visited".to_string(),
+                sk: i.to_string(),
+                int: i,
+                ..Default::default()
+            };
+            tenant.force_create(&resource).await.unwrap();
+        }
+
+        let sum = AtomicI64::new(0);
+        let reported = Arc::new(Mutex::new(0));
+        let on_progress = reported.clone();
+        let progress = tenant
+            .for_each_item::<TestResource, _, _>(
+                3,
+                |resource| {
+                    sum.fetch_add(resource.int, Ordering::SeqCst);
+                    async { Ok(()) }
+                },
+                Some(ForEachOptions {
+                    on_progress: Some(Box::new(move |p| *on_progress.lock().unwrap() = p.read)),
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(sum.load(Ordering::SeqCst), 15);
+        assert_eq!((progress.read, progress.processed), (5, 5));
+        assert_eq!(*reported.lock().unwrap(), 5);
+
+        let err = tenant
+            .for_each_item::<TestResource, _, _>(
+                2,
+                |_| async { Err(DynarustError::UnexpectedError("stop".into())) },
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected error: stop");
+    }
+}
@@ -1,11 +1,19 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::time::Duration;
 
-use aws_sdk_dynamodb::model::{AttributeValue, KeysAndAttributes};
+use aws_sdk_dynamodb::model::{AttributeValue, KeysAndAttributes, ReturnConsumedCapacity};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use crate::client::{PK, SK};
-use crate::{Client, DynarustError, Resource};
+use crate::stats::Operation;
+use crate::{Client, DynamoStore, DynarustError, Resource};
+
+/// Maximum number of keys in a single batch get.
+pub(crate) const MAX_BATCH_GET_SIZE: usize = 100;
+/// Times that the keys left unprocessed by a batch get are retried.
+const MAX_BATCH_GET_RETRIES: usize = 8;
 
 impl Client {
     /// Retrieves a resource. If the resource does not exist returns Option::None.
@@ -14,31 +22,13 @@ impl Client {
     /// * `pk_sk` - Pk and sk pair for identifying the resource
     pub async fn get<T: Resource + DeserializeOwned>(
         &self,
-        (pk, sk): (String, String),
+        pk_sk: (String, String),
     ) -> Result<Option<T>, DynarustError> {
-        let result = self
-            .client
-            .get_item()
-            .table_name(T::table())
-            .key(PK, AttributeValue::S(pk))
-            .key(SK, AttributeValue::S(sk))
-            .send()
-            .await?;
-
-        if let Some(item) = result.item() {
-            let mut object = Value::Object(serde_json::Map::new());
-            for (k, v) in item {
-                object[k] = Self::attr2value(v)?
-            }
-            let t: T = serde_json::from_value(object)?;
-            Ok(Some(t))
-        } else {
-            Ok(None)
-        }
+        get_resource(self, pk_sk).await
     }
 
     /// Retrieves multiple resource in the same operation. If one of the resources do not exist
-    /// it will not be present in the resulting HashMap.
+    /// it will not be present in the resulting HashMap. No keys return an empty HashMap.
     ///
     /// # arguments
     /// * `items` - Array of pk and sk pairs identifying the resource that will be retrieved.
@@ -46,49 +36,259 @@ impl Client {
         &self,
         items: Vec<(String, String)>,
     ) -> Result<HashMap<(String, String), T>, DynarustError> {
-        let mut builder = KeysAndAttributes::builder();
+        batch_get_resources(self, items).await
+    }
+
+    /// Retrieves multiple resources in the same operation, returning an entry for every
+    /// requested key: Some with the resource if it exists, or None if it does not. Keys
+    /// requested more than once get a single entry.
+    ///
+    /// # arguments
+    /// * `items` - Array of pk and sk pairs identifying the resource that will be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let users = client.batch_get_all::<User>(keys).await?;
+    ///     for (key, user) in users {
+    ///         if user.is_none() {
+    ///             println!("user {key:?} does not exist");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn batch_get_all<T: Resource + DeserializeOwned>(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<HashMap<(String, String), Option<T>>, DynarustError> {
+        batch_get_all_resources(self, items).await
+    }
 
-        for (pk, sk) in items {
-            builder = builder.keys(HashMap::from([
-                (PK.to_string(), AttributeValue::S(pk)),
-                (SK.to_string(), AttributeValue::S(sk)),
-            ]))
+    /// Retrieves multiple resources in the same operation, returning them in the same order as
+    /// the requested keys, with None in the positions of the resources that do not exist.
+    /// Keys requested more than once get a copy of the resource in each of their positions.
+    ///
+    /// # arguments
+    /// * `items` - Array of pk and sk pairs identifying the resource that will be retrieved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let keys = ids.iter().map(|id| ("users".to_string(), id.clone())).collect();
+    ///     let users: Vec<Option<User>> = client.batch_get_ordered(keys).await?;
+    /// }
+    /// ```
+    pub async fn batch_get_ordered<T: Resource + DeserializeOwned + Clone>(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<Vec<Option<T>>, DynarustError> {
+        batch_get_ordered_resources(self, items).await
+    }
+
+    /// Retrieves a large number of resources, yielding them as they are read instead of
+    /// buffering all of them. The keys are consumed lazily in chunks of 100, which are read
+    /// with up to `concurrency` batch gets in flight, so memory stays flat no matter how many
+    /// keys there are. The resources are yielded in no particular order, and the ones that do
+    /// not exist are not yielded.
+    ///
+    /// # arguments
+    /// * `keys` - pk and sk pairs identifying the resources that will be retrieved.
+    /// * `concurrency` - maximum number of batch gets in flight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// async {
+    ///     let keys = order_ids.into_iter().map(|id| ("order".to_string(), id));
+    ///     let mut orders = client.batch_get_stream::<Order, _>(keys, 8);
+    ///     while let Some(order) = orders.try_next().await? {
+    ///         process(order);
+    ///     }
+    /// }
+    /// ```
+    pub fn batch_get_stream<'a, T, I>(
+        &'a self,
+        keys: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<T, DynarustError>> + 'a
+    where
+        T: Resource + DeserializeOwned + 'a,
+        I: IntoIterator<Item = (String, String)> + 'a,
+    {
+        let table = T::table();
+        stream::iter(keys)
+            .chunks(MAX_BATCH_GET_SIZE)
+            .map(move |chunk| {
+                let table = table.clone();
+                async move { batch_get_items(self, &table, chunk).await }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .map_ok(|values| stream::iter(values.into_iter().map(Client::value_as_resource)))
+            .try_flatten()
+    }
+}
+
+pub(crate) async fn get_resource<S: DynamoStore + ?Sized, T: Resource + DeserializeOwned>(
+    store: &S,
+    pk_sk: (String, String),
+) -> Result<Option<T>, DynarustError> {
+    match store.get_item(&T::table(), pk_sk).await? {
+        Some(item) => Ok(Some(Client::value_as_resource(item)?)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) async fn batch_get_resources<S: DynamoStore + ?Sized, T: Resource + DeserializeOwned>(
+    store: &S,
+    items: Vec<(String, String)>,
+) -> Result<HashMap<(String, String), T>, DynarustError> {
+    let mut resources = HashMap::new();
+    for item in store.batch_get_items(&T::table(), items).await? {
+        let t: T = Client::value_as_resource(item)?;
+        resources.insert(t.pk_sk(), t);
+    }
+    Ok(resources)
+}
+
+pub(crate) async fn batch_get_all_resources<S, T>(
+    store: &S,
+    items: Vec<(String, String)>,
+) -> Result<HashMap<(String, String), Option<T>>, DynarustError>
+where
+    S: DynamoStore + ?Sized,
+    T: Resource + DeserializeOwned,
+{
+    let mut found = batch_get_resources::<S, T>(store, items.clone()).await?;
+    let mut resources = HashMap::new();
+    for key in items {
+        if let Entry::Vacant(entry) = resources.entry(key) {
+            let resource = found.remove(entry.key());
+            entry.insert(resource);
         }
+    }
+    Ok(resources)
+}
+
+pub(crate) async fn batch_get_ordered_resources<S, T>(
+    store: &S,
+    items: Vec<(String, String)>,
+) -> Result<Vec<Option<T>>, DynarustError>
+where
+    S: DynamoStore + ?Sized,
+    T: Resource + DeserializeOwned + Clone,
+{
+    let found = batch_get_resources::<S, T>(store, items.clone()).await?;
+    Ok(items.iter().map(|key| found.get(key).cloned()).collect())
+}
+
+pub(crate) async fn get_item(
+    client: &Client,
+    table: &str,
+    (pk, sk): (String, String),
+) -> Result<Option<Value>, DynarustError> {
+    let (pk_attr, sk_attr) = client.key_names(table);
+    let result = client
+        .dynamo(table)
+        .get_item()
+        .table_name(table)
+        .key(pk_attr, AttributeValue::S(client.scope_pk(pk)))
+        .key(sk_attr, AttributeValue::S(sk))
+        .consistent_read(client.consistent_read(None))
+        .return_consumed_capacity(ReturnConsumedCapacity::Total)
+        .send()
+        .await?;
+    client.stats.add_capacity(
+        Operation::GetItem,
+        result.consumed_capacity().and_then(|c| c.capacity_units()),
+    );
 
-        let result = self
-            .client
+    match result.item() {
+        Some(item) => Ok(Some(
+            client
+                .decode_item(item)
+                .map_err(|err| err.in_table(table))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+pub(crate) async fn batch_get_items(
+    client: &Client,
+    table: &str,
+    items: Vec<(String, String)>,
+) -> Result<Vec<Value>, DynarustError> {
+    if items.is_empty() {
+        return Ok(vec![]);
+    }
+    let (pk_attr, sk_attr) = client.key_names(table);
+    let mut builder = KeysAndAttributes::builder().consistent_read(client.consistent_read(None));
+
+    for (pk, sk) in items {
+        builder = builder.keys(HashMap::from([
+            (pk_attr.to_string(), AttributeValue::S(client.scope_pk(pk))),
+            (sk_attr.to_string(), AttributeValue::S(sk)),
+        ]))
+    }
+
+    let mut values = vec![];
+    let mut request = builder.build();
+    let mut backoff = Duration::from_millis(50);
+    for attempt in 0..=MAX_BATCH_GET_RETRIES {
+        if attempt > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(5));
+        }
+        let result = client
+            .dynamo(table)
             .batch_get_item()
-            .request_items(T::table(), builder.build())
+            .request_items(table, request)
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
             .send()
             .await?;
-
-        let mut resources = HashMap::new();
+        client
+            .stats
+            .add_all_capacity(Operation::BatchGetItems, result.consumed_capacity());
 
         if let Some(responses) = result.responses() {
-            let responses = responses.get(&T::table()).ok_or_else(|| {
+            let responses = responses.get(table).ok_or_else(|| {
                 DynarustError::UnexpectedError(
                     "Table was not returned in that batch items response".to_string(),
                 )
             })?;
 
             for item in responses {
-                let mut object = Value::Object(serde_json::Map::new());
-                for (k, v) in item {
-                    object[k] = Self::attr2value(v)?
-                }
-                let t: T = serde_json::from_value(object)?;
-                resources.insert(t.pk_sk(), t);
+                values.push(
+                    client
+                        .decode_item(item)
+                        .map_err(|err| err.in_table(table))?,
+                );
             }
-        } else {
-            return Ok(HashMap::new());
         }
 
-        Ok(resources)
+        match result
+            .unprocessed_keys()
+            .and_then(|unprocessed| unprocessed.get(table))
+        {
+            Some(unprocessed) if !unprocessed.keys().unwrap_or_default().is_empty() => {
+                request = unprocessed.clone()
+            }
+            _ => return Ok(values),
+        }
     }
+
+    Err(DynarustError::DynamoError(format!(
+        "{} keys of table {table} were left unprocessed after every retry",
+        request.keys().unwrap_or_default().len()
+    )))
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::TryStreamExt;
+
     use crate::client::tests::TestResource;
     use crate::{Client, Resource};
 
@@ -141,13 +341,70 @@ mod tests {
 
     #[tokio::test]
     async fn batch_gets_empty() {
+        let client = Client::local_on_port(1).await;
+
+        let retrieved = client.batch_get::<TestResource>(vec![]).await.unwrap();
+
+        assert!(retrieved.is_empty())
+    }
+
+    #[tokio::test]
+    async fn batch_gets_with_misses() {
         let client = Client::local().await;
         client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "batch_gets_with_misses".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
 
-        let err = client.batch_get::<TestResource>(vec![]).await.unwrap_err();
+        let missing = ("batch_gets_with_misses".to_string(), "2".to_string());
+        let retrieved = client
+            .batch_get_all::<TestResource>(vec![
+                resource.pk_sk(),
+                missing.clone(),
+                resource.pk_sk(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(retrieved.len(), 2);
+        assert_eq!(retrieved[&resource.pk_sk()], Some(resource.clone()));
+        assert_eq!(retrieved[&missing], None);
 
-        assert!(err
-            .to_string()
-            .starts_with("The list of keys in RequestItems for BatchGetItem is required"))
+        let ordered = client
+            .batch_get_ordered::<TestResource>(vec![missing, resource.pk_sk(), resource.pk_sk()])
+            .await
+            .unwrap();
+        assert_eq!(ordered, vec![None, Some(resource.clone()), Some(resource)]);
+    }
+
+    #[tokio::test]
+    async fn streams_batch_gets() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "streams_batch_gets".to_string();
+        let resources: Vec<_> = (0..150)
+            .map(|i| TestResource {
+                pk: pk.clone(),
+                sk: i.to_string(),
+                int: i,
+                ..Default::default()
+            })
+            .collect();
+        crate::bulk::load_resources(&client, resources, Default::default())
+            .await
+            .unwrap();
+
+        let keys = (0..160).map(|i| (pk.clone(), i.to_string()));
+        let mut retrieved: Vec<TestResource> = client
+            .batch_get_stream(keys, 3)
+            .try_collect()
+            .await
+            .unwrap();
+        retrieved.sort_by_key(|r| r.int);
+        assert_eq!(retrieved.len(), 150);
+        assert_eq!(retrieved[149].int, 149);
     }
 }
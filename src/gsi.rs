@@ -0,0 +1,528 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::client::{GSI1_INDEX, GSI1_PK, GSI1_SK, INVERTED_INDEX, PK, SK};
+use crate::get::{batch_get_items, MAX_BATCH_GET_SIZE};
+use crate::list::{list_key_condition, query_values};
+use crate::stats::Operation;
+use crate::{Client, DynarustError, ListOptions, Page, Resource};
+
+/// A global secondary index of the table of a resource, whose keys are string attributes
+/// written from `Resource::index_keys`, or one of the indexes that dynarust writes the keys of
+/// on its own, like `SecondaryIndex::INVERTED`.
+///
+/// # Examples
+///
+/// ```
+/// impl dynarust::Resource for Order {
+///     fn table() -> String { "Orders".into() }
+///     fn pk_sk(&self) -> (String, String) { (self.customer.clone(), self.id.clone()) }
+///     fn secondary_indexes() -> &'static [SecondaryIndex] {
+///         const INDEXES: &[SecondaryIndex] = &[SecondaryIndex::new("ByStatus", "StatusPk", Some("StatusSk"))];
+///         INDEXES
+///     }
+///     fn index_keys(&self) -> Vec<(&'static str, String)> {
+///         vec![("StatusPk", self.status.clone()), ("StatusSk", self.created_at.to_rfc3339())]
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecondaryIndex {
+    pub name: &'static str,
+    /// attribute of the PrimaryKey of the index.
+    pub pk_attribute: &'static str,
+    /// attribute of the SecondaryKey of the index, which has none if not provided.
+    pub sk_attribute: Option<&'static str>,
+}
+
+impl SecondaryIndex {
+    /// The index whose keys are the SecondaryKey and the PrimaryKey of the table, needed for
+    /// listing edges of an adjacency list by their target with `Client::list_edges_to`.
+    pub const INVERTED: SecondaryIndex = SecondaryIndex::new(INVERTED_INDEX, SK, Some(PK));
+
+    /// The index whose keys are the ones returned by `Resource::gsi1_pk_sk`, needed by
+    /// `Client::list_by_gsi1`.
+    pub const GSI1: SecondaryIndex = SecondaryIndex::new(GSI1_INDEX, GSI1_PK, Some(GSI1_SK));
+
+    pub const fn new(
+        name: &'static str,
+        pk_attribute: &'static str,
+        sk_attribute: Option<&'static str>,
+    ) -> Self {
+        Self {
+            name,
+            pk_attribute,
+            sk_attribute,
+        }
+    }
+
+    /// The key attributes of the index.
+    pub(crate) fn attributes(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.pk_attribute).chain(self.sk_attribute)
+    }
+}
+
+/// Whether the attribute is a key of one of the secondary indexes of resource T.
+pub(crate) fn is_index_attribute<T: Resource>(attr: &str) -> bool {
+    T::secondary_indexes()
+        .iter()
+        .any(|index| index.attributes().any(|a| a == attr))
+}
+
+impl Client {
+    /// Lists the resources whose GSI1 PrimaryKey, as returned by `Resource::gsi1_pk_sk`, is the
+    /// provided one, querying the GSI1 index of the table, which has to be created with
+    /// `SecondaryIndex::GSI1` in the `Resource::secondary_indexes` of T or in the options of
+    /// `create_table`. The `from` and `sk_condition` fields of the options are ignored, and
+    /// strongly consistent reads are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `gsi1_pk` - GSI1 PrimaryKey of the listed resources.
+    /// * `options` - optional pagination options.
+    pub async fn list_by_gsi1<T: Resource + DeserializeOwned>(
+        &self,
+        gsi1_pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        let builder = self
+            .dynamo(&T::table())
+            .query()
+            .table_name(T::table())
+            .index_name(GSI1_INDEX)
+            .key_condition_expression("#pk = :pk")
+            .expression_attribute_names("#pk", GSI1_PK)
+            .expression_attribute_values(":pk", AttributeValue::S(self.scope_pk(gsi1_pk)))
+            .consistent_read(self.index_consistent_read(options)?);
+
+        self.query_page(builder, options).await
+    }
+
+    /// Lists the resources with the provided PrimaryKey in one of the `secondary_indexes` of
+    /// resource T, one page at a time, with the same options as `list`. If the index has a
+    /// SecondaryKey, the resources are sorted by it and `from` and `sk_condition` apply to it,
+    /// otherwise they are rejected. Strongly consistent reads are rejected too.
+    ///
+    /// The index keys are written as `Resource::index_keys` returns them, without the tenant
+    /// prefix of scoped clients, so tenant scoped clients are rejected, as the index would give
+    /// them the resources of every tenant. They can use `list_by_gsi1`, whose keys are scoped.
+    ///
+    /// # arguments
+    ///
+    /// * `index_name` - Name of one of the `secondary_indexes` of resource T.
+    /// * `pk` - Value of the index PrimaryKey of the listed resources.
+    /// * `options` - optional pagination options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let pending = client
+    ///         .query_index::<Order>("ByStatus", "pending".into(), &ListOptions::default())
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn query_index<T: Resource + DeserializeOwned>(
+        &self,
+        index_name: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        let index = T::secondary_indexes()
+            .iter()
+            .find(|index| index.name == index_name)
+            .ok_or_else(|| {
+                DynarustError::InvalidRequestError(format!(
+                    "{index_name} is not a secondary index of the resource"
+                ))
+            })?;
+        if let Some(tenant) = &self.tenant {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "{index_name} cannot be queried by the scoped client of tenant {}",
+                tenant.tenant()
+            )));
+        }
+
+        let (expression, mut values) = list_key_condition(options)?;
+        let mut names = HashMap::from([("#pk".to_string(), index.pk_attribute.to_string())]);
+        if !values.is_empty() {
+            let sk_attribute = index.sk_attribute.ok_or_else(|| {
+                DynarustError::InvalidRequestError(format!(
+                    "{index_name} has no SecondaryKey to apply the sort key options to"
+                ))
+            })?;
+            names.insert("#sk".to_string(), sk_attribute.to_string());
+        }
+        values.insert(":pk".to_string(), AttributeValue::S(pk));
+
+        let table = T::table();
+        let builder = self
+            .dynamo(&table)
+            .query()
+            .table_name(&table)
+            .index_name(index.name)
+            .key_condition_expression(expression)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .consistent_read(self.index_consistent_read(options)?);
+
+        self.query_page(builder, options).await
+    }
+
+    /// Lists resources through a global secondary index that only projects the keys, which is
+    /// the usual setup for sparse indexes. The index is queried for the keys of one page, and
+    /// then the full items are read from the base table with batch gets, retrying the keys
+    /// that dynamo leaves unprocessed. The resources keep the order of the index, and the ones
+    /// deleted between both reads are left out of the page. The `from` and `sk_condition`
+    /// fields of the options are ignored, and strongly consistent reads are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `index_name` - Name of the global secondary index.
+    /// * `index_pk_attr` - Attribute that is the PrimaryKey of the index.
+    /// * `index_pk` - Value of the index PrimaryKey of the listed resources. It is prefixed
+    ///   with the tenant id of scoped clients, as dynarust does with its own index keys.
+    /// * `options` - optional pagination options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let page = client
+    ///         .query_index_hydrated::<Order>(
+    ///             "PendingOrders",
+    ///             "pending_since",
+    ///             "2023-06".into(),
+    ///             &ListOptions::default(),
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn query_index_hydrated<T: Resource + DeserializeOwned>(
+        &self,
+        index_name: &str,
+        index_pk_attr: &str,
+        index_pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        let table = T::table();
+        let (pk_attr, sk_attr) = self.key_names(&table);
+        let builder = self
+            .dynamo(&table)
+            .query()
+            .table_name(&table)
+            .index_name(index_name)
+            .key_condition_expression("#indexPk = :indexPk")
+            .projection_expression("#pk, #sk")
+            .expression_attribute_names("#indexPk", index_pk_attr)
+            .expression_attribute_names("#pk", pk_attr)
+            .expression_attribute_names("#sk", sk_attr)
+            .expression_attribute_values(":indexPk", AttributeValue::S(self.scope_pk(index_pk)))
+            .consistent_read(self.index_consistent_read(options)?);
+        let page = query_values(self, &table, builder, options).await?;
+
+        let key = |value: &Value| match (value.get(pk_attr), value.get(sk_attr)) {
+            (Some(Value::String(pk)), Some(Value::String(sk))) => Some((pk.clone(), sk.clone())),
+            _ => None,
+        };
+        let keys: Vec<(String, String)> = page.items.iter().filter_map(key).collect();
+        let mut items = HashMap::new();
+        for chunk in keys.chunks(MAX_BATCH_GET_SIZE) {
+            let unscoped = chunk
+                .iter()
+                .filter_map(|(pk, sk)| match &self.tenant {
+                    Some(tenant) => Some((tenant.strip(pk)?.to_string(), sk.clone())),
+                    None => Some((pk.clone(), sk.clone())),
+                })
+                .collect();
+            let batch_get = batch_get_items(self, &table, unscoped);
+            for item in self
+                .stats
+                .measure(Operation::BatchGetItems, batch_get)
+                .await?
+            {
+                if let Some(key) = key(&item) {
+                    items.insert(key, item);
+                }
+            }
+        }
+
+        let mut resources = vec![];
+        for key in keys {
+            if let Some(item) = items.remove(&key) {
+                resources.push(Client::value_as_resource(item)?)
+            }
+        }
+        Ok(Page {
+            items: resources,
+            next: page.next,
+            scanned_count: page.scanned_count,
+            count: page.count,
+            consumed_capacity: page.consumed_capacity,
+        })
+    }
+}
+
+/// Types of the fields from which `resource_gsi1` builds index keys, which are the ones that
+/// always have a value, so using an optional field is a compile error rather than an item
+/// written with a `None` key.
+pub trait IndexKey: std::fmt::Display {}
+
+impl IndexKey for String {}
+impl IndexKey for str {}
+impl<T: IndexKey + ?Sized> IndexKey for &T {}
+impl IndexKey for i32 {}
+impl IndexKey for i64 {}
+impl IndexKey for u32 {}
+impl IndexKey for u64 {}
+impl IndexKey for usize {}
+
+/// Generates the GSI1 keys of a resource from one of its fields, and a typed query method for
+/// listing the resources by that field. The GSI1 PrimaryKey is the field name and its value,
+/// like `email#john@example.com`, so different resources indexed in the same table do not
+/// collide. The GSI1 SecondaryKey is the SecondaryKey of the resource, unless another field
+/// is provided with `sk`. The generated `gsi1_keys` method must be returned from
+/// `Resource::gsi1_pk_sk`, and `SecondaryIndex::GSI1` listed in `Resource::secondary_indexes`.
+/// Key fields that do not exist, or that are not strings or integers, like optional ones, are
+/// a compile error.
+///
+/// # Examples
+///
+/// ```
+/// impl dynarust::Resource for User {
+///     fn table() -> String { "Users".into() }
+///     fn pk_sk(&self) -> (String, String) { ("users".into(), self.id.clone()) }
+///     fn gsi1_pk_sk(&self) -> Option<(String, String)> { self.gsi1_keys() }
+///     fn secondary_indexes() -> &'static [SecondaryIndex] { &[SecondaryIndex::GSI1] }
+/// }
+///
+/// dynarust::resource_gsi1!(pub User, pk = email, by_email);
+///
+/// async {
+///     let users = User::by_email(&client, "john@example.com", &ListOptions::default()).await?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! resource_gsi1 {
+    ($vis:vis $resource:ident, pk = $pk:ident, $method:ident) => {
+        $crate::resource_gsi1!(@impl $vis $resource, [$pk], $pk, $method, |resource: &$resource| {
+            $crate::Resource::pk_sk(resource).1
+        });
+    };
+    ($vis:vis $resource:ident, pk = $pk:ident, sk = $sk:ident, $method:ident) => {
+        $crate::resource_gsi1!(@impl $vis $resource, [$pk, $sk], $pk, $method, |resource: &$resource| {
+            resource.$sk.to_string()
+        });
+    };
+    (@impl $vis:vis $resource:ident, [$($key:ident),*], $pk:ident, $method:ident, $sk:expr) => {
+        impl $resource {
+            /// Keys of this resource in the GSI1 index.
+            $vis fn gsi1_keys(&self) -> Option<(String, String)> {
+                const _: fn(&$resource) = |resource| {
+                    fn index_key<K: $crate::IndexKey + ?Sized>(_: &K) {}
+                    $(index_key(&resource.$key);)*
+                };
+                let gsi1_pk = format!("{}#{}", stringify!($pk), self.$pk);
+                Some((gsi1_pk, ($sk)(self)))
+            }
+
+            /// Lists the resources by the value of their indexed field.
+            $vis async fn $method(
+                client: &$crate::Client,
+                value: impl std::fmt::Display,
+                options: &$crate::ListOptions,
+            ) -> Result<$crate::Page<$resource>, $crate::DynarustError> {
+                let gsi1_pk = format!("{}#{}", stringify!($pk), value);
+                client.list_by_gsi1::<$resource>(gsi1_pk, options).await
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::TABLE;
+    use crate::client::{GSI1_INDEX, GSI1_PK};
+    use crate::{Client, DynarustError, ListOptions, Resource, SecondaryIndex};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Member {
+        id: String,
+        email: String,
+        joined_at: i64,
+    }
+
+    impl Resource for Member {
+        fn table() -> String {
+            format!("{}-gsi1", *TABLE)
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("members".into(), self.id.clone())
+        }
+
+        fn gsi1_pk_sk(&self) -> Option<(String, String)> {
+            self.gsi1_keys()
+        }
+
+        fn secondary_indexes() -> &'static [SecondaryIndex] {
+            &[SecondaryIndex::GSI1]
+        }
+    }
+
+    resource_gsi1!(Member, pk = email, sk = joined_at, by_email);
+
+    #[test]
+    fn generates_gsi1_keys() {
+        let member = Member {
+            id: "1".into(),
+            email: "john@example.com".into(),
+            joined_at: 5,
+        };
+        assert_eq!(
+            member.gsi1_pk_sk(),
+            Some(("email#john@example.com".into(), "5".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_resources_by_indexed_field() {
+        let client = Client::local().await;
+        client.create_table::<Member>(None).await.unwrap();
+        let email = "lists_resources_by_indexed_field@example.com";
+        for id in ["1", "2"] {
+            let member = Member {
+                id: format!("lists_resources_by_indexed_field{id}"),
+                email: email.into(),
+                joined_at: id.parse().unwrap(),
+            };
+            client.force_create(&member).await.unwrap();
+        }
+
+        let members = Member::by_email(&client, email, &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(members.items.len(), 2);
+        assert_eq!(members.items[0].joined_at, 1);
+    }
+
+    #[tokio::test]
+    async fn hydrates_index_queries() {
+        let client = Client::local().await;
+        client.create_table::<Member>(None).await.unwrap();
+        let email = "hydrates_index_queries@example.com";
+        let mut expected = vec![];
+        for joined_at in [3, 1, 2] {
+            let member = Member {
+                id: format!("hydrates_index_queries{joined_at}"),
+                email: email.into(),
+                joined_at,
+            };
+            client.force_create(&member).await.unwrap();
+            expected.push(member);
+        }
+        expected.sort_by_key(|member| member.joined_at);
+
+        let page = client
+            .query_index_hydrated::<Member>(
+                GSI1_INDEX,
+                GSI1_PK,
+                format!("email#{email}"),
+                &ListOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.items, expected);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Ticket {
+        id: String,
+        status: Option<String>,
+        priority: i64,
+    }
+
+    impl Resource for Ticket {
+        fn table() -> String {
+            format!("{}-indexes", *TABLE)
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("tickets".into(), self.id.clone())
+        }
+
+        fn secondary_indexes() -> &'static [SecondaryIndex] {
+            const INDEXES: &[SecondaryIndex] = &[
+                SecondaryIndex::new("ByStatus", "StatusPk", Some("StatusSk")),
+                SecondaryIndex::new("ById", "IdPk", None),
+            ];
+            INDEXES
+        }
+
+        fn index_keys(&self) -> Vec<(&'static str, String)> {
+            let mut keys = vec![("IdPk", self.id.clone())];
+            if let Some(status) = &self.status {
+                keys.push(("StatusPk", status.clone()));
+                keys.push(("StatusSk", format!("{:03}", self.priority)));
+            }
+            keys
+        }
+    }
+
+    #[tokio::test]
+    async fn queries_secondary_indexes() {
+        let client = Client::local().await;
+        client.create_table::<Ticket>(None).await.unwrap();
+        let status = "queries_secondary_indexes";
+        let mut tickets = vec![];
+        for priority in [2, 1, 3] {
+            let ticket = Ticket {
+                id: format!("queries_secondary_indexes{priority}"),
+                status: Some(status.into()),
+                priority,
+            };
+            client.force_create(&ticket).await.unwrap();
+            tickets.push(ticket);
+        }
+        client
+            .update(&tickets[2], json!({ "status": null }))
+            .await
+            .unwrap();
+
+        let page = client
+            .query_index::<Ticket>("ByStatus", status.into(), &ListOptions::default())
+            .await
+            .unwrap();
+        let priorities: Vec<i64> = page.items.iter().map(|t| t.priority).collect();
+        assert_eq!(priorities, vec![1, 2]);
+
+        let page = client
+            .query_index::<Ticket>("ById", tickets[2].id.clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].status, None);
+
+        let err = client
+            .query_index::<Ticket>("Missing", status.into(), &ListOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+    }
+
+    #[tokio::test]
+    async fn rejects_index_queries_of_scoped_clients() {
+        let client = Client::local_on_port(12345).await.scoped("tenant").unwrap();
+        let err = client
+            .query_index::<Ticket>("ByStatus", "open".into(), &ListOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+    }
+}
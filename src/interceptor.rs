@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+use serde_json::{Map, Value};
+
+use crate::client::strip_key_attributes;
+use crate::transaction::describe_item;
+use crate::{Client, DynarustError};
+
+/// Hook that stamps extra attributes on every item written by a client, so cross-cutting
+/// attributes like the last writer or the schema version do not need to live in every
+/// resource.
+pub trait WriteInterceptor: Send + Sync {
+    /// Attributes written along with every put and update of an item of the provided table.
+    /// The key attributes managed by dynarust cannot be stamped and are ignored.
+    fn attributes(&self, table: &str) -> Map<String, Value>;
+}
+
+impl<F: Fn(&str) -> Map<String, Value> + Send + Sync> WriteInterceptor for F {
+    fn attributes(&self, table: &str) -> Map<String, Value> {
+        self(table)
+    }
+}
+
+impl Client {
+    /// Returns a client that stamps the attributes of the provided interceptor on every put
+    /// and update, including the ones in transactions and bulk writes. Puts get the stamped
+    /// attributes overwriting the ones of the resource, while updates that already write one
+    /// of them keep their own value. Interceptors are cumulative, so calling this again adds
+    /// another one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let client = client.with_write_interceptor(|_table: &str| {
+    ///         let mut attributes = serde_json::Map::new();
+    ///         attributes.insert("last_writer".into(), json!("billing-service"));
+    ///         attributes.insert("schema_version".into(), json!(3));
+    ///         attributes
+    ///     });
+    ///     client.create(&invoice).await?;
+    /// }
+    /// ```
+    pub fn with_write_interceptor(&self, interceptor: impl WriteInterceptor + 'static) -> Client {
+        let mut write_interceptors = self.write_interceptors.clone();
+        write_interceptors.push(Arc::new(interceptor));
+        Client {
+            write_interceptors,
+            ..self.clone()
+        }
+    }
+
+    /// The attributes stamped by every interceptor of the client on writes to the table.
+    fn stamped_attributes(
+        &self,
+        table: &str,
+    ) -> Result<HashMap<String, AttributeValue>, DynarustError> {
+        let mut stamped = Map::new();
+        for interceptor in self.write_interceptors.iter() {
+            stamped.extend(interceptor.attributes(table));
+        }
+        strip_key_attributes(&mut stamped);
+        let mut attributes = HashMap::new();
+        for (k, v) in stamped {
+            attributes.insert(k, self.encode_value(&v)?);
+        }
+        Ok(attributes)
+    }
+
+    /// Stamps the attributes of the interceptors on an item that is about to be put.
+    pub(crate) fn intercept_item(
+        &self,
+        table: &str,
+        item: &mut HashMap<String, AttributeValue>,
+    ) -> Result<(), DynarustError> {
+        if !self.write_interceptors.is_empty() {
+            item.extend(self.stamped_attributes(table)?);
+        }
+        Ok(())
+    }
+
+    /// Adds the attributes of the interceptors to the set clause of an update expression,
+    /// unless the update expression already references them.
+    pub(crate) fn intercept_update(
+        &self,
+        table: &str,
+        expression: &mut String,
+        names: &mut HashMap<String, String>,
+        values: &mut HashMap<String, AttributeValue>,
+    ) -> Result<(), DynarustError> {
+        if self.write_interceptors.is_empty() {
+            return Ok(());
+        }
+        let mut stamped: Vec<_> = self.stamped_attributes(table)?.into_iter().collect();
+        let referenced: Vec<&str> = expression
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '#' && c != '_')
+            .filter_map(|token| names.get(token).map(String::as_str))
+            .collect();
+        stamped.retain(|(attr, _)| !referenced.contains(&attr.as_str()));
+        stamped.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut set = vec![];
+        for (i, (attr, value)) in stamped.into_iter().enumerate() {
+            let (name, placeholder) = (format!("#stampAttr{i}"), format!(":stampAttr{i}"));
+            set.push(format!("{name} = {placeholder}"));
+            names.insert(name, attr);
+            values.insert(placeholder, value);
+        }
+        if set.is_empty() {
+            return Ok(());
+        }
+        *expression = match expression.strip_prefix("set ") {
+            Some(rest) => format!("set {}, {rest}", set.join(", ")),
+            None if expression.is_empty() => format!("set {}", set.join(", ")),
+            None => format!("set {} {expression}", set.join(", ")),
+        };
+        Ok(())
+    }
+
+    /// Stamps the attributes of the interceptors on a put or update of a transaction.
+    pub(crate) fn intercept_transaction_item(
+        &self,
+        item: &mut TransactWriteItem,
+    ) -> Result<(), DynarustError> {
+        if self.write_interceptors.is_empty() {
+            return Ok(());
+        }
+        let table = describe_item(item)?.1.to_string();
+        if let Some(put_item) = item.put.as_mut().and_then(|put| put.item.as_mut()) {
+            self.intercept_item(&table, put_item)?;
+        }
+        if let Some(update) = item.update.as_mut() {
+            let mut expression = update.update_expression.take().unwrap_or_default();
+            let names = update
+                .expression_attribute_names
+                .get_or_insert_with(HashMap::new);
+            let values = update
+                .expression_attribute_values
+                .get_or_insert_with(HashMap::new);
+            self.intercept_update(&table, &mut expression, names, values)?;
+            update.update_expression = Some(expression);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_config::SdkConfig;
+    use serde_json::{json, Map, Value};
+
+    use crate::client::tests::TestResource;
+    use crate::{Client, ClientBuilder, EntityRegistry, Resource};
+
+    fn stamp(_table: &str) -> Map<String, Value> {
+        let mut attributes = Map::new();
+        attributes.insert("last_writer".into(), json!("tests"));
+        attributes.insert("PrimaryKey".into(), json!("hijacked"));
+        attributes
+    }
+
+    #[test]
+    fn stamps_update_expressions() {
+        let client = ClientBuilder::new(SdkConfig::builder().build())
+            .build()
+            .with_write_interceptor(stamp);
+
+        let mut expression = "set #updateAttr0 = :updateAttr0 remove #removeAttr0".to_string();
+        let mut names = HashMap::from([
+            ("#updateAttr0".to_string(), "int".to_string()),
+            ("#removeAttr0".to_string(), "string".to_string()),
+        ]);
+        let mut values = HashMap::new();
+        client
+            .intercept_update("Table", &mut expression, &mut names, &mut values)
+            .unwrap();
+        assert_eq!(
+            expression,
+            "set #stampAttr0 = :stampAttr0, #updateAttr0 = :updateAttr0 remove #removeAttr0"
+        );
+        assert_eq!(names["#stampAttr0"], "last_writer");
+        assert_eq!(names.len(), 3);
+
+        let mut expression = "set #stampAttr0 = :stampAttr0".to_string();
+        let before = expression.clone();
+        client
+            .intercept_update("Table", &mut expression, &mut names, &mut values)
+            .unwrap();
+        assert_eq!(expression, before);
+    }
+
+    #[tokio::test]
+    async fn stamps_every_write() {
+        let client = Client::local().await.with_write_interceptor(stamp);
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = TestResource {
+            pk: "stamps_every_write".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.create(&resource).await.unwrap();
+        client.update(&resource, json!({ "int": 1 })).await.unwrap();
+
+        let items = client
+            .list_entities(
+                "stamps_every_write".into(),
+                &EntityRegistry::new(TestResource::table()),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(items.items[0].value["last_writer"], json!("tests"));
+        assert_eq!(items.items[0].value["int"], json!(1));
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::Resource;
+
+/// Types that can describe themselves with a JSON Schema, as they are serialized by serde.
+/// Implemented for the primitive types, strings, dates, options and collections, and for
+/// structs with `resource_json_schema`.
+pub trait JsonSchemaType {
+    /// The schema of the serialized values of this type.
+    fn json_schema() -> Value;
+
+    /// whether a field of this type must be present, which is not the case for options.
+    fn required() -> bool {
+        true
+    }
+}
+
+macro_rules! impl_json_schema_type {
+    ($schema:tt, $($t:ty),*) => {
+        $(impl JsonSchemaType for $t {
+            fn json_schema() -> Value {
+                json!($schema)
+            }
+        })*
+    };
+}
+
+impl_json_schema_type!({ "type": "string" }, String, str, char);
+impl_json_schema_type!({ "type": "boolean" }, bool);
+impl_json_schema_type!({ "type": "integer" }, i8, i16, i32, i64, i128, isize);
+impl_json_schema_type!({ "type": "integer", "minimum": 0 }, u8, u16, u32, u64, u128, usize);
+impl_json_schema_type!({ "type": "number" }, f32, f64);
+impl_json_schema_type!({}, Value);
+impl_json_schema_type!({ "type": "string", "format": "date-time" }, DateTime<Utc>);
+
+impl<T: JsonSchemaType + ?Sized> JsonSchemaType for &T {
+    fn json_schema() -> Value {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchemaType> JsonSchemaType for Option<T> {
+    fn json_schema() -> Value {
+        let mut schema = T::json_schema();
+        match schema.get("type").cloned() {
+            Some(Value::String(t)) => {
+                schema["type"] = json!([t, "null"]);
+                schema
+            }
+            _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+        }
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+impl<T: JsonSchemaType> JsonSchemaType for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchemaType, S> JsonSchemaType for HashSet<T, S> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema(), "uniqueItems": true })
+    }
+}
+
+impl<T: JsonSchemaType> JsonSchemaType for BTreeSet<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema(), "uniqueItems": true })
+    }
+}
+
+impl<V: JsonSchemaType, S> JsonSchemaType for HashMap<String, V, S> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::json_schema() })
+    }
+}
+
+impl<V: JsonSchemaType> JsonSchemaType for BTreeMap<String, V> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::json_schema() })
+    }
+}
+
+/// The schema of an object with the provided fields, each one with its schema and whether it
+/// is required. Used by `resource_json_schema`.
+#[doc(hidden)]
+pub fn object_json_schema(fields: Vec<(&str, Value, bool)>) -> Value {
+    let mut properties = serde_json::Map::new();
+    let mut required = vec![];
+    for (name, schema, is_required) in fields {
+        if is_required {
+            required.push(name);
+        }
+        properties.insert(name.to_string(), schema);
+    }
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+/// JSON Schema document of resource T, so HTTP APIs exposing it can keep their definitions in
+/// sync with how it is stored. It follows draft 2020-12, so it can also be used as a component
+/// schema of an OpenAPI 3.1 definition. The storage metadata of the resource, like its table
+/// and its immutable fields, is under the `x-dynarust` extension.
+///
+/// # Examples
+///
+/// ```
+/// dynarust::resource_json_schema!(User { name: String, age: Option<u32> });
+///
+/// let schema = dynarust::create_json_schema::<User>();
+/// std::fs::write("openapi/user.schema.json", schema.to_string())?;
+/// ```
+pub fn create_json_schema<T: Resource + JsonSchemaType>() -> Value {
+    let mut schema = T::json_schema();
+    let name = std::any::type_name::<T>()
+        .rsplit("::")
+        .next()
+        .unwrap_or_default();
+    schema["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    schema["title"] = json!(name);
+    schema["x-dynarust"] = json!({
+        "table": T::table(),
+        "immutableFields": T::immutable_fields(),
+        "ttlAttribute": T::ttl_attribute(),
+        "schemaVersion": T::schema_version(),
+    });
+    schema
+}
+
+/// Implements `JsonSchemaType` for a struct from its fields and their types, so it can be
+/// passed to `create_json_schema` if it is a resource, or used as the type of a field of
+/// another one. Listing a field that does not exist, or whose type does not match, is a
+/// compile error. Fields renamed with serde attributes are not supported, as the property
+/// name is the name of the rust field.
+///
+/// # Examples
+///
+/// ```
+/// dynarust::resource_json_schema!(Address { street: String, zip: String });
+/// dynarust::resource_json_schema!(User { name: String, address: Option<Address> });
+/// ```
+#[macro_export]
+macro_rules! resource_json_schema {
+    ($resource:ident { $($field:ident : $type:ty),* $(,)? }) => {
+        impl $crate::JsonSchemaType for $resource {
+            fn json_schema() -> $crate::serde_json::Value {
+                const _: fn(&$resource) = |resource| {
+                    $(let _: &$type = &resource.$field;)*
+                };
+                $crate::object_json_schema(vec![$((
+                    stringify!($field),
+                    <$type as $crate::JsonSchemaType>::json_schema(),
+                    <$type as $crate::JsonSchemaType>::required(),
+                )),*])
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::client::tests::TABLE;
+    use crate::{create_json_schema, JsonSchemaType, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Address {
+        street: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Customer {
+        id: String,
+        age: u32,
+        address: Option<Address>,
+        nickname: Option<String>,
+        tags: Vec<String>,
+        extra: HashMap<String, f64>,
+    }
+
+    impl Resource for Customer {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("customers".into(), self.id.clone())
+        }
+
+        fn immutable_fields() -> &'static [&'static str] {
+            &["id"]
+        }
+    }
+
+    resource_json_schema!(Address { street: String });
+    resource_json_schema!(Customer {
+        id: String,
+        age: u32,
+        address: Option<Address>,
+        nickname: Option<String>,
+        tags: Vec<String>,
+        extra: HashMap<String, f64>,
+    });
+
+    #[test]
+    fn describes_resources_with_json_schema() {
+        let schema = create_json_schema::<Customer>();
+        assert_eq!(schema["title"], json!("Customer"));
+        assert_eq!(schema["required"], json!(["id", "age", "tags", "extra"]));
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["age"],
+            json!({ "type": "integer", "minimum": 0 })
+        );
+        assert_eq!(
+            properties["nickname"],
+            json!({ "type": ["string", "null"] })
+        );
+        assert_eq!(properties["address"]["type"], json!(["object", "null"]));
+        assert_eq!(
+            properties["address"]["properties"],
+            Address::json_schema()["properties"]
+        );
+        assert_eq!(
+            Option::<serde_json::Value>::json_schema(),
+            json!({ "anyOf": [{}, { "type": "null" }] })
+        );
+        assert_eq!(
+            properties["extra"]["additionalProperties"],
+            json!({ "type": "number" })
+        );
+        assert_eq!(
+            schema["x-dynarust"],
+            json!({
+                "table": Customer::table(),
+                "immutableFields": ["id"],
+                "ttlAttribute": null,
+                "schemaVersion": 0,
+            })
+        );
+    }
+}
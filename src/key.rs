@@ -0,0 +1,134 @@
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use crate::{transact_delete, Client, DynarustError, Resource, TransactionContext};
+
+/// The pk and sk pair of a resource of type T, so that operations that only need the key of
+/// a resource know its type without a turbofish, and keys of different resources cannot be
+/// mixed up. They are usually built with `Resource::key`.
+pub struct KeyOf<T> {
+    pub pk: String,
+    pub sk: String,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> KeyOf<T> {
+    pub fn new(pk: impl Into<String>, sk: impl Into<String>) -> Self {
+        Self {
+            pk: pk.into(),
+            sk: sk.into(),
+            resource: PhantomData,
+        }
+    }
+
+    /// The pk and sk pair, as accepted by the untyped operations.
+    pub fn pk_sk(&self) -> (String, String) {
+        (self.pk.clone(), self.sk.clone())
+    }
+}
+
+impl<T> Clone for KeyOf<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.pk.clone(), self.sk.clone())
+    }
+}
+
+impl<T> PartialEq for KeyOf<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pk == other.pk && self.sk == other.sk
+    }
+}
+
+impl<T> Debug for KeyOf<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyOf")
+            .field("pk", &self.pk)
+            .field("sk", &self.sk)
+            .finish()
+    }
+}
+
+impl<T> From<(String, String)> for KeyOf<T> {
+    fn from((pk, sk): (String, String)) -> Self {
+        Self::new(pk, sk)
+    }
+}
+
+/// Adds the deletion of the resource with the provided key to a transaction context.
+///
+/// # arguments
+/// * `key` - The key of the resource.
+/// * `transaction_context` - The transaction context to which the delete operation will be added.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_delete_key(Car::key("car", "1"), &mut context)?;
+///     client.execute_transaction(context).await?;
+/// }
+/// ```
+pub fn transact_delete_key<T: Resource>(
+    key: KeyOf<T>,
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
+    transact_delete::<T>(key.pk_sk(), transaction_context)
+}
+
+impl Client {
+    /// Deletes the resource with the provided key.
+    ///
+    /// # arguments
+    ///
+    /// * `key` - The key of the resource that will get deleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client.delete_key(Car::key("car", "1")).await?;
+    /// }
+    /// ```
+    pub async fn delete_key<T: Resource>(&self, key: KeyOf<T>) -> Result<(), DynarustError> {
+        self.delete::<T>(key.pk_sk()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::{begin_transaction, transact_delete_key, Client, KeyOf, Resource};
+
+    #[test]
+    fn builds_keys_of_resources() {
+        let key = TestResource::key("pk", "sk");
+        assert_eq!(key, KeyOf::from(("pk".to_string(), "sk".to_string())));
+        assert_eq!(key.pk_sk(), ("pk".to_string(), "sk".to_string()));
+
+        let mut context = begin_transaction();
+        transact_delete_key(key, &mut context).unwrap();
+        assert_eq!(context.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn deletes_by_key() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "deletes_by_key".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        client
+            .delete_key(TestResource::key("deletes_by_key", "1"))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.get::<TestResource>(resource.pk_sk()).await.unwrap(),
+            None
+        );
+    }
+}
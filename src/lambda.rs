@@ -0,0 +1,70 @@
+//! Process global client for AWS Lambda handlers.
+//!
+//! Lambda reuses the process of a function across invocations, so the client must be built
+//! once, during the cold start, and shared by every invocation afterwards. Building a client
+//! loads the AWS config and sets up its connection pool, which is too slow to repeat on every
+//! request.
+//!
+//! ```
+//! async fn handler(event: LambdaEvent<Request>) -> Result<Response, Error> {
+//!     let car = dynarust::lambda::client()
+//!         .await
+//!         .get::<Car>(("cars".into(), event.payload.id))
+//!         .await?;
+//!     ...
+//! }
+//! ```
+use tokio::sync::OnceCell;
+
+use crate::Client;
+
+static CLIENT: OnceCell<Client> = OnceCell::const_new();
+
+/// Returns the process global client, building it with `Client::aws` the first time it is
+/// called. Concurrent callers during the first call wait for the same initialization, so the
+/// config is only loaded once.
+pub async fn client() -> &'static Client {
+    CLIENT.get_or_init(Client::aws).await
+}
+
+/// Returns the process global client, building it with the provided function the first time
+/// it is called, for functions that need a client configured with routes or an audit sink.
+/// The function is ignored if the client was already built.
+///
+/// # arguments
+///
+/// * `init` - builds the client on the cold start.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let client = dynarust::lambda::client_with(|| async {
+///         dynarust::ClientBuilder::from_env()
+///             .await
+///             .route_table("AuditLog", Region::new("eu-west-1"))
+///             .build()
+///     })
+///     .await;
+/// }
+/// ```
+pub async fn client_with<F, Fut>(init: F) -> &'static Client
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Client>,
+{
+    CLIENT.get_or_init(init).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn builds_the_client_once() {
+        let first = client_with(|| Client::local_on_port(12345)).await;
+        let second = client_with(|| async { unreachable!() }).await;
+        assert!(std::ptr::eq(first, second));
+        assert!(std::ptr::eq(first, client().await));
+    }
+}
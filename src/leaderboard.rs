@@ -0,0 +1,277 @@
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::condition_check::{condition_check_exists, ConditionCheckInfo};
+use crate::list::query_values;
+use crate::update::update_item_or_return_old;
+use crate::{Client, DynarustError, ItemUpdate, ListOptions, Resource};
+
+/// A global secondary index that ranks resources by a numeric score. Its PrimaryKey is an
+/// attribute that groups the ranked resources in boards, like a game id, and its sort key is
+/// the score, which must be declared with the number type `N`. The index must project all the
+/// attributes, and it is created outside of dynarust.
+///
+/// # Examples
+///
+/// ```
+/// struct ByScore;
+///
+/// impl ScoreIndex for ByScore {
+///     fn index_name() -> &'static str { "ByScore" }
+///     fn board_attribute() -> &'static str { "game" }
+///     fn score_attribute() -> &'static str { "score" }
+/// }
+/// ```
+pub trait ScoreIndex {
+    /// Name of the global secondary index.
+    fn index_name() -> &'static str;
+    /// Attribute that is the PrimaryKey of the index.
+    fn board_attribute() -> &'static str;
+    /// Numeric attribute that is the sort key of the index.
+    fn score_attribute() -> &'static str;
+}
+
+impl Client {
+    /// Returns the n resources of a board with the highest scores, in descending order of
+    /// score, querying the score index I. Resources of other tenants sharing the board are
+    /// left out for scoped clients, so fewer than n resources might be returned.
+    ///
+    /// # arguments
+    ///
+    /// * `board` - Value of the board attribute of the ranked resources.
+    /// * `n` - Number of resources returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let podium = client.top_n_by::<Player, ByScore>("game-1".into(), 3).await?;
+    /// }
+    /// ```
+    pub async fn top_n_by<T: Resource + DeserializeOwned, I: ScoreIndex>(
+        &self,
+        board: String,
+        n: i32,
+    ) -> Result<Vec<T>, DynarustError> {
+        let table = T::table();
+        let options = ListOptions {
+            limit: n,
+            sort_desc: true,
+            ..Default::default()
+        };
+        let builder = self
+            .dynamo(&table)
+            .query()
+            .table_name(&table)
+            .index_name(I::index_name())
+            .key_condition_expression("#board = :board")
+            .expression_attribute_names("#board", I::board_attribute())
+            .expression_attribute_values(":board", AttributeValue::S(board))
+            .consistent_read(self.index_consistent_read(&options)?);
+        let page = query_values(self, &table, builder, &options).await?;
+
+        let pk_attr = self.key_names(&table).0;
+        let mut resources = vec![];
+        for item in page.items {
+            let own = match (&self.tenant, item.get(pk_attr)) {
+                (Some(tenant), Some(Value::String(pk))) => tenant.strip(pk).is_some(),
+                (Some(_), _) => false,
+                (None, _) => true,
+            };
+            if own {
+                resources.push(Client::value_as_resource(item)?);
+            }
+        }
+        Ok(resources)
+    }
+
+    /// Atomically adds a delta, which can be negative, to the score of an existing resource,
+    /// returning the new score. A missing score is treated as zero.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `delta` - The amount added to the score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let score = client.incr_score::<Player, ByScore>(player.pk_sk(), 10).await?;
+    /// }
+    /// ```
+    pub async fn incr_score<T: Resource, I: ScoreIndex>(
+        &self,
+        pk_sk: (String, String),
+        delta: i64,
+    ) -> Result<i64, DynarustError> {
+        self.incr::<T>(pk_sk, I::score_attribute(), delta).await
+    }
+
+    /// Sets the score of an existing resource only if it is higher than its current one, in a
+    /// single conditional update, which is how best scores are kept. Returns whether the
+    /// score was updated. If the resource does not exist a
+    /// `DynarustError::ConditionalCheckFailedError` is returned.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `score` - The new score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     if client.submit_high_score::<Player, ByScore>(player.pk_sk(), 1200).await? {
+    ///         println!("new personal best");
+    ///     }
+    /// }
+    /// ```
+    pub async fn submit_high_score<T: Resource, I: ScoreIndex>(
+        &self,
+        pk_sk: (String, String),
+        score: i64,
+    ) -> Result<bool, DynarustError> {
+        let update = ItemUpdate::default().with_set(I::score_attribute(), json!(score));
+        let condition_check = condition_check_exists().merge(vec![ConditionCheckInfo::default()
+            .condition_expression("attribute_not_exists(#score) or #score < :score")
+            .expression_attribute_names("#score", I::score_attribute())
+            .expression_attribute_values(":score", AttributeValue::N(score.to_string()))]);
+        let result =
+            update_item_or_return_old(self, &T::table(), pk_sk, update, condition_check).await;
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(DynarustError::ConditionalCheckFailedError(Some(_))) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::model::{
+        AttributeDefinition, BillingMode, GlobalSecondaryIndex, KeySchemaElement, KeyType,
+        Projection, ProjectionType, ScalarAttributeType,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::{PK, SK};
+    use crate::{Client, Resource, ScoreIndex};
+
+    const LEADERBOARD_TABLE: &str = "Leaderboard";
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+    struct Player {
+        game: String,
+        name: String,
+        score: i64,
+    }
+
+    impl Resource for Player {
+        fn table() -> String {
+            LEADERBOARD_TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.game.clone(), self.name.clone())
+        }
+    }
+
+    struct ByScore;
+
+    impl ScoreIndex for ByScore {
+        fn index_name() -> &'static str {
+            "ByScore"
+        }
+
+        fn board_attribute() -> &'static str {
+            "game"
+        }
+
+        fn score_attribute() -> &'static str {
+            "score"
+        }
+    }
+
+    async fn create_leaderboard_table(client: &Client) {
+        let attribute = |name: &str, attribute_type| {
+            AttributeDefinition::builder()
+                .attribute_name(name)
+                .attribute_type(attribute_type)
+                .build()
+        };
+        let key = |name: &str, key_type| {
+            KeySchemaElement::builder()
+                .attribute_name(name)
+                .key_type(key_type)
+                .build()
+        };
+        let _ = client
+            .client
+            .create_table()
+            .table_name(LEADERBOARD_TABLE)
+            .billing_mode(BillingMode::PayPerRequest)
+            .attribute_definitions(attribute(PK, ScalarAttributeType::S))
+            .attribute_definitions(attribute(SK, ScalarAttributeType::S))
+            .attribute_definitions(attribute("game", ScalarAttributeType::S))
+            .attribute_definitions(attribute("score", ScalarAttributeType::N))
+            .key_schema(key(PK, KeyType::Hash))
+            .key_schema(key(SK, KeyType::Range))
+            .global_secondary_indexes(
+                GlobalSecondaryIndex::builder()
+                    .index_name("ByScore")
+                    .key_schema(key("game", KeyType::Hash))
+                    .key_schema(key("score", KeyType::Range))
+                    .projection(
+                        Projection::builder()
+                            .projection_type(ProjectionType::All)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .send()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn ranks_top_scores() {
+        let client = Client::local().await;
+        create_leaderboard_table(&client).await;
+
+        let game = "ranks_top_scores".to_string();
+        for (name, score) in [("ada", 30), ("bob", 10), ("eve", 20)] {
+            let player = Player {
+                game: game.clone(),
+                name: name.into(),
+                score,
+            };
+            client.force_create(&player).await.unwrap();
+        }
+        let bob = (game.clone(), "bob".to_string());
+        assert_eq!(
+            client
+                .incr_score::<Player, ByScore>(bob.clone(), 15)
+                .await
+                .unwrap(),
+            25
+        );
+        assert!(!client
+            .submit_high_score::<Player, ByScore>(bob.clone(), 5)
+            .await
+            .unwrap());
+        assert!(client
+            .submit_high_score::<Player, ByScore>(bob, 40)
+            .await
+            .unwrap());
+        assert!(client
+            .submit_high_score::<Player, ByScore>((game.clone(), "nobody".into()), 40)
+            .await
+            .is_err());
+
+        let top = client.top_n_by::<Player, ByScore>(game, 2).await.unwrap();
+        let names: Vec<_> = top.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, vec!["bob", "ada"]);
+    }
+}
@@ -1,22 +1,131 @@
+// Lets the macros of this crate name it as `dynarust` also from within the crate.
+extern crate self as dynarust;
+
+mod adjacency;
+mod aggregate;
+mod analyze;
+mod audit;
+mod cascade;
 mod client;
+mod clock;
+mod codec;
+mod collection_metrics;
 mod condition_check;
+mod config;
+mod consistency;
+mod copy;
+mod counter;
 mod create;
 mod delete;
+mod delete_where;
+mod entity;
 mod errors;
+mod explain;
+mod external;
+mod fields;
+mod find;
+mod for_each;
 mod get;
+mod gsi;
+mod interceptor;
+mod json_schema;
+mod key;
+mod leaderboard;
 mod list;
+mod list_element;
+mod list_many;
+mod local;
+mod map_entry;
+mod modify;
+mod page;
+mod projection;
+mod queue;
+mod rate_limit;
+mod raw;
+mod replay;
+mod repository;
+mod routing;
+mod scan;
+mod schema;
+mod sequence;
+mod state_machine;
+mod stats;
+mod store;
+mod strict;
+mod sweep;
 mod table;
+mod tenant;
+mod testing;
+mod time_bucket;
+mod time_key;
+mod transaction;
 mod update;
+mod update_where;
+mod write_all;
+
+pub mod bulk;
+pub mod lambda;
+pub mod ops;
+pub mod prelude;
 
+pub use adjacency::*;
+pub use aggregate::*;
+pub use analyze::*;
+pub use audit::*;
+pub use cascade::*;
+pub use chrono;
 pub use client::*;
+pub use clock::*;
+pub use codec::*;
+pub use collection_metrics::CollectionMetrics;
 pub use condition_check::*;
+pub use config::*;
+pub use consistency::*;
+pub use copy::*;
 pub use create::*;
 pub use delete::*;
+pub use delete_where::*;
+pub use entity::*;
 pub use errors::*;
+pub use explain::*;
+pub use fields::*;
+pub use for_each::*;
+// get and list only hold Client methods for now, their globs export any free items added later
+#[allow(unused_imports)]
 pub use get::*;
+pub use gsi::{IndexKey, SecondaryIndex};
+pub use interceptor::*;
+pub use json_schema::*;
+pub use key::*;
+pub use leaderboard::*;
+#[allow(unused_imports)]
 pub use list::*;
+pub use list_many::*;
+pub use local::*;
+pub use page::*;
+pub use projection::*;
+pub use queue::*;
+pub use rate_limit::*;
+pub use replay::*;
+pub use repository::*;
+pub use routing::*;
+pub use scan::*;
+pub use schema::*;
+pub use sequence::SequenceOptions;
 pub use serde;
 pub use serde_json;
+pub use state_machine::*;
+pub use stats::{ClientStats, OperationStats};
+pub use store::*;
+pub use strict::*;
+pub use sweep::*;
 pub use table::CreateTableOptions;
 pub use table::*;
+pub use tenant::*;
+pub use testing::*;
+pub use time_bucket::*;
+pub use time_key::*;
+pub use transaction::*;
 pub use update::*;
+pub use update_where::*;
+pub use write_all::*;
@@ -1,12 +1,23 @@
-use aws_sdk_dynamodb::model::AttributeValue;
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::client::fluent_builders::Query;
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity, Select};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use crate::client::{PK, SK};
-use crate::{Client, DynarustError, ListOptions, Resource};
+use crate::stats::Operation;
+use crate::{
+    Client, Cursor, DynamoStore, DynarustError, ListOptions, ListResult, Page, Resource,
+    SkCondition, SortKeyTimeFormat,
+};
 
 impl Client {
-    /// List all the resources under the same pk.
+    /// List all the resources under the same pk, one page at a time. The `next` cursor of a
+    /// page, passed in the options, continues the listing right after its last item, whatever
+    /// the format of the sort keys. The `sk_condition` of the options narrows the listing down
+    /// to a prefix or a range of sort keys in the query itself, without reading the rest.
     ///
     /// # arguments
     ///
@@ -37,66 +48,330 @@ impl Client {
     ///         &ListOptions {
     ///              from: Some("16794003059".into()),
     ///              limit: 100,
-    ///              sort_desc: true
+    ///              sort_desc: true,
+    ///              ..Default::default()
     ///         }
     ///     ).await?;
-    ///     assert_eq!(result.len(), 100)
+    ///     assert_eq!(result.items.len(), 100)
     /// }
     /// ```
     pub async fn list<T: Resource + DeserializeOwned>(
         &self,
         pk: String,
         options: &ListOptions,
-    ) -> Result<Vec<T>, DynarustError> {
-        let scan_index_forward = !options.sort_desc;
-        let limit = options.limit;
-        let operator = match scan_index_forward {
-            true => ">",
-            false => "<",
-        };
-        let sk = match &options.from {
-            Some(sk) => sk,
-            None => match scan_index_forward {
-                true => "+++++++++",   // hehehe
-                false => "zzzzzzzzzz", // hohoho
-            },
+    ) -> Result<ListResult<T>, DynarustError> {
+        list_resources(self, pk, options).await
+    }
+
+    /// Streams all the resources under the same pk, querying the next page whenever the
+    /// previous one is exhausted, so cursors do not need to be handled. The `limit` of the
+    /// options is the size of each page, not a cap on the number of streamed resources.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the listed resources live.
+    /// * `options` - options of the first page, the following ones continue from its cursor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// async {
+    ///     let mut events = client.list_stream::<Event>("client-events".into(), &ListOptions::default());
+    ///     while let Some(event) = events.try_next().await? {
+    ///         replay(event).await?;
+    ///     }
+    /// }
+    /// ```
+    pub fn list_stream<T: Resource + DeserializeOwned>(
+        &self,
+        pk: String,
+        options: &ListOptions,
+    ) -> impl Stream<Item = Result<T, DynarustError>> + '_ {
+        let pages = stream::try_unfold(Some(options.clone()), move |options| {
+            let pk = pk.clone();
+            async move {
+                let Some(mut options) = options else {
+                    return Ok(None);
+                };
+                let page: Page<T> = self.list(pk, &options).await?;
+                let next = page.next.map(|cursor| {
+                    options.cursor = Some(cursor);
+                    options
+                });
+                Ok::<_, DynarustError>(Some((page.items, next)))
+            }
+        });
+
+        pages
+            .map_ok(|resources| stream::iter(resources.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// List the resources under the same pk whose sort key is a timestamp between `start` and
+    /// `end`, both inclusive. The sort keys of the resources must have been formatted with the
+    /// same `format`, so that their lexicographic order matches their chronological order.
+    /// The range is the sort key condition of the listing, so it cannot be combined with the
+    /// `from` and `sk_condition` options, and timestamps that the format cannot encode are
+    /// rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the listed resources live.
+    /// * `start` - Start of the time window.
+    /// * `end` - End of the time window.
+    /// * `format` - Format in which timestamps are encoded in the sort key.
+    /// * `options` - optional pagination options.
+    ///
+    /// # example
+    ///
+    /// ```
+    /// use dynarust::SortKeyTimeFormat;
+    ///
+    /// impl dynarust::Resource for Event {
+    ///     fn table() -> String { "Events".into() }
+    ///     fn pk_sk(&self) -> (String, String) {
+    ///         let sk = SortKeyTimeFormat::Rfc3339.format(&self.timestamp).unwrap();
+    ///         (self.id.clone(), sk)
+    ///     }
+    /// }
+    ///
+    /// async {
+    ///     let result = client.list_in_range::<Event>(
+    ///         "client-events".into(),
+    ///         Utc::now() - Duration::hours(1),
+    ///         Utc::now(),
+    ///         SortKeyTimeFormat::Rfc3339,
+    ///         &ListOptions::default()
+    ///     ).await?;
+    /// }
+    /// ```
+    pub async fn list_in_range<T: Resource + DeserializeOwned>(
+        &self,
+        pk: String,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        format: SortKeyTimeFormat,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        if options.sk_condition != SkCondition::Any {
+            return Err(DynarustError::InvalidRequestError(
+                "The sk_condition list option cannot be combined with a time range".into(),
+            ));
+        }
+        let options = ListOptions {
+            sk_condition: SkCondition::Between(format.format(&start)?, format.format(&end)?),
+            ..options.clone()
         };
+        self.list(pk, &options).await
+    }
+
+    pub(crate) async fn query_page<T: Resource + DeserializeOwned>(
+        &self,
+        builder: Query,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        let page = query_values(self, &T::table(), builder, options).await?;
+        page_as_resources(page)
+    }
+
+    /// Counts all the resources under the same pk. Dynamo counts at most 1MB of data per
+    /// request, so this follows every page of the partition until the count is complete.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the counted resources live.
+    pub async fn count_by_pk<T: Resource>(&self, pk: String) -> Result<u64, DynarustError> {
+        let pk = self.scope_pk(pk);
+        let mut count = 0;
+        let mut exclusive_start_key = None;
+        loop {
+            let query = self
+                .dynamo(&T::table())
+                .query()
+                .table_name(T::table())
+                .key_condition_expression("#pk = :pk")
+                .expression_attribute_names("#pk", self.key_names(&T::table()).0)
+                .expression_attribute_values(":pk", AttributeValue::S(pk.clone()))
+                .select(Select::Count)
+                .set_exclusive_start_key(exclusive_start_key)
+                .return_consumed_capacity(ReturnConsumedCapacity::Total)
+                .send();
+            let result = self
+                .stats
+                .measure(Operation::Query, async { Ok(query.await?) })
+                .await?;
+            self.stats.add_capacity(
+                Operation::Query,
+                result.consumed_capacity().and_then(|c| c.capacity_units()),
+            );
 
-        let result = self
-            .client
-            .query()
-            .table_name(T::table())
-            .key_condition_expression(format!("#pk = :pk and #sk {} :sk", operator))
-            .expression_attribute_names("#pk", PK)
-            .expression_attribute_names("#sk", SK)
-            .expression_attribute_values(":pk", AttributeValue::S(pk.to_string()))
-            .expression_attribute_values(":sk", AttributeValue::S(sk.to_string()))
-            .limit(limit)
-            .scan_index_forward(scan_index_forward)
-            .send()
-            .await?;
-
-        let mut results = vec![];
-
-        if let Some(items) = result.items() {
-            for item in items {
-                let mut object = Value::Object(serde_json::Map::new());
-                for (k, v) in item {
-                    object[k] = Self::attr2value(v)?
-                }
-                let t: T = serde_json::from_value(object)?;
-                results.push(t)
+            count += result.count() as u64;
+            exclusive_start_key = result.last_evaluated_key().cloned();
+            if exclusive_start_key.is_none() {
+                return Ok(count);
             }
         }
+    }
+}
+
+pub(crate) async fn list_resources<S: DynamoStore + ?Sized, T: Resource + DeserializeOwned>(
+    store: &S,
+    pk: String,
+    options: &ListOptions,
+) -> Result<Page<T>, DynarustError> {
+    let page = store.query_items(&T::table(), pk, options).await?;
+    page_as_resources(page)
+}
+
+fn page_as_resources<T: Resource + DeserializeOwned>(
+    page: Page<Value>,
+) -> Result<Page<T>, DynarustError> {
+    let mut items = vec![];
+    for item in page.items {
+        items.push(Client::value_as_resource(item)?)
+    }
+    Ok(Page {
+        items,
+        next: page.next,
+        scanned_count: page.scanned_count,
+        count: page.count,
+        consumed_capacity: page.consumed_capacity,
+    })
+}
+
+/// Key condition expression for listing the items under a pk, along with the values of its
+/// sort key placeholders, which refer to the sort key as `#sk` only if there are any. The pk
+/// goes in the `:pk` placeholder.
+pub(crate) fn list_key_condition(
+    options: &ListOptions,
+) -> Result<(String, HashMap<String, AttributeValue>), DynarustError> {
+    let condition = match (&options.from, &options.sk_condition) {
+        (None, condition) => condition.clone(),
+        (Some(sk), SkCondition::Any) => match options.sort_desc {
+            false => SkCondition::Gt(sk.clone()),
+            true => SkCondition::Lt(sk.clone()),
+        },
+        (Some(_), _) => {
+            return Err(DynarustError::InvalidRequestError(
+                "The from and sk_condition list options cannot be combined".into(),
+            ))
+        }
+    };
+    Ok(condition.key_condition())
+}
+
+pub(crate) async fn query_items(
+    client: &Client,
+    table: &str,
+    pk: String,
+    options: &ListOptions,
+) -> Result<Page<Value>, DynarustError> {
+    let builder = list_query(client, table, pk, options)?;
+    query_values(client, table, builder, options).await
+}
+
+/// Query for listing the items under a pk, without the pagination options applied.
+pub(crate) fn list_query(
+    client: &Client,
+    table: &str,
+    pk: String,
+    options: &ListOptions,
+) -> Result<Query, DynarustError> {
+    let (expression, mut values) = list_key_condition(options)?;
+    let (pk_attr, sk_attr) = client.key_names(table);
+    let pk = client.scope_pk(pk);
+    check_cursor_partition(options, pk_attr, &pk)?;
+    let mut names = HashMap::from([("#pk".to_string(), pk_attr.to_string())]);
+    if !values.is_empty() {
+        names.insert("#sk".to_string(), sk_attr.to_string());
+    }
+    values.insert(":pk".to_string(), AttributeValue::S(pk));
+    Ok(client
+        .dynamo(table)
+        .query()
+        .table_name(table)
+        .key_condition_expression(expression)
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values))
+        .consistent_read(client.consistent_read(Some(options))))
+}
 
-        Ok(results)
+/// Fails if the cursor of the options points to another partition, as dynamo would continue
+/// from an arbitrary position instead of where the previous page stopped.
+fn check_cursor_partition(
+    options: &ListOptions,
+    pk_attr: &str,
+    pk: &str,
+) -> Result<(), DynarustError> {
+    let Some(cursor) = &options.cursor else {
+        return Ok(());
+    };
+    match cursor.0.get(pk_attr) {
+        Some(AttributeValue::S(cursor_pk)) if cursor_pk == pk => Ok(()),
+        _ => Err(DynarustError::InvalidRequestError(
+            "The cursor belongs to a listing of another pk".into(),
+        )),
     }
 }
 
+pub(crate) async fn query_values(
+    client: &Client,
+    table: &str,
+    mut builder: Query,
+    options: &ListOptions,
+) -> Result<Page<Value>, DynarustError> {
+    builder = builder
+        .limit(options.limit)
+        .scan_index_forward(!options.sort_desc)
+        .return_consumed_capacity(ReturnConsumedCapacity::Total);
+
+    if let Some(cursor) = &options.cursor {
+        builder = builder.set_exclusive_start_key(Some(cursor.0.clone()));
+    }
+
+    let query = async { Ok(builder.send().await?) };
+    let result = client.stats.measure(Operation::Query, query).await?;
+    client.stats.add_capacity(
+        Operation::Query,
+        result.consumed_capacity().and_then(|c| c.capacity_units()),
+    );
+
+    let mut items = vec![];
+
+    if let Some(result_items) = result.items() {
+        for item in result_items {
+            items.push(
+                client
+                    .decode_item(item)
+                    .map_err(|err| err.in_table(table))?,
+            )
+        }
+    }
+
+    Ok(Page {
+        items,
+        next: result.last_evaluated_key().cloned().map(Cursor),
+        scanned_count: result.scanned_count(),
+        count: result.count(),
+        consumed_capacity: result.consumed_capacity().and_then(|c| c.capacity_units()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::model::AttributeValue;
+    use chrono::{Duration, TimeZone, Utc};
+    use futures::TryStreamExt;
+
     use crate::client::tests::TestResource;
-    use crate::{Client, ListOptions, Resource};
+    use crate::client::{PK, SK};
+    use crate::list::{check_cursor_partition, list_key_condition};
+    use crate::{
+        Client, Cursor, DynamoOperator, ListOptions, Resource, SkCondition, SortKeyTimeFormat,
+    };
 
     #[tokio::test]
     async fn creates_lists_resources() {
@@ -126,7 +401,8 @@ mod tests {
                 },
             )
             .await
-            .unwrap();
+            .unwrap()
+            .items;
 
         assert_eq!(asc_results[0], expected[0]);
         assert_eq!(asc_results[1], expected[1]);
@@ -142,7 +418,8 @@ mod tests {
                 },
             )
             .await
-            .unwrap();
+            .unwrap()
+            .items;
 
         assert_eq!(desc_results[0], expected[9]);
         assert_eq!(desc_results[1], expected[8]);
@@ -155,13 +432,319 @@ mod tests {
                     limit: 3,
                     sort_desc: true,
                     from: Some(desc_results[2].pk_sk().1),
+                    ..Default::default()
                 },
             )
             .await
-            .unwrap();
+            .unwrap()
+            .items;
 
         assert_eq!(desc_results_offset[0], expected[6]);
         assert_eq!(desc_results_offset[1], expected[5]);
         assert_eq!(desc_results_offset[2], expected[4]);
     }
+
+    #[tokio::test]
+    async fn lists_all_pages_following_cursor() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "lists_all_pages_following_cursor";
+        for i in 0..10 {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: i.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let mut options = ListOptions {
+            limit: 3,
+            ..Default::default()
+        };
+        let mut all = vec![];
+        loop {
+            let page = client
+                .list::<TestResource>(pk.to_string(), &options)
+                .await
+                .unwrap();
+            all.extend(page.items);
+            match page.next {
+                Some(cursor) => options.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(all.len(), 10);
+        assert_eq!(all[9].sk, "9");
+    }
+
+    #[test]
+    fn rejects_cursors_of_other_partitions() {
+        let cursor = Cursor::from(HashMap::from([
+            (PK.to_string(), AttributeValue::S("a".into())),
+            (SK.to_string(), AttributeValue::S("PREFIX#1".into())),
+        ]));
+        let options = ListOptions {
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        assert!(check_cursor_partition(&options, PK, "a").is_ok());
+        assert!(check_cursor_partition(&options, PK, "b").is_err());
+        assert!(check_cursor_partition(&ListOptions::default(), PK, "b").is_ok());
+    }
+
+    #[test]
+    fn encodes_cursors_as_opaque_tokens() {
+        let cursor = Cursor::from(HashMap::from([
+            (PK.to_string(), AttributeValue::S("users#\"1\"".into())),
+            ("Score".to_string(), AttributeValue::N("10".into())),
+            (
+                "Amount".to_string(),
+                AttributeValue::N("12345678901234567890.123456789".into()),
+            ),
+        ]));
+        let token = cursor.encode().unwrap();
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+        assert!(Cursor::decode("7b").is_err());
+        assert!(Cursor::decode("7").is_err());
+
+        let unsupported = Cursor::from(HashMap::from([(
+            PK.to_string(),
+            AttributeValue::Bool(true),
+        )]));
+        assert!(unsupported.encode().is_err());
+    }
+
+    #[tokio::test]
+    async fn lists_sort_keys_of_any_format() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "lists_sort_keys_of_any_format";
+        let sks = ["!", "+", "A", "zzzzzzzzzzz", "~"];
+        for sk in sks {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: sk.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        for sort_desc in [false, true] {
+            let options = ListOptions {
+                sort_desc,
+                ..Default::default()
+            };
+            let page = client
+                .list::<TestResource>(pk.to_string(), &options)
+                .await
+                .unwrap();
+            let mut listed: Vec<_> = page.items.into_iter().map(|r| r.sk).collect();
+            if sort_desc {
+                listed.reverse();
+            }
+            assert_eq!(listed, sks);
+        }
+    }
+
+    #[test]
+    fn builds_sort_key_conditions() {
+        let options = |from: Option<&str>, sk_condition| ListOptions {
+            from: from.map(String::from),
+            sk_condition,
+            sort_desc: true,
+            ..Default::default()
+        };
+        let (expression, values) = list_key_condition(&options(None, SkCondition::Any)).unwrap();
+        assert_eq!((expression.as_str(), values.len()), ("#pk = :pk", 0));
+
+        let (expression, _) = list_key_condition(&options(Some("5"), SkCondition::Any)).unwrap();
+        assert_eq!(expression, "#pk = :pk and #sk < :sk");
+
+        let between = SkCondition::Between("a".into(), "b".into());
+        let (expression, values) = list_key_condition(&options(None, between.clone())).unwrap();
+        assert_eq!(expression, "#pk = :pk and #sk between :sk and :skEnd");
+        assert_eq!(values[":skEnd"], AttributeValue::S("b".into()));
+        assert!(list_key_condition(&options(Some("5"), between)).is_err());
+
+        let compare = SkCondition::compare(DynamoOperator::GtEq, "2023").unwrap();
+        assert_eq!(compare, SkCondition::Ge("2023".into()));
+        assert!(SkCondition::compare(DynamoOperator::NEq, "2023").is_err());
+    }
+
+    #[tokio::test]
+    async fn lists_sort_key_ranges() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "lists_sort_key_ranges";
+        for sk in ["event#2023-01", "event#2023-02", "event#2023-03", "note#1"] {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: sk.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let list = |sk_condition| {
+            let options = ListOptions {
+                sk_condition,
+                ..Default::default()
+            };
+            let client = &client;
+            async move {
+                let page = client.list::<TestResource>(pk.to_string(), &options).await;
+                page.unwrap()
+                    .items
+                    .into_iter()
+                    .map(|r| r.sk)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            list(SkCondition::BeginsWith("event#".into())).await,
+            vec!["event#2023-01", "event#2023-02", "event#2023-03"]
+        );
+        assert_eq!(
+            list(SkCondition::Between(
+                "event#2023-02".into(),
+                "event#2023-03".into()
+            ))
+            .await,
+            vec!["event#2023-02", "event#2023-03"]
+        );
+        assert_eq!(
+            list(SkCondition::compare(DynamoOperator::Gt, "event#2023-03").unwrap()).await,
+            vec!["note#1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_every_page() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "streams_every_page";
+        for i in 0..7 {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: i.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let options = ListOptions {
+            limit: 3,
+            sort_desc: true,
+            ..Default::default()
+        };
+        let streamed: Vec<TestResource> = client
+            .list_stream(pk.to_string(), &options)
+            .try_collect()
+            .await
+            .unwrap();
+        let sks: Vec<_> = streamed.iter().map(|r| r.sk.as_str()).collect();
+        assert_eq!(sks, vec!["6", "5", "4", "3", "2", "1", "0"]);
+    }
+
+    #[tokio::test]
+    async fn counts_resources_by_pk() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "counts_resources_by_pk";
+        for i in 0..5 {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: i.to_string(),
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let count = client
+            .count_by_pk::<TestResource>(pk.to_string())
+            .await
+            .unwrap();
+        assert_eq!(count, 5);
+    }
+
+    #[tokio::test]
+    async fn lists_resources_in_time_range() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "lists_resources_in_time_range";
+        let start = Utc.with_ymd_and_hms(2023, 3, 1, 0, 0, 0).unwrap();
+        for days in [0, 9, 10, 11, 100] {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: SortKeyTimeFormat::EpochMillis
+                    .format(&(start + Duration::days(days)))
+                    .unwrap(),
+                int: days,
+                ..Default::default()
+            };
+            client.create(&resource).await.unwrap();
+        }
+
+        let page = client
+            .list_in_range::<TestResource>(
+                pk.to_string(),
+                start + Duration::days(9),
+                start + Duration::days(11),
+                SortKeyTimeFormat::EpochMillis,
+                &ListOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let days: Vec<i64> = page.items.iter().map(|r| r.int).collect();
+        assert_eq!(days, vec![9, 10, 11]);
+        assert_eq!(page.count, 3);
+        assert!(!page.discarded_by_filter());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_time_ranges() {
+        let client = Client::local_on_port(12345).await;
+        let start = Utc.with_ymd_and_hms(1969, 12, 1, 0, 0, 0).unwrap();
+        let list = |format, options: ListOptions| {
+            let client = &client;
+            async move {
+                client
+                    .list_in_range::<TestResource>(
+                        "rejects_invalid_time_ranges".into(),
+                        start,
+                        start + Duration::days(60),
+                        format,
+                        &options,
+                    )
+                    .await
+                    .unwrap_err()
+                    .to_string()
+            }
+        };
+
+        let err = list(SortKeyTimeFormat::EpochSeconds, ListOptions::default()).await;
+        assert!(err.contains("does not fit the EpochSeconds sort key format"));
+        let options = ListOptions {
+            from: Some("1".into()),
+            ..Default::default()
+        };
+        let err = list(SortKeyTimeFormat::Rfc3339, options).await;
+        assert!(err.contains("cannot be combined"));
+        let options = ListOptions {
+            sk_condition: SkCondition::Gt("1".into()),
+            ..Default::default()
+        };
+        let err = list(SortKeyTimeFormat::Rfc3339, options).await;
+        assert!(err.contains("cannot be combined with a time range"));
+    }
 }
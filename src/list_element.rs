@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+use crate::{Client, DynarustError, ItemUpdate, Resource};
+
+impl Client {
+    /// Sets the element at a position of a list field of an existing resource, without
+    /// rewriting the rest of the list, so concurrent writers of different positions do not
+    /// overwrite each other. The field can be a typed handle generated by `resource_fields!`.
+    /// Positions start at 0. If the position is past the end of the list, dynamo appends the
+    /// element at the end instead, and the list field must already exist, even if empty. If
+    /// the resource does not exist a `DynarustError::ConditionalCheckFailedError` is returned,
+    /// and as in `patch`, immutable fields and index keys are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The list field that is modified.
+    /// * `index` - Position of the element that is set.
+    /// * `value` - The new value of the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client
+    ///         .set_list_element::<Job>(job.pk_sk(), Job::fields().steps, 2, Step::done())
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn set_list_element<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: impl AsRef<str>,
+        index: usize,
+        value: impl Serialize,
+    ) -> Result<(), DynarustError> {
+        let value = serde_json::to_value(value)?;
+        let update = ItemUpdate::default().with_set_element(field.as_ref(), index, value);
+        self.patch_item::<T>(pk_sk, update, None).await?;
+        Ok(())
+    }
+
+    /// Removes the element at a position of a list field of an existing resource, shifting the
+    /// following elements one position back, which pops the head of queue-like lists when the
+    /// position is 0. Positions start at 0, and removing a position past the end of the list
+    /// is a no-op. If the resource does not exist a
+    /// `DynarustError::ConditionalCheckFailedError` is returned, and immutable fields and index
+    /// keys are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The list field that is modified.
+    /// * `index` - Position of the element that is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client
+    ///         .remove_list_element::<Queue>(queue.pk_sk(), Queue::fields().pending, 0)
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn remove_list_element<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: impl AsRef<str>,
+        index: usize,
+    ) -> Result<(), DynarustError> {
+        let update = ItemUpdate::default().with_remove_element(field.as_ref(), index);
+        self.patch_item::<T>(pk_sk, update, None).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::client::GSI1_PK;
+    use crate::{Client, ItemUpdate, Resource};
+
+    #[test]
+    fn builds_list_element_updates() {
+        let update = ItemUpdate::default()
+            .with_set_element("steps", 2, json!("done"))
+            .with_remove_element("pending", 0);
+        let (expression, names, _) = update.expression().unwrap();
+        assert_eq!(
+            expression,
+            "set #setElement0[2] = :setElement0 remove #removeElement0[0]"
+        );
+        assert_eq!(names["#removeElement0"], "pending");
+    }
+
+    #[tokio::test]
+    async fn rejects_list_elements_of_index_keys() {
+        let client = Client::local_on_port(12345).await;
+        let err = client
+            .remove_list_element::<TestResource>(("pk".into(), "sk".into()), GSI1_PK, 0)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid request: Cannot patch {GSI1_PK}")
+        );
+    }
+
+    #[tokio::test]
+    async fn mutates_list_elements() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "mutates_list_elements".to_string(),
+            sk: "1".to_string(),
+            string_arr: vec!["a".into(), "b".into(), "c".into()],
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        client
+            .set_list_element::<TestResource>(resource.pk_sk(), "string_arr", 1, "B")
+            .await
+            .unwrap();
+        client
+            .set_list_element::<TestResource>(resource.pk_sk(), "string_arr", 10, "d")
+            .await
+            .unwrap();
+        client
+            .remove_list_element::<TestResource>(resource.pk_sk(), "string_arr", 0)
+            .await
+            .unwrap();
+        client
+            .remove_list_element::<TestResource>(resource.pk_sk(), "string_arr", 10)
+            .await
+            .unwrap();
+
+        let stored = client
+            .get::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.string_arr, vec!["B", "c", "d"]);
+    }
+}
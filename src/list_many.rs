@@ -0,0 +1,134 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+
+use crate::{Client, DynarustError, ListOptions, Resource};
+
+/// Options for listing several partitions at once with `list_many`.
+#[derive(Debug, Clone)]
+pub struct ListManyOptions {
+    /// options applied to the listing of every pk, its limit being the maximum number of items
+    /// read from each of them. Cursors are not supported, as each pk has its own.
+    pub list: ListOptions,
+    /// maximum number of partitions listed at the same time, default is 8.
+    pub concurrency: usize,
+    /// whether to sort the merged items by their sort key, in the order requested by the list
+    /// options. If false, the items of each pk come together, in the order of the pks.
+    pub sorted: bool,
+}
+
+impl Default for ListManyOptions {
+    fn default() -> Self {
+        Self {
+            list: ListOptions::default(),
+            concurrency: 8,
+            sorted: false,
+        }
+    }
+}
+
+impl Client {
+    /// Lists the first page of resources under each of the provided pks, querying up to
+    /// `concurrency` partitions at the same time, and merges them in a single vector. Useful for
+    /// aggregating a handful of tenants or date buckets.
+    ///
+    /// # arguments
+    ///
+    /// * `pks` - Primary Keys of the listed partitions.
+    /// * `options` - listing options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let events = client
+    ///         .list_many::<Event>(
+    ///             vec!["2023-06-01".into(), "2023-06-02".into()],
+    ///             &ListManyOptions {
+    ///                 sorted: true,
+    ///                 ..Default::default()
+    ///             },
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn list_many<T: Resource + DeserializeOwned>(
+        &self,
+        pks: Vec<String>,
+        options: &ListManyOptions,
+    ) -> Result<Vec<T>, DynarustError> {
+        if options.list.cursor.is_some() {
+            return Err(DynarustError::InvalidRequestError(
+                "Cursors are not supported when listing several pks".into(),
+            ));
+        }
+        let pages: Vec<Vec<T>> = stream::iter(pks)
+            .map(|pk| async move {
+                let page = self.list::<T>(pk, &options.list).await?;
+                Ok::<_, DynarustError>(page.items)
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let mut items: Vec<T> = pages.into_iter().flatten().collect();
+        if options.sorted {
+            items.sort_by(|a, b| match options.list.sort_desc {
+                true => b.pk_sk().1.cmp(&a.pk_sk().1),
+                false => a.pk_sk().1.cmp(&b.pk_sk().1),
+            });
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::{Client, ListManyOptions, ListOptions};
+
+    #[tokio::test]
+    async fn lists_many_partitions() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pks: Vec<String> = (0..3)
+            .map(|i| format!("lists_many_partitions{i}"))
+            .collect();
+        for (i, pk) in pks.iter().enumerate() {
+            for j in 0..3 {
+                let resource = TestResource {
+                    pk: pk.clone(),
+                    sk: format!("{}", j * 3 + i),
+                    ..Default::default()
+                };
+                client.force_create(&resource).await.unwrap();
+            }
+        }
+
+        let options = ListManyOptions {
+            list: ListOptions {
+                limit: 2,
+                ..Default::default()
+            },
+            concurrency: 2,
+            sorted: false,
+        };
+        let items = client
+            .list_many::<TestResource>(pks.clone(), &options)
+            .await
+            .unwrap();
+        let sks: Vec<_> = items.iter().map(|item| item.sk.as_str()).collect();
+        assert_eq!(sks, vec!["0", "3", "1", "4", "2", "5"]);
+
+        let options = ListManyOptions {
+            sorted: true,
+            ..options
+        };
+        let items = client
+            .list_many::<TestResource>(pks, &options)
+            .await
+            .unwrap();
+        let sks: Vec<_> = items.iter().map(|item| item.sk.as_str()).collect();
+        assert_eq!(sks, vec!["0", "1", "2", "3", "4", "5"]);
+    }
+}
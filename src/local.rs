@@ -0,0 +1,98 @@
+use aws_sdk_dynamodb::{Credentials, Region};
+
+use crate::{Client, ClientBuilder};
+
+/// Options for connecting to DynamoDB Local.
+///
+/// Unless DynamoDB Local is started with `-sharedDb`, it keeps a separate database for every
+/// access key id and region, in a file named `{access_key_id}_{region}.db`, so tables created
+/// by other tools, like the AWS CLI or NoSQL Workbench, are only visible to dynarust if both
+/// use the same access key id and region. With `-sharedDb` every client sees the same
+/// database, whatever its credentials are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalOptions {
+    /// port where DynamoDB Local listens, default is 8000.
+    pub port: u16,
+    /// region sent with the requests, default is `us-east-1`.
+    pub region: String,
+    /// access key id sent with the requests, default is `.`.
+    pub access_key_id: String,
+    /// secret access key, which DynamoDB Local does not validate, default is `.`.
+    pub secret_access_key: String,
+}
+
+impl Default for LocalOptions {
+    fn default() -> Self {
+        Self {
+            port: 8000,
+            region: "us-east-1".into(),
+            access_key_id: ".".into(),
+            secret_access_key: ".".into(),
+        }
+    }
+}
+
+impl LocalOptions {
+    /// Options that reach the same database as the AWS CLI when DynamoDB Local runs without
+    /// `-sharedDb`, given the access key id and region of the CLI profile.
+    pub fn matching(access_key_id: impl Into<String>, region: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            region: region.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Client {
+    /// Connect against a local version of DynamoDB with the provided options. Unlike
+    /// `Client::aws`, the region and credentials are never read from the environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     // same database as `aws dynamodb --endpoint-url http://localhost:8000 ...`
+    ///     let client = Client::local_with(LocalOptions::matching("AKIAEXAMPLE", "eu-west-1")).await;
+    /// }
+    /// ```
+    pub async fn local_with(options: LocalOptions) -> Self {
+        let credentials = Credentials::new(
+            options.access_key_id,
+            options.secret_access_key,
+            None,
+            None,
+            "dynarust-local",
+        );
+        let cfg = aws_config::from_env()
+            .region(Region::new(options.region))
+            .credentials_provider(credentials)
+            .load()
+            .await;
+        ClientBuilder::new(cfg)
+            .endpoint_url(format!("http://localhost:{}", options.port))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::{Client, LocalOptions, Resource};
+
+    #[tokio::test]
+    async fn connects_with_local_options() {
+        let client = Client::local_with(LocalOptions::default()).await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "connects_with_local_options".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let other = Client::local().await;
+        let got = other.get::<TestResource>(resource.pk_sk()).await.unwrap();
+        assert_eq!(got, Some(resource));
+    }
+}
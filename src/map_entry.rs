@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity, ReturnValue};
+use serde::Serialize;
+
+use crate::audit::audit_image;
+use crate::condition_check::condition_check_exists;
+use crate::stats::Operation;
+use crate::{AuditOperation, Client, DynarustError, Resource};
+
+impl Client {
+    /// Sets a single entry of a map field of an existing resource, like a `HashMap<String, S>`,
+    /// without rewriting the rest of the map, so concurrent writers of different entries do not
+    /// overwrite each other. Both the field and the entry key are sent as attribute names, so
+    /// keys with dots, spaces or any other character are written as they are. The map field
+    /// must already exist, even if empty. If the resource does not exist a
+    /// `DynarustError::ConditionalCheckFailedError` is returned, and as in `patch`, immutable
+    /// fields and index keys are rejected.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The map field that is modified.
+    /// * `key` - Key of the entry that is set.
+    /// * `value` - The new value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let address = Address { street: "Main St".into(), city: "Springfield".into() };
+    ///     client
+    ///         .set_map_entry::<User>(user.pk_sk(), "addresses", "home", &address)
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn set_map_entry<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        key: &str,
+        value: impl Serialize,
+    ) -> Result<(), DynarustError> {
+        self.check_patched_field::<T>(&T::table(), field)?;
+        let value = self.encode_value(&serde_json::to_value(value)?)?;
+        self.update_path::<T>(
+            pk_sk,
+            "set #map.#mapKey = :mapValue",
+            map_entry_names(field, key),
+            HashMap::from([(":mapValue".to_string(), value)]),
+        )
+        .await
+    }
+
+    /// Removes a single entry of a map field of an existing resource, without rewriting the
+    /// rest of the map. Removing an entry that does not exist is a no-op. If the resource does
+    /// not exist a `DynarustError::ConditionalCheckFailedError` is returned.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The map field that is modified.
+    /// * `key` - Key of the entry that is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client
+    ///         .remove_map_entry::<User>(user.pk_sk(), "addresses", "home")
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn remove_map_entry<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        key: &str,
+    ) -> Result<(), DynarustError> {
+        self.check_patched_field::<T>(&T::table(), field)?;
+        self.update_path::<T>(
+            pk_sk,
+            "remove #map.#mapKey",
+            map_entry_names(field, key),
+            HashMap::new(),
+        )
+        .await
+    }
+
+    /// Runs an update expression on a nested path of an existing resource.
+    pub(crate) async fn update_path<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        expression: &str,
+        mut names: HashMap<String, String>,
+        mut values: HashMap<String, AttributeValue>,
+    ) -> Result<(), DynarustError> {
+        let table = T::table();
+        let mut expression = expression.to_string();
+        self.intercept_update(&table, &mut expression, &mut names, &mut values)?;
+
+        let (pk, sk) = pk_sk.clone();
+        let (pk_attr, sk_attr) = self.key_names(&table);
+        let mut builder = self
+            .dynamo(&table)
+            .update_item()
+            .table_name(&table)
+            .key(pk_attr, AttributeValue::S(self.scope_pk(pk)))
+            .key(sk_attr, AttributeValue::S(sk))
+            .update_expression(expression)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()))
+            .set_return_values(self.audit.is_some().then_some(ReturnValue::AllNew))
+            .set_return_item_collection_metrics(self.return_collection_metrics())
+            .return_consumed_capacity(ReturnConsumedCapacity::Total);
+
+        builder = condition_check_exists()
+            .externalized(self, &table)
+            .dump_in_update_item(builder);
+
+        let result = self
+            .stats
+            .measure(Operation::UpdateItem, async { Ok(builder.send().await?) })
+            .await?;
+        self.stats.add_capacity(
+            Operation::UpdateItem,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+        self.report_collection_metrics(&table, result.item_collection_metrics());
+        let after = audit_image(self, result.attributes())?;
+        self.audit(AuditOperation::Update, &table, pk_sk, None, after)
+            .await
+    }
+}
+
+fn map_entry_names(field: &str, key: &str) -> HashMap<String, String> {
+    HashMap::from([
+        ("#map".to_string(), field.to_string()),
+        ("#mapKey".to_string(), key.to_string()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::client::GSI1_PK;
+    use crate::{Client, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Contact {
+        id: String,
+        addresses: HashMap<String, Address>,
+    }
+
+    impl Resource for Contact {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("mutates_map_entries".into(), self.id.clone())
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Frozen {
+        id: String,
+        addresses: HashMap<String, Address>,
+    }
+
+    impl Resource for Frozen {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("rejects_immutable_map_entries".into(), self.id.clone())
+        }
+
+        fn immutable_fields() -> &'static [&'static str] {
+            &["addresses"]
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_immutable_map_entries() {
+        let client = Client::local_on_port(12345).await;
+        let pk_sk = ("rejects_immutable_map_entries".to_string(), "1".to_string());
+        let err = client
+            .set_map_entry::<Frozen>(pk_sk.clone(), "addresses", "home", "Paris")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid request: Cannot patch addresses");
+        assert!(client
+            .remove_map_entry::<Frozen>(pk_sk, "addresses", "home")
+            .await
+            .is_err());
+        assert!(client
+            .remove_map_entry::<Contact>(("a".into(), "b".into()), GSI1_PK, "home")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn mutates_map_entries() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let address = |city: &str| Address { city: city.into() };
+        let contact = Contact {
+            id: "1".into(),
+            addresses: HashMap::from([("home".to_string(), address("Paris"))]),
+        };
+        client.force_create(&contact).await.unwrap();
+
+        client
+            .set_map_entry::<Contact>(
+                contact.pk_sk(),
+                "addresses",
+                "work.main #1",
+                address("Rome"),
+            )
+            .await
+            .unwrap();
+        client
+            .remove_map_entry::<Contact>(contact.pk_sk(), "addresses", "home")
+            .await
+            .unwrap();
+        let stored = client
+            .get::<Contact>(contact.pk_sk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored.addresses,
+            HashMap::from([("work.main #1".to_string(), address("Rome"))])
+        );
+
+        let missing = ("mutates_map_entries".to_string(), "2".to_string());
+        assert!(client
+            .remove_map_entry::<Contact>(missing, "addresses", "home")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    condition_check, Client, Condition, ConditionCheckInfo, DynamoOperator, DynarustError, KeyOf,
+    Resource,
+};
+
+/// Times that `Client::modify` reads and writes a resource again after losing a race.
+const MAX_MODIFY_RETRIES: usize = 5;
+
+impl Client {
+    /// Reads a resource, applies a modification to it and writes back the fields that changed,
+    /// only if the resource was not modified by someone else in between. On a conflict the
+    /// resource is read again and the modification is applied again, up to 5 times, after which
+    /// a `DynarustError::ConditionalCheckFailedError` is returned. The modification can be run
+    /// several times, so it should not have side effects. Returns the resource as it was
+    /// written, or None if it does not exist.
+    ///
+    /// The write is conditioned on every field of the resource still having the value that was
+    /// read, so for resources with big collections or very many fields, the condition might
+    /// exceed the maximum expression size of dynamo.
+    ///
+    /// # arguments
+    ///
+    /// * `key` - The key of the resource.
+    /// * `f` - Builds the modified resource from the current one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let cart = client
+    ///         .modify(Cart::key("carts", user_id), |mut cart| {
+    ///             cart.items.push(item.clone());
+    ///             cart.total += item.price;
+    ///             cart
+    ///         })
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn modify<T, F>(&self, key: KeyOf<T>, f: F) -> Result<Option<T>, DynarustError>
+    where
+        T: Resource + Serialize + DeserializeOwned,
+        F: Fn(T) -> T,
+    {
+        let mut attempt = 0;
+        loop {
+            let Some(current) = self.get::<T>(key.pk_sk()).await? else {
+                return Ok(None);
+            };
+            let original = Client::resource_as_object(&current)?;
+            let checks = unchanged_checks::<T>(&original)?;
+            let modified = Client::resource_as_object(&f(current))?;
+
+            let mut request = Map::new();
+            for (k, v) in modified.iter() {
+                if original.get(k) != Some(v) {
+                    request.insert(k.clone(), v.clone());
+                }
+            }
+            for k in original.keys() {
+                if !modified.contains_key(k) {
+                    request.insert(k.clone(), Value::Null);
+                }
+            }
+            let current: T = serde_json::from_value(Value::Object(original))?;
+            if request.is_empty() {
+                return Ok(Some(current));
+            }
+
+            match self
+                .update_with_checks(&current, Value::Object(request), checks)
+                .await
+            {
+                Err(DynarustError::ConditionalCheckFailedError(_))
+                    if attempt < MAX_MODIFY_RETRIES =>
+                {
+                    attempt += 1;
+                }
+                result => return result.map(Some),
+            }
+        }
+    }
+}
+
+/// Condition checks that pass only if every field of the item still has the value it had
+/// when it was read. Fields that are not written because of the write policy of the resource
+/// must still be missing.
+fn unchanged_checks<T: Resource>(
+    original: &Map<String, Value>,
+) -> Result<Vec<ConditionCheckInfo>, DynarustError> {
+    original
+        .iter()
+        .map(|(k, v)| {
+            let condition = match T::write_policy().skips(v) {
+                true => Condition::NotExists,
+                false => Condition::Compare(DynamoOperator::Eq, v.clone()),
+            };
+            condition_check(k, condition)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+
+    use crate::client::tests::TestResource;
+    use crate::{Client, Resource};
+
+    #[tokio::test]
+    async fn retries_conflicting_modifications() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "retries_conflicting_modifications".to_string(),
+            sk: "1".to_string(),
+            string_arr: vec![],
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+        let key = || TestResource::key(resource.pk.clone(), resource.sk.clone());
+
+        let results = join_all((0..3).map(|i| {
+            client.modify(key(), move |mut resource| {
+                resource.int += 1;
+                resource.string_arr.push(i.to_string());
+                resource
+            })
+        }))
+        .await;
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        let stored = client
+            .get::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.int, 3);
+        assert_eq!(stored.string_arr.len(), 3);
+
+        let missing = TestResource::key(resource.pk.clone(), "2");
+        assert_eq!(client.modify(missing, |r| r).await.unwrap(), None);
+    }
+}
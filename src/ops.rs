@@ -0,0 +1,166 @@
+//! Maintenance operations meant to be run periodically, for example from a scheduled Lambda.
+use aws_sdk_dynamodb::model::BackupTypeFilter;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::{Client, DynarustError, Resource};
+
+/// A backup of a table, as listed by dynamo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub name: String,
+    pub arn: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Changes made by `ensure_backup_schedule`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackupReport {
+    /// name of the backup created for today, None if it already existed.
+    pub created: Option<String>,
+    /// names of the pruned backups.
+    pub deleted: Vec<String>,
+}
+
+/// Name of the backup of a table for a given day.
+fn backup_name(table: &str, now: DateTime<Utc>) -> String {
+    format!("{table}-{}", now.format("%Y-%m-%d"))
+}
+
+/// Decides which backup needs to be created, if any, and which ones need to be deleted. Only
+/// the backups named after the table are considered, so backups created by hand are kept.
+fn plan_backups(
+    table: &str,
+    now: DateTime<Utc>,
+    retention: Duration,
+    existing: &[Backup],
+) -> (Option<String>, Vec<Backup>) {
+    let today = backup_name(table, now);
+    let prefix = format!("{table}-");
+    let create = (!existing.iter().any(|backup| backup.name == today)).then_some(today);
+    let prune = existing
+        .iter()
+        .filter(|backup| backup.name.starts_with(&prefix) && backup.created_at < now - retention)
+        .cloned()
+        .collect();
+    (create, prune)
+}
+
+/// Lists the backups of a table created on demand.
+async fn list_backups(client: &Client, table: &str) -> Result<Vec<Backup>, DynarustError> {
+    let mut backups = vec![];
+    let mut exclusive_start_backup_arn = None;
+    loop {
+        let result = client
+            .dynamo(table)
+            .list_backups()
+            .table_name(table)
+            .backup_type(BackupTypeFilter::User)
+            .set_exclusive_start_backup_arn(exclusive_start_backup_arn)
+            .send()
+            .await?;
+
+        for summary in result.backup_summaries().unwrap_or_default() {
+            if let (Some(name), Some(arn), Some(created_at)) = (
+                summary.backup_name(),
+                summary.backup_arn(),
+                summary.backup_creation_date_time(),
+            ) {
+                backups.push(Backup {
+                    name: name.to_string(),
+                    arn: arn.to_string(),
+                    created_at: Utc
+                        .timestamp_opt(created_at.secs(), 0)
+                        .single()
+                        .unwrap_or_default(),
+                });
+            }
+        }
+
+        exclusive_start_backup_arn = result.last_evaluated_backup_arn().map(str::to_string);
+        if exclusive_start_backup_arn.is_none() {
+            return Ok(backups);
+        }
+    }
+}
+
+/// Creates an on demand backup of the table of resource T named after the table and the
+/// current date, unless it already exists, and deletes the backups created by previous runs
+/// that are older than the retention window. Running it more than once a day is harmless.
+///
+/// # arguments
+///
+/// * `client` - The client used for managing the backups.
+/// * `retention` - How long backups are kept.
+///
+/// # Examples
+///
+/// ```
+/// async fn handler(_: LambdaEvent<Value>) -> Result<(), Error> {
+///     let client = dynarust::lambda::client().await;
+///     dynarust::ops::ensure_backup_schedule::<Car>(client, Duration::days(14)).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn ensure_backup_schedule<T: Resource>(
+    client: &Client,
+    retention: Duration,
+) -> Result<BackupReport, DynarustError> {
+    let table = T::table();
+    let existing = list_backups(client, &table).await?;
+    let (create, prune) = plan_backups(&table, client.now(), retention, &existing);
+
+    if let Some(name) = &create {
+        client
+            .dynamo(&table)
+            .create_backup()
+            .table_name(&table)
+            .backup_name(name)
+            .send()
+            .await?;
+    }
+
+    let mut deleted = vec![];
+    for backup in prune {
+        client
+            .dynamo(&table)
+            .delete_backup()
+            .backup_arn(backup.arn)
+            .send()
+            .await?;
+        deleted.push(backup.name);
+    }
+
+    Ok(BackupReport {
+        created: create,
+        deleted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_todays_backup_and_prunes_old_ones() {
+        let now = Utc.with_ymd_and_hms(2023, 5, 10, 12, 0, 0).unwrap();
+        let backup = |name: &str, days_ago: i64| Backup {
+            name: name.to_string(),
+            arn: format!("arn:{name}"),
+            created_at: now - Duration::days(days_ago),
+        };
+        let existing = vec![
+            backup("Cars-2023-05-09", 1),
+            backup("Cars-2023-04-01", 39),
+            backup("manual", 100),
+        ];
+
+        let (create, prune) = plan_backups("Cars", now, Duration::days(30), &existing);
+        assert_eq!(create.as_deref(), Some("Cars-2023-05-10"));
+        assert_eq!(prune, vec![backup("Cars-2023-04-01", 39)]);
+
+        let existing = vec![backup("Cars-2023-05-10", 0)];
+        let (create, prune) = plan_backups("Cars", now, Duration::days(30), &existing);
+        assert_eq!(create, None);
+        assert!(prune.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde_json::{json, Map, Value};
+
+use crate::DynarustError;
+
+/// Opaque pointer to the position where a paginated operation stopped. Pass it back in the
+/// options of the same operation for retrieving the next page.
+///
+/// A cursor holds the raw LastEvaluatedKey returned by dynamo, which has every key attribute of
+/// the last evaluated item, the table ones included when listing an index. Pages continue
+/// right after that exact item, so they never skip or repeat items at their boundaries, even
+/// if several items share the same index sort key or a prefix of it. Sort key offsets, like
+/// the `from` list option, are only meant for choosing where the listing starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor(pub(crate) HashMap<String, AttributeValue>);
+
+impl Cursor {
+    /// The raw LastEvaluatedKey of the page that returned this cursor.
+    pub fn last_evaluated_key(&self) -> &HashMap<String, AttributeValue> {
+        &self.0
+    }
+
+    /// Encodes the cursor as an opaque url safe token, for handing it to the clients of an API
+    /// that paginates over a listing. Only cursors of string and number keys can be encoded.
+    /// Numbers are kept as the string dynamo returned, so they do not lose precision.
+    pub fn encode(&self) -> Result<String, DynarustError> {
+        let mut key = Map::new();
+        for (k, v) in self.0.iter() {
+            let typed = match v {
+                AttributeValue::S(s) => json!({ "S": s }),
+                AttributeValue::N(n) => json!({ "N": n }),
+                _ => {
+                    return Err(DynarustError::InvalidRequestError(format!(
+                        "Cannot encode a cursor with the key attribute {k}"
+                    )))
+                }
+            };
+            key.insert(k.clone(), typed);
+        }
+        let json = Value::Object(key).to_string();
+        Ok(json.bytes().map(|b| format!("{b:02x}")).collect())
+    }
+
+    /// Decodes a token built by `Cursor::encode`.
+    pub fn decode(token: &str) -> Result<Self, DynarustError> {
+        let invalid = || DynarustError::InvalidRequestError("Invalid cursor".into());
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| {
+                let byte = token.get(i..i + 2).ok_or_else(invalid)?;
+                u8::from_str_radix(byte, 16).map_err(|_| invalid())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let key: HashMap<String, HashMap<String, String>> =
+            serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        let mut last_evaluated_key = HashMap::new();
+        for (k, typed) in key {
+            let mut typed = typed.into_iter();
+            let attr = match (typed.next(), typed.next()) {
+                (Some((t, s)), None) if t == "S" => AttributeValue::S(s),
+                (Some((t, n)), None) if t == "N" && n.parse::<f64>().is_ok() => {
+                    AttributeValue::N(n)
+                }
+                _ => return Err(invalid()),
+            };
+            last_evaluated_key.insert(k, attr);
+        }
+        Ok(Self(last_evaluated_key))
+    }
+}
+
+impl From<HashMap<String, AttributeValue>> for Cursor {
+    fn from(last_evaluated_key: HashMap<String, AttributeValue>) -> Self {
+        Self(last_evaluated_key)
+    }
+}
+
+/// A page of resources returned by `Client::list`, whose `next` cursor continues the listing.
+pub type ListResult<T> = Page<T>;
+
+/// A single page of results returned by any of the paginated operations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// The items in this page.
+    pub items: Vec<T>,
+    /// Cursor for retrieving the next page, None if this is the last one.
+    pub next: Option<Cursor>,
+    /// Number of items evaluated by dynamo before applying any filter.
+    pub scanned_count: i32,
+    /// Number of items returned by dynamo after applying the filters.
+    pub count: i32,
+    /// Capacity units consumed while retrieving this page, if reported by dynamo.
+    pub consumed_capacity: Option<f64>,
+}
+
+impl<T> Page<T> {
+    /// Whether there are more pages after this one.
+    pub fn has_next(&self) -> bool {
+        self.next.is_some()
+    }
+
+    /// Whether a filter discarded some of the evaluated items. Capacity is consumed for every
+    /// evaluated item, so queries that discard most of them are wasting read capacity.
+    pub fn discarded_by_filter(&self) -> bool {
+        self.scanned_count > self.count
+    }
+}
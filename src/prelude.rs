@@ -0,0 +1,12 @@
+//! Convenience re-exports of the most commonly used dynarust items.
+//!
+//! ```
+//! use dynarust::prelude::*;
+//! ```
+pub use crate::{
+    begin_transaction, condition_check_exists, condition_check_not_exists, condition_check_number,
+    condition_check_set_contains, condition_check_string, transact_condition_check,
+    transact_create, transact_create_with_checks, transact_delete, transact_delete_with_checks,
+    transact_update, transact_update_with_checks, Client, ConditionCheckInfo, DynamoOperator,
+    DynamoStore, DynarustError, ListOptions, Page, Repository, Resource, TransactionContext,
+};
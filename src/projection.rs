@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::de::DeserializeOwned;
+
+use crate::list::{list_query, query_values};
+use crate::{Client, DynarustError, ListOptions, Page, Resource};
+
+/// A view of some of the fields of a resource, read with a projection expression so that only
+/// those attributes are retrieved from dynamo. Projections are generated with the
+/// `resource_projection!` macro.
+pub trait Projection: DeserializeOwned {
+    /// The resource this is a view of.
+    type Resource: Resource;
+    /// The attributes requested to dynamo, exactly the fields of the projection.
+    fn attributes() -> &'static [&'static str];
+}
+
+/// Projection expression requesting the attributes of P, along with its attribute names.
+fn projection_expression<P: Projection>() -> (String, HashMap<String, String>) {
+    let mut names = HashMap::new();
+    let mut placeholders = vec![];
+    for (i, attr) in P::attributes().iter().enumerate() {
+        let name = format!("#projAttr{i}");
+        placeholders.push(name.clone());
+        names.insert(name, attr.to_string());
+    }
+    (placeholders.join(", "), names)
+}
+
+impl Client {
+    /// Retrieves the fields of a resource declared in the projection P, without reading the
+    /// rest of its attributes. If the resource does not exist returns Option::None.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// dynarust::resource_projection!(pub User => UserSummary { id: String, name: String });
+    ///
+    /// async {
+    ///     let summary = client.get_projection::<UserSummary>(user.pk_sk()).await?;
+    /// }
+    /// ```
+    pub async fn get_projection<P: Projection>(
+        &self,
+        (pk, sk): (String, String),
+    ) -> Result<Option<P>, DynarustError> {
+        let table = P::Resource::table();
+        let (expression, names) = projection_expression::<P>();
+        let (pk_attr, sk_attr) = self.key_names(&table);
+        let result = self
+            .dynamo(&table)
+            .get_item()
+            .table_name(&table)
+            .key(pk_attr, AttributeValue::S(self.scope_pk(pk)))
+            .key(sk_attr, AttributeValue::S(sk))
+            .projection_expression(expression)
+            .set_expression_attribute_names(Some(names))
+            .consistent_read(self.consistent_read(None))
+            .send()
+            .await?;
+
+        match result.item() {
+            Some(item) => Ok(Some(serde_json::from_value(self.decode_item(item)?)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List the fields declared in the projection P of all the resources under the same pk,
+    /// one page at a time, without reading the rest of their attributes.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the listed resources live.
+    /// * `options` - pagination options.
+    pub async fn list_projection<P: Projection>(
+        &self,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<P>, DynarustError> {
+        let table = P::Resource::table();
+        let (expression, names) = projection_expression::<P>();
+        let mut builder = list_query(self, &table, pk, options)?.projection_expression(expression);
+        for (k, v) in names {
+            builder = builder.expression_attribute_names(k, v);
+        }
+        let page = query_values(self, &table, builder, options).await?;
+
+        let mut items = vec![];
+        for item in page.items {
+            items.push(serde_json::from_value(item)?)
+        }
+        Ok(Page {
+            items,
+            next: page.next,
+            scanned_count: page.scanned_count,
+            count: page.count,
+            consumed_capacity: page.consumed_capacity,
+        })
+    }
+}
+
+/// Generates a projection of a resource: a struct with the provided subset of its fields that
+/// implements `Projection`, so it can be read with `get_projection` and `list_projection`. A
+/// field that does not exist in the resource, or whose type does not match, is a compile
+/// error. As in `resource_fields!`, fields renamed with serde attributes are not supported.
+/// The generated struct derives serde through `dynarust::serde`, so the crate must be
+/// available under that name.
+///
+/// # Examples
+///
+/// ```
+/// dynarust::resource_projection!(pub User => UserSummary { id: String, name: String });
+/// ```
+#[macro_export]
+macro_rules! resource_projection {
+    ($vis:vis $resource:ident => $projection:ident { $($field:ident : $type:ty),* $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq, $crate::serde::Deserialize)]
+        #[serde(crate = "dynarust::serde")]
+        $vis struct $projection {
+            $($vis $field: $type,)*
+        }
+
+        impl $crate::Projection for $projection {
+            type Resource = $resource;
+
+            fn attributes() -> &'static [&'static str] {
+                const _: fn(&$resource) = |resource| {
+                    $(let _: &$type = &resource.$field;)*
+                };
+                &[$(stringify!($field)),*]
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::projection::projection_expression;
+    use crate::{Client, ListOptions, Projection, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+    struct User {
+        id: String,
+        name: String,
+        bio: String,
+    }
+
+    impl Resource for User {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("users".into(), self.id.clone())
+        }
+    }
+
+    resource_projection!(User => UserSummary { id: String, name: String });
+
+    #[test]
+    fn requests_exactly_the_projected_fields() {
+        assert_eq!(UserSummary::attributes(), &["id", "name"]);
+        let (expression, names) = projection_expression::<UserSummary>();
+        assert_eq!(expression, "#projAttr0, #projAttr1");
+        assert_eq!(names["#projAttr1"], "name");
+    }
+
+    #[tokio::test]
+    async fn reads_projections() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let user = User {
+            id: "reads_projections".into(),
+            name: "Ada".into(),
+            bio: "a very long bio".into(),
+        };
+        client.force_create(&user).await.unwrap();
+
+        let summary = client
+            .get_projection::<UserSummary>(user.pk_sk())
+            .await
+            .unwrap();
+        let expected = UserSummary {
+            id: user.id.clone(),
+            name: user.name.clone(),
+        };
+        assert_eq!(summary, Some(expected.clone()));
+
+        let page = client
+            .list_projection::<UserSummary>("users".into(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert!(page.items.contains(&expected));
+    }
+}
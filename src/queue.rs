@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnValue};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::condition_check::{condition_check, condition_check_exists, condition_check_not_exists};
+use crate::stats::Operation;
+use crate::update::update_item;
+use crate::{
+    AuditOperation, Client, Condition, DynamoOperator, DynamoStore, DynarustError, ItemUpdate,
+};
+
+/// Attribute with the payload of a message.
+const PAYLOAD: &str = "payload";
+/// Attribute with the epoch milliseconds until which a message is claimed by a consumer.
+const CLAIMED_UNTIL: &str = "claimed_until";
+/// Messages that are read at once while looking for one that can be claimed.
+const DEQUEUE_PAGE_SIZE: i32 = 25;
+
+/// A FIFO work queue stored in a single partition of a table. Messages live under the
+/// PrimaryKey of the queue, with a zero-padded sequence number as sort key, so that they are
+/// listed in the order in which they were enqueued. The sequence is kept in its own item, under
+/// the PrimaryKey `<name>#sequence`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Queue {
+    table: String,
+    name: String,
+    visibility_timeout: Duration,
+}
+
+impl Queue {
+    /// A queue stored in the provided table under the PrimaryKey `name`, whose messages stay
+    /// claimed for 30 seconds.
+    pub fn new(table: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            name: name.into(),
+            visibility_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Returns the queue with a different visibility timeout, which is the time a dequeued
+    /// message stays invisible to other consumers. A message that is not acknowledged before
+    /// the timeout expires is handed out again, so it should be longer than the time it
+    /// takes to process a message.
+    pub fn with_visibility_timeout(self, visibility_timeout: Duration) -> Self {
+        Self {
+            visibility_timeout,
+            ..self
+        }
+    }
+}
+
+/// A message claimed with `Client::dequeue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueMessage<P> {
+    /// sort key of the message, its position in the queue.
+    pub id: String,
+    pub payload: P,
+    /// end of the claim, in epoch milliseconds, which identifies it when acknowledging.
+    claimed_until: i64,
+}
+
+impl Client {
+    /// Appends a message to the end of a queue, returning its id.
+    ///
+    /// # arguments
+    ///
+    /// * `queue` - The queue to which the message is appended.
+    /// * `payload` - Content of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let emails = Queue::new("Jobs", "emails");
+    ///     client.enqueue(&emails, &Email { to: "ada@example.com".into() }).await?;
+    /// }
+    /// ```
+    pub async fn enqueue<P: Serialize>(
+        &self,
+        queue: &Queue,
+        payload: &P,
+    ) -> Result<String, DynarustError> {
+        let sequence = (
+            self.scope_pk(format!("{}#sequence", queue.name)),
+            "sequence".to_string(),
+        );
+        let number = self
+            .reserve_sequence_block(&queue.table, sequence, 1)
+            .await?;
+        let id = format!("{number:020}");
+
+        let mut item = Map::new();
+        item.insert(PAYLOAD.into(), serde_json::to_value(payload)?);
+        item.insert(CLAIMED_UNTIL.into(), Value::from(0));
+        self.put_item(
+            &queue.table,
+            (queue.name.clone(), id.clone()),
+            item,
+            condition_check_not_exists(),
+        )
+        .await?;
+        Ok(id)
+    }
+
+    /// Claims the oldest message of a queue that is not claimed by another consumer, or whose
+    /// claim expired. The message stays in the queue, invisible to other consumers, until it is
+    /// acknowledged with `ack` or until the visibility timeout of the queue expires, so a
+    /// consumer that crashes does not lose it. Returns None if there are no visible messages.
+    ///
+    /// # arguments
+    ///
+    /// * `queue` - The queue from which the message is claimed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     while let Some(message) = client.dequeue::<Email>(&emails).await? {
+    ///         send(&message.payload).await?;
+    ///         client.ack(&emails, &message).await?;
+    ///     }
+    /// }
+    /// ```
+    pub async fn dequeue<P: DeserializeOwned>(
+        &self,
+        queue: &Queue,
+    ) -> Result<Option<QueueMessage<P>>, DynarustError> {
+        let (pk_attr, sk_attr) = self.key_names(&queue.table);
+        let mut exclusive_start_key = None;
+        loop {
+            let now = self.now().timestamp_millis();
+            let result = self
+                .dynamo(&queue.table)
+                .query()
+                .table_name(&queue.table)
+                .key_condition_expression("#pk = :pk")
+                .filter_expression("#claimedUntil < :now")
+                .expression_attribute_names("#pk", pk_attr)
+                .expression_attribute_names("#claimedUntil", CLAIMED_UNTIL)
+                .expression_attribute_values(
+                    ":pk",
+                    AttributeValue::S(self.scope_pk(queue.name.clone())),
+                )
+                .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+                .consistent_read(true)
+                .limit(DEQUEUE_PAGE_SIZE)
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .await?;
+
+            for item in result.items().unwrap_or_default() {
+                let Some(AttributeValue::S(id)) = item.get(sk_attr) else {
+                    continue;
+                };
+                match self.claim_message(queue, id, now).await {
+                    Ok(message) => return Ok(Some(message)),
+                    // another consumer claimed it first.
+                    Err(DynarustError::ConditionalCheckFailedError(_)) => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+
+            exclusive_start_key = result.last_evaluated_key().cloned();
+            if exclusive_start_key.is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Acknowledges a message claimed with `dequeue`, deleting it from the queue. If the claim
+    /// expired and the message was claimed again by another consumer, or it was already
+    /// acknowledged, a `DynarustError::ConditionalCheckFailedError` is returned.
+    ///
+    /// # arguments
+    ///
+    /// * `queue` - The queue from which the message was claimed.
+    /// * `message` - The claimed message.
+    pub async fn ack<P>(
+        &self,
+        queue: &Queue,
+        message: &QueueMessage<P>,
+    ) -> Result<(), DynarustError> {
+        let condition = condition_check(
+            CLAIMED_UNTIL,
+            Condition::Compare(DynamoOperator::Eq, json!(message.claimed_until)),
+        )?;
+        self.delete_item(
+            &queue.table,
+            (queue.name.clone(), message.id.clone()),
+            condition,
+        )
+        .await
+    }
+
+    /// Claims a message if it is still visible at `now`.
+    async fn claim_message<P: DeserializeOwned>(
+        &self,
+        queue: &Queue,
+        id: &str,
+        now: i64,
+    ) -> Result<QueueMessage<P>, DynarustError> {
+        let claimed_until = now + queue.visibility_timeout.as_millis() as i64;
+        let key = (queue.name.clone(), id.to_string());
+        let condition = condition_check_exists().merge(vec![condition_check(
+            CLAIMED_UNTIL,
+            Condition::Compare(DynamoOperator::Ls, json!(now)),
+        )?]);
+        let update = update_item(
+            self,
+            &queue.table,
+            key.clone(),
+            ItemUpdate::default().with_set(CLAIMED_UNTIL, json!(claimed_until)),
+            condition,
+            Some(ReturnValue::AllNew),
+        );
+        let after = self.stats.measure(Operation::UpdateItem, update).await?;
+        self.audit(
+            AuditOperation::Update,
+            &queue.table,
+            key,
+            None,
+            after.clone(),
+        )
+        .await?;
+
+        let mut item = after.unwrap_or(Value::Null);
+        Ok(QueueMessage {
+            id: id.to_string(),
+            payload: serde_json::from_value(item[PAYLOAD].take())?,
+            claimed_until,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, DynarustError, ManualClock, Queue};
+
+    #[tokio::test]
+    async fn hands_out_messages_in_order() {
+        let clock = ManualClock::default();
+        let client = Client::local().await.with_clock(clock.clone());
+        client.create_table::<TestResource>(None).await.unwrap();
+        let queue = Queue::new(TABLE.as_str(), "hands_out_messages_in_order")
+            .with_visibility_timeout(Duration::from_secs(60));
+
+        for payload in ["first", "second"] {
+            client.enqueue(&queue, &payload).await.unwrap();
+        }
+
+        let first = client.dequeue::<String>(&queue).await.unwrap().unwrap();
+        let second = client.dequeue::<String>(&queue).await.unwrap().unwrap();
+        assert_eq!(
+            (first.payload.as_str(), second.payload.as_str()),
+            ("first", "second")
+        );
+        assert!(client.dequeue::<String>(&queue).await.unwrap().is_none());
+
+        client.ack(&queue, &first).await.unwrap();
+        clock.advance(chrono::Duration::seconds(61));
+        let again = client.dequeue::<String>(&queue).await.unwrap().unwrap();
+        assert_eq!(again.payload, "second");
+        assert!(matches!(
+            client.ack(&queue, &second).await,
+            Err(DynarustError::ConditionalCheckFailedError(_))
+        ));
+        client.ack(&queue, &again).await.unwrap();
+        assert!(client.dequeue::<String>(&queue).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity};
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::stats::Operation;
+use crate::{Client, DynarustError};
+
+/// Attribute with the tokens left in a bucket.
+const TOKENS: &str = "tokens";
+/// Attribute with the time of the last refill of a bucket, in epoch milliseconds.
+const REFILLED_AT: &str = "refilled_at";
+/// Sort key of the bucket item of every key.
+const BUCKET_SK: &str = "bucket";
+/// Times a check is attempted when concurrent checks of the same key update its bucket first.
+const MAX_ATTEMPTS: usize = 10;
+
+/// Outcome of `RateLimiter::check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// whether the request is allowed, in which case it took a token.
+    pub allowed: bool,
+    /// whole tokens left in the bucket, which is the number of requests allowed right away.
+    pub remaining: u64,
+    /// when the next token is refilled if the request was denied, or when the bucket is full
+    /// again if it was allowed.
+    pub reset_at: DateTime<Utc>,
+}
+
+/// Per-key rate limits enforced with token buckets stored in a table, so that every instance
+/// of a service shares the same quota. Each key gets an item with its tokens and the time of
+/// their last refill, which is updated on the condition that no other check updated it in the
+/// meantime. The item carries a TTL attribute so dynamo deletes it once the bucket would be
+/// full again, provided TTL is enabled on that attribute of the table.
+#[derive(Clone)]
+pub struct RateLimiter {
+    client: Client,
+    table: String,
+    ttl_attribute: String,
+}
+
+/// State of the bucket of a key, along with the raw attribute values it was read from, on
+/// which the next write is conditioned.
+struct Bucket {
+    tokens: f64,
+    refilled_at: i64,
+    raw: (String, String),
+}
+
+impl RateLimiter {
+    /// A rate limiter that stores its buckets in the provided table, with the TTL in the
+    /// attribute `expires_at`, in epoch seconds.
+    pub fn new(client: &Client, table: impl Into<String>) -> Self {
+        Self {
+            client: client.clone(),
+            table: table.into(),
+            ttl_attribute: "expires_at".into(),
+        }
+    }
+
+    /// Returns the rate limiter with a different TTL attribute.
+    pub fn with_ttl_attribute(self, ttl_attribute: impl Into<String>) -> Self {
+        Self {
+            ttl_attribute: ttl_attribute.into(),
+            ..self
+        }
+    }
+
+    /// Takes a token from the bucket of a key and returns whether the request is allowed,
+    /// which is the case if the bucket had a token left. Buckets hold up to `limit` tokens and
+    /// are refilled continuously at `limit` tokens per `window`, so bursts of up to `limit`
+    /// requests are allowed and the sustained rate never exceeds `limit` per `window`. Denied
+    /// requests do not take any token.
+    ///
+    /// # arguments
+    ///
+    /// * `key` - What is limited, like a user id or an api key.
+    /// * `limit` - Capacity of the bucket, and tokens refilled in a window.
+    /// * `window` - Time in which a whole bucket is refilled, at least one second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let limiter = RateLimiter::new(&client, "RateLimits");
+    ///     let decision = limiter.check(&user.id, 100, Duration::from_secs(60)).await?;
+    ///     if !decision.allowed {
+    ///         return Err(TooManyRequests { retry_at: decision.reset_at });
+    ///     }
+    /// }
+    /// ```
+    pub async fn check(
+        &self,
+        key: &str,
+        limit: u64,
+        window: Duration,
+    ) -> Result<RateLimitDecision, DynarustError> {
+        let window = window.as_millis().max(1000) as i64;
+        if limit == 0 {
+            let now = self.client.now().timestamp_millis();
+            return Ok(RateLimitDecision {
+                allowed: false,
+                remaining: 0,
+                reset_at: millis_to_time(now + window),
+            });
+        }
+
+        let capacity = limit as f64;
+        let millis_per_token = window as f64 / capacity;
+        let pk = self.client.scope_pk(format!("ratelimit#{key}"));
+        for _ in 0..MAX_ATTEMPTS {
+            let now = self.client.now().timestamp_millis();
+            let bucket = self.read_bucket(&pk).await?;
+            let tokens = match &bucket {
+                Some(bucket) => {
+                    let elapsed = (now - bucket.refilled_at).max(0) as f64;
+                    (bucket.tokens + elapsed / millis_per_token).min(capacity)
+                }
+                None => capacity,
+            };
+            let refilled_in = |tokens: f64| (tokens * millis_per_token).ceil() as i64;
+
+            if tokens < 1.0 {
+                return Ok(RateLimitDecision {
+                    allowed: false,
+                    remaining: 0,
+                    reset_at: millis_to_time(now + refilled_in(1.0 - tokens)),
+                });
+            }
+            let tokens = tokens - 1.0;
+            let full_at = now + refilled_in(capacity - tokens);
+            match self
+                .write_bucket(&pk, tokens, now, full_at, bucket.map(|b| b.raw))
+                .await
+            {
+                Ok(()) => {
+                    return Ok(RateLimitDecision {
+                        allowed: true,
+                        remaining: tokens.floor() as u64,
+                        reset_at: millis_to_time(full_at),
+                    })
+                }
+                Err(DynarustError::ConditionalCheckFailedError(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(DynarustError::UnexpectedError(format!(
+            "The bucket of {key} kept changing after {MAX_ATTEMPTS} attempts"
+        )))
+    }
+
+    fn bucket_key(&self, pk: &str) -> HashMap<String, AttributeValue> {
+        let (pk_attr, sk_attr) = self.client.key_names(&self.table);
+        HashMap::from([
+            (pk_attr.to_string(), AttributeValue::S(pk.to_string())),
+            (
+                sk_attr.to_string(),
+                AttributeValue::S(BUCKET_SK.to_string()),
+            ),
+        ])
+    }
+
+    async fn read_bucket(&self, pk: &str) -> Result<Option<Bucket>, DynarustError> {
+        let get = self
+            .client
+            .dynamo(&self.table)
+            .get_item()
+            .table_name(&self.table)
+            .set_key(Some(self.bucket_key(pk)))
+            .consistent_read(true)
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send();
+        let result = self
+            .client
+            .stats
+            .measure(Operation::GetItem, async { Ok(get.await?) })
+            .await?;
+        self.client.stats.add_capacity(
+            Operation::GetItem,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+
+        let Some(item) = result.item() else {
+            return Ok(None);
+        };
+        let raw = match (item.get(TOKENS), item.get(REFILLED_AT)) {
+            (Some(AttributeValue::N(tokens)), Some(AttributeValue::N(refilled_at))) => {
+                (tokens.clone(), refilled_at.clone())
+            }
+            _ => (String::new(), String::new()),
+        };
+        match (raw.0.parse(), raw.1.parse()) {
+            (Ok(tokens), Ok(refilled_at)) => Ok(Some(Bucket {
+                tokens,
+                refilled_at,
+                raw,
+            })),
+            _ => Err(DynarustError::AttributeParseError(format!(
+                "The rate limit bucket {pk} has no valid {TOKENS} and {REFILLED_AT}"
+            ))),
+        }
+    }
+
+    /// Writes the new state of a bucket, failing with a
+    /// `DynarustError::ConditionalCheckFailedError` if it is not the `previous` one anymore.
+    async fn write_bucket(
+        &self,
+        pk: &str,
+        tokens: f64,
+        refilled_at: i64,
+        full_at: i64,
+        previous: Option<(String, String)>,
+    ) -> Result<(), DynarustError> {
+        let mut expression =
+            "set #tokens = :tokens, #refilledAt = :refilledAt, #ttl = :expiresAt".to_string();
+        let mut names = HashMap::from([
+            ("#tokens".to_string(), TOKENS.to_string()),
+            ("#refilledAt".to_string(), REFILLED_AT.to_string()),
+            ("#ttl".to_string(), self.ttl_attribute.clone()),
+        ]);
+        let mut values = HashMap::from([
+            (
+                ":tokens".to_string(),
+                AttributeValue::N(format!("{tokens:.6}")),
+            ),
+            (
+                ":refilledAt".to_string(),
+                AttributeValue::N(refilled_at.to_string()),
+            ),
+            (
+                ":expiresAt".to_string(),
+                AttributeValue::N((full_at / 1000 + 1).to_string()),
+            ),
+        ]);
+        self.client
+            .intercept_update(&self.table, &mut expression, &mut names, &mut values)?;
+
+        let condition = match previous {
+            Some((tokens, refilled_at)) => {
+                values.insert(":previousTokens".into(), AttributeValue::N(tokens));
+                values.insert(":previousRefilledAt".into(), AttributeValue::N(refilled_at));
+                "#tokens = :previousTokens and #refilledAt = :previousRefilledAt"
+            }
+            None => "attribute_not_exists(#refilledAt)",
+        };
+        let update = self
+            .client
+            .dynamo(&self.table)
+            .update_item()
+            .table_name(&self.table)
+            .set_key(Some(self.bucket_key(pk)))
+            .update_expression(expression)
+            .condition_expression(condition)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send();
+        let result = self
+            .client
+            .stats
+            .measure(Operation::UpdateItem, async { Ok(update.await?) })
+            .await?;
+        self.client.stats.add_capacity(
+            Operation::UpdateItem,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+        Ok(())
+    }
+}
+
+fn millis_to_time(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, Clock, ManualClock, RateLimiter};
+
+    #[tokio::test]
+    async fn limits_requests_with_token_buckets() {
+        let clock = ManualClock::new(Utc.timestamp_opt(Utc::now().timestamp(), 0).unwrap());
+        let client = Client::local().await.with_clock(clock.clone());
+        client.create_table::<TestResource>(None).await.unwrap();
+        let limiter = RateLimiter::new(&client, TABLE.as_str());
+        let window = Duration::from_secs(3600);
+        let key = &format!(
+            "limits_requests_with_token_buckets#{}",
+            clock.now().timestamp()
+        );
+
+        let mut decisions = vec![];
+        for _ in 0..3 {
+            let decision = limiter.check(key, 2, window).await.unwrap();
+            decisions.push((decision.allowed, decision.remaining));
+        }
+        assert_eq!(decisions, vec![(true, 1), (true, 0), (false, 0)]);
+
+        let denied = limiter.check(key, 2, window).await.unwrap();
+        assert_eq!(
+            denied.reset_at,
+            clock.now() + chrono::Duration::seconds(1800)
+        );
+        clock.advance(chrono::Duration::seconds(1800));
+        let refilled = limiter.check(key, 2, window).await.unwrap();
+        assert_eq!((refilled.allowed, refilled.remaining), (true, 0));
+        assert!(!limiter.check(key, 2, window).await.unwrap().allowed);
+
+        let other = limiter.check(&format!("{key}#other"), 2, window).await;
+        assert!(other.unwrap().allowed);
+        assert!(!limiter.check("any", 0, window).await.unwrap().allowed);
+    }
+}
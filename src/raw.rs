@@ -0,0 +1,105 @@
+use serde_json::Value;
+
+use crate::get::get_item;
+use crate::list::query_items;
+use crate::{Client, DynarustError, ListOptions, Page};
+
+impl Client {
+    /// Retrieves an item as json, without deserializing it into a resource, which is handy for
+    /// admin tools or for debugging. The attributes are converted with the codec of the client,
+    /// and the item is returned as it is stored, key attributes included. If the item does not
+    /// exist returns Option::None.
+    ///
+    /// # arguments
+    ///
+    /// * `table` - Table where the item is stored.
+    /// * `pk_sk` - Pk and sk pair for identifying the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let item = client.get_raw("Users", ("users".into(), "ada".into())).await?;
+    ///     println!("{}", serde_json::to_string_pretty(&item)?);
+    /// }
+    /// ```
+    pub async fn get_raw(
+        &self,
+        table: &str,
+        pk_sk: (String, String),
+    ) -> Result<Option<Value>, DynarustError> {
+        get_item(self, table, pk_sk).await
+    }
+
+    /// Lists the items under the same pk as json, without deserializing them into resources.
+    /// Like with `get_raw`, the items are returned as they are stored, key attributes included.
+    ///
+    /// # arguments
+    ///
+    /// * `table` - Table where the items are stored.
+    /// * `pk` - Primary Key under which the listed items live.
+    /// * `options` - listing options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let page = client
+    ///         .list_raw("Users", "users".into(), &ListOptions::default())
+    ///         .await?;
+    ///     for item in page.items {
+    ///         println!("{item}");
+    ///     }
+    /// }
+    /// ```
+    pub async fn list_raw(
+        &self,
+        table: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<Value>, DynarustError> {
+        query_items(self, table, pk, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::client::{PK, SK};
+    use crate::{Client, ListOptions, Resource};
+
+    #[tokio::test]
+    async fn reads_raw_items() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "reads_raw_items".to_string(),
+            sk: "1".to_string(),
+            int: 3,
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let item = client
+            .get_raw(TABLE.as_str(), resource.pk_sk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item[PK], json!("reads_raw_items"));
+        assert_eq!(item[SK], json!("1"));
+        assert_eq!(item["int"], json!(3));
+        assert!(client
+            .get_raw(TABLE.as_str(), ("reads_raw_items".into(), "2".into()))
+            .await
+            .unwrap()
+            .is_none());
+
+        let page = client
+            .list_raw(TABLE.as_str(), resource.pk, &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(page.items, vec![item]);
+    }
+}
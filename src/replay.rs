@@ -0,0 +1,490 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::model::AttributeValue;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::client::{PK, SK};
+use crate::transaction::describe_item;
+use crate::{
+    Client, ConditionCheckInfo, Cursor, DynamoStore, DynarustError, ItemUpdate, ListOptions, Page,
+    SkCondition, TransactionContext,
+};
+
+/// An operation performed on a store, along with its outcome, as stored in a fixture file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub operation: String,
+    pub request: Value,
+    pub response: Result<Value, RecordedError>,
+}
+
+/// An error returned by a recorded operation. Only failed conditional checks keep their
+/// variant on replay, every other error is replayed as a `DynarustError::DynamoError` with the
+/// same message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedError {
+    ConditionalCheckFailed(Option<Value>),
+    Message(String),
+}
+
+impl From<&DynarustError> for RecordedError {
+    fn from(err: &DynarustError) -> Self {
+        match err {
+            DynarustError::ConditionalCheckFailedError(item) => {
+                RecordedError::ConditionalCheckFailed(item.clone())
+            }
+            err => RecordedError::Message(err.to_string()),
+        }
+    }
+}
+
+impl From<RecordedError> for DynarustError {
+    fn from(err: RecordedError) -> Self {
+        match err {
+            RecordedError::ConditionalCheckFailed(item) => {
+                DynarustError::ConditionalCheckFailedError(item)
+            }
+            RecordedError::Message(message) => DynarustError::DynamoError(message),
+        }
+    }
+}
+
+/// Store that forwards every operation to another store and records it, so the recorded
+/// calls can be saved to a fixture file and served back later by a `ReplayStore`.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let recorder = Arc::new(dynarust::RecordingStore::new(dynarust::Client::local().await));
+///     let store: Arc<dyn dynarust::DynamoStore> = recorder.clone();
+///     store.create(&car).await?;
+///     recorder.save("tests/fixtures/create_car.json")?;
+/// }
+/// ```
+pub struct RecordingStore<S: DynamoStore> {
+    inner: S,
+    calls: Mutex<Vec<RecordedCall>>,
+}
+
+impl<S: DynamoStore> RecordingStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            calls: Mutex::new(vec![]),
+        }
+    }
+
+    /// The calls recorded so far.
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Writes the calls recorded so far to a json fixture file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DynarustError> {
+        let content = serde_json::to_string_pretty(&self.calls())?;
+        fs::write(path, content).map_err(|err| DynarustError::UnexpectedError(err.to_string()))
+    }
+
+    fn record<R>(
+        &self,
+        operation: &str,
+        request: Value,
+        result: Result<R, DynarustError>,
+        encode: impl Fn(&R) -> Result<Value, DynarustError>,
+    ) -> Result<R, DynarustError> {
+        let response = match &result {
+            Ok(r) => Ok(encode(r)?),
+            Err(err) => Err(err.into()),
+        };
+        self.calls.lock().unwrap().push(RecordedCall {
+            operation: operation.to_string(),
+            request,
+            response,
+        });
+        result
+    }
+}
+
+/// Store that serves the calls of a fixture file recorded by a `RecordingStore`, without
+/// connecting to dynamo. Operations must be performed in the same order and with the same
+/// requests as when they were recorded, otherwise they fail with an unexpected error.
+pub struct ReplayStore {
+    calls: Mutex<VecDeque<RecordedCall>>,
+}
+
+impl ReplayStore {
+    pub fn new(calls: Vec<RecordedCall>) -> Self {
+        Self {
+            calls: Mutex::new(calls.into()),
+        }
+    }
+
+    /// Loads the calls of a json fixture file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DynarustError> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| DynarustError::UnexpectedError(err.to_string()))?;
+        Ok(Self::new(serde_json::from_str(&content)?))
+    }
+
+    /// Number of recorded calls that have not been served yet.
+    pub fn remaining(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+
+    fn replay(&self, operation: &str, request: Value) -> Result<Value, DynarustError> {
+        let call = self.calls.lock().unwrap().pop_front().ok_or_else(|| {
+            DynarustError::UnexpectedError(format!("no recorded call left for {operation}"))
+        })?;
+        if call.operation != operation || call.request != request {
+            return Err(DynarustError::UnexpectedError(format!(
+                "expected recorded {} {}, got {operation} {request}",
+                call.operation, call.request
+            )));
+        }
+        call.response.map_err(DynarustError::from)
+    }
+}
+
+fn key_json((pk, sk): &(String, String)) -> Value {
+    json!([pk, sk])
+}
+
+fn condition_json(condition_check: &ConditionCheckInfo) -> Result<Value, DynarustError> {
+    let (expression, names, values) = condition_check.clone().into_parts();
+    let mut rendered_values = Map::new();
+    for (k, v) in values {
+        rendered_values.insert(k, Client::attr2value(&v)?);
+    }
+    Ok(json!({ "expression": expression, "names": names, "values": rendered_values }))
+}
+
+fn cursor_json(cursor: &Option<Cursor>) -> Result<Value, DynarustError> {
+    match cursor {
+        Some(cursor) => Client::item_as_value(&cursor.0),
+        None => Ok(Value::Null),
+    }
+}
+
+fn cursor_from_json(value: &Value) -> Result<Option<Cursor>, DynarustError> {
+    match value.as_object() {
+        Some(object) => {
+            let mut key = HashMap::new();
+            for (k, v) in object {
+                key.insert(k.clone(), Client::value2attr(v)?);
+            }
+            Ok(Some(Cursor(key)))
+        }
+        None => Ok(None),
+    }
+}
+
+fn transaction_json(transaction_context: &TransactionContext) -> Result<Value, DynarustError> {
+    let mut items = vec![];
+    for item in transaction_context.items() {
+        let (operation, table, key) = describe_item(item)?;
+        let attr = |name: &str| match key.as_ref().and_then(|key| key.get(name)) {
+            Some(AttributeValue::S(v)) => v.clone(),
+            _ => String::new(),
+        };
+        items.push(json!([operation, table, attr(PK), attr(SK)]));
+    }
+    Ok(Value::Array(items))
+}
+
+fn query_json(table: &str, pk: &str, options: &ListOptions) -> Result<Value, DynarustError> {
+    let mut request = json!({
+        "table": table,
+        "pk": pk,
+        "from": options.from,
+        "limit": options.limit,
+        "sort_desc": options.sort_desc,
+        "cursor": cursor_json(&options.cursor)?,
+    });
+    if options.sk_condition != SkCondition::Any {
+        request["sk_condition"] = json!(format!("{:?}", options.sk_condition));
+    }
+    Ok(request)
+}
+
+fn page_json(page: &Page<Value>) -> Result<Value, DynarustError> {
+    Ok(json!({
+        "items": page.items,
+        "next": cursor_json(&page.next)?,
+        "scanned_count": page.scanned_count,
+        "count": page.count,
+        "consumed_capacity": page.consumed_capacity,
+    }))
+}
+
+fn page_from_json(value: Value) -> Result<Page<Value>, DynarustError> {
+    let number = |name: &str| value[name].as_i64().unwrap_or_default() as i32;
+    Ok(Page {
+        items: serde_json::from_value(value["items"].clone())?,
+        next: cursor_from_json(&value["next"])?,
+        scanned_count: number("scanned_count"),
+        count: number("count"),
+        consumed_capacity: value["consumed_capacity"].as_f64(),
+    })
+}
+
+fn unit_json(_: &()) -> Result<Value, DynarustError> {
+    Ok(Value::Null)
+}
+
+#[async_trait]
+impl<S: DynamoStore> DynamoStore for RecordingStore<S> {
+    async fn get_item(
+        &self,
+        table: &str,
+        key: (String, String),
+    ) -> Result<Option<Value>, DynarustError> {
+        let request = json!({ "table": table, "key": key_json(&key) });
+        let result = self.inner.get_item(table, key).await;
+        self.record("get_item", request, result, |r| Ok(json!(r)))
+    }
+
+    async fn batch_get_items(
+        &self,
+        table: &str,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Value>, DynarustError> {
+        let request =
+            json!({ "table": table, "keys": keys.iter().map(key_json).collect::<Vec<_>>() });
+        let result = self.inner.batch_get_items(table, keys).await;
+        self.record("batch_get_items", request, result, |r| Ok(json!(r)))
+    }
+
+    async fn put_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        item: Map<String, Value>,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "item": item,
+            "condition": condition_json(&condition_check)?,
+        });
+        let result = self.inner.put_item(table, key, item, condition_check).await;
+        self.record("put_item", request, result, unit_json)
+    }
+
+    async fn update_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        update: ItemUpdate,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "set": update.set,
+            "remove": update.remove,
+            "add": update.add,
+            "delete": update.delete,
+            "set_elements": update.set_elements,
+            "remove_elements": update.remove_elements,
+            "condition": condition_json(&condition_check)?,
+        });
+        let result = self
+            .inner
+            .update_item(table, key, update, condition_check)
+            .await;
+        self.record("update_item", request, result, unit_json)
+    }
+
+    async fn delete_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "condition": condition_json(&condition_check)?,
+        });
+        let result = self.inner.delete_item(table, key, condition_check).await;
+        self.record("delete_item", request, result, unit_json)
+    }
+
+    async fn query_items(
+        &self,
+        table: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<Value>, DynarustError> {
+        let request = query_json(table, &pk, options)?;
+        let result = self.inner.query_items(table, pk, options).await;
+        self.record("query_items", request, result, page_json)
+    }
+
+    async fn execute_transaction(
+        &self,
+        transaction_context: TransactionContext,
+    ) -> Result<(), DynarustError> {
+        let request = transaction_json(&transaction_context)?;
+        let result = self.inner.execute_transaction(transaction_context).await;
+        self.record("execute_transaction", request, result, unit_json)
+    }
+}
+
+#[async_trait]
+impl DynamoStore for ReplayStore {
+    async fn get_item(
+        &self,
+        table: &str,
+        key: (String, String),
+    ) -> Result<Option<Value>, DynarustError> {
+        let request = json!({ "table": table, "key": key_json(&key) });
+        let response = self.replay("get_item", request)?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    async fn batch_get_items(
+        &self,
+        table: &str,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Value>, DynarustError> {
+        let request =
+            json!({ "table": table, "keys": keys.iter().map(key_json).collect::<Vec<_>>() });
+        let response = self.replay("batch_get_items", request)?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    async fn put_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        item: Map<String, Value>,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "item": item,
+            "condition": condition_json(&condition_check)?,
+        });
+        self.replay("put_item", request)?;
+        Ok(())
+    }
+
+    async fn update_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        update: ItemUpdate,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "set": update.set,
+            "remove": update.remove,
+            "add": update.add,
+            "delete": update.delete,
+            "set_elements": update.set_elements,
+            "remove_elements": update.remove_elements,
+            "condition": condition_json(&condition_check)?,
+        });
+        self.replay("update_item", request)?;
+        Ok(())
+    }
+
+    async fn delete_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let request = json!({
+            "table": table,
+            "key": key_json(&key),
+            "condition": condition_json(&condition_check)?,
+        });
+        self.replay("delete_item", request)?;
+        Ok(())
+    }
+
+    async fn query_items(
+        &self,
+        table: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<Value>, DynarustError> {
+        let request = query_json(table, &pk, options)?;
+        page_from_json(self.replay("query_items", request)?)
+    }
+
+    async fn execute_transaction(
+        &self,
+        transaction_context: TransactionContext,
+    ) -> Result<(), DynarustError> {
+        let request = transaction_json(&transaction_context)?;
+        self.replay("execute_transaction", request)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::store::tests::FakeStore;
+    use crate::{DynamoStore, DynarustError, ListOptions, RecordingStore, ReplayStore, Resource};
+
+    async fn scenario(store: &dyn DynamoStore) -> Result<Vec<TestResource>, DynarustError> {
+        let resource = TestResource {
+            pk: "replays_recorded_calls".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        store.create(&resource).await?;
+        let err = store.create(&resource).await.unwrap_err();
+        assert_eq!(err.to_string(), "The conditional request failed");
+        store.update(&resource, json!({ "int": 4 })).await?;
+        let page = store
+            .list::<TestResource>(resource.pk.clone(), &ListOptions::default())
+            .await?;
+        Ok(page.items)
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_calls() {
+        let recorder = Arc::new(RecordingStore::new(FakeStore::default()));
+        let recorded = scenario(recorder.as_ref()).await.unwrap();
+
+        let path = std::env::temp_dir().join("dynarust_replays_recorded_calls.json");
+        recorder.save(&path).unwrap();
+        let replay = ReplayStore::load(&path).unwrap();
+        assert_eq!(replay.remaining(), 4);
+
+        let replayed = scenario(&replay).await.unwrap();
+        assert_eq!(replayed, recorded);
+        assert_eq!(replayed[0].int, 4);
+        assert_eq!(replay.remaining(), 0);
+
+        let store: &dyn DynamoStore = &replay;
+        let err = store
+            .get::<TestResource>(recorded[0].pk_sk())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected error: no recorded call left for get_item"
+        );
+    }
+}
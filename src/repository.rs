@@ -0,0 +1,175 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    Client, ConditionCheckInfo, Cursor, DynamoStore, DynarustError, ListOptions, Page, Resource,
+};
+
+/// Narrow interface for operating on a single resource type. Application services can depend
+/// on a `Repository<T>` for each of their entities instead of on the whole client.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let cars = client.repository::<Car>();
+///     cars.create(&car).await?;
+///     let page = cars.page("ford".into(), None).await?;
+///     let next = cars.page("ford".into(), page.next).await?;
+/// }
+/// ```
+pub struct Repository<T> {
+    store: Arc<dyn DynamoStore>,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Repository<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            resource: PhantomData,
+        }
+    }
+}
+
+impl<T: Resource + Serialize + DeserializeOwned> Repository<T> {
+    /// Creates a repository backed by the provided store.
+    pub fn new(store: Arc<dyn DynamoStore>) -> Self {
+        Self {
+            store,
+            resource: PhantomData,
+        }
+    }
+
+    /// Retrieves a resource. If the resource does not exist returns Option::None.
+    pub async fn get(&self, pk_sk: (String, String)) -> Result<Option<T>, DynarustError> {
+        self.store.get(pk_sk).await
+    }
+
+    /// Creates a new resource, if the resource already exists, this operation fails.
+    pub async fn create<'a>(&self, resource: &'a T) -> Result<&'a T, DynarustError> {
+        self.store.create(resource).await
+    }
+
+    /// Creates a new resource with additional condition checks.
+    pub async fn create_with_checks<'a>(
+        &self,
+        resource: &'a T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<&'a T, DynarustError> {
+        self.store
+            .create_with_checks(resource, condition_checks)
+            .await
+    }
+
+    /// Creates a new resource, overwriting a previously existing resource if necessary.
+    pub async fn force_create<'a>(&self, resource: &'a T) -> Result<&'a T, DynarustError> {
+        self.store.force_create(resource).await
+    }
+
+    /// Creates a new resource, overwriting a previous one only if the condition checks pass.
+    pub async fn force_create_with_checks<'a>(
+        &self,
+        resource: &'a T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<&'a T, DynarustError> {
+        self.store
+            .force_create_with_checks(resource, condition_checks)
+            .await
+    }
+
+    /// Updates a resource. It returns an error if the resource does not exist.
+    pub async fn update(&self, resource: &T, request: Value) -> Result<T, DynarustError> {
+        self.store.update(resource, request).await
+    }
+
+    /// Updates a resource with additional condition checks.
+    pub async fn update_with_checks(
+        &self,
+        resource: &T,
+        request: Value,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<T, DynarustError> {
+        self.store
+            .update_with_checks(resource, request, condition_checks)
+            .await
+    }
+
+    /// Deletes a resource.
+    pub async fn delete(&self, pk_sk: (String, String)) -> Result<(), DynarustError> {
+        self.store.delete::<T>(pk_sk).await
+    }
+
+    /// Deletes a resource with additional condition checks.
+    pub async fn delete_with_checks(
+        &self,
+        pk_sk: (String, String),
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<(), DynarustError> {
+        self.store
+            .delete_with_checks::<T>(pk_sk, condition_checks)
+            .await
+    }
+
+    /// List the resources under the same pk, one page at a time.
+    pub async fn list(&self, pk: String, options: &ListOptions) -> Result<Page<T>, DynarustError> {
+        self.store.list(pk, options).await
+    }
+
+    /// Retrieves the page of resources under the same pk that starts at the provided cursor,
+    /// using the default list options. Pass None for retrieving the first page.
+    pub async fn page(&self, pk: String, cursor: Option<Cursor>) -> Result<Page<T>, DynarustError> {
+        let options = ListOptions {
+            cursor,
+            ..Default::default()
+        };
+        self.list(pk, &options).await
+    }
+}
+
+impl Client {
+    /// Returns a repository for operating on resources of type T through this client.
+    pub fn repository<T: Resource + Serialize + DeserializeOwned>(&self) -> Repository<T> {
+        Repository::new(Arc::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::store::tests::FakeStore;
+    use crate::{Repository, Resource};
+
+    #[tokio::test]
+    async fn operates_on_a_single_resource_type() {
+        let repo = Repository::<TestResource>::new(Arc::new(FakeStore::default()));
+
+        let resource = TestResource {
+            pk: "operates_on_a_single_resource_type".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        repo.create(&resource).await.unwrap();
+        let updated = repo.update(&resource, json!({ "int": 2 })).await.unwrap();
+
+        assert_eq!(
+            repo.get(resource.pk_sk()).await.unwrap(),
+            Some(updated.clone())
+        );
+
+        let page = repo.page(resource.pk.clone(), None).await.unwrap();
+        assert_eq!(page.items, vec![updated]);
+        assert!(!page.has_next());
+
+        repo.delete(resource.pk_sk()).await.unwrap();
+        assert_eq!(repo.get(resource.pk_sk()).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::model::TransactWriteItem;
+pub use aws_sdk_dynamodb::Region;
+
+use crate::transaction::describe_item;
+use crate::{Client, DynarustError};
+
+enum Route {
+    Region(Region),
+    Endpoint(String),
+}
+
+/// Builder for a client that sends the requests of some tables to a different region or
+/// endpoint than the rest, so services spanning a regional split can use a single client.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let client = dynarust::ClientBuilder::from_env()
+///         .await
+///         .route_table("AuditLog", Region::new("eu-west-1"))
+///         .build();
+/// }
+/// ```
+pub struct ClientBuilder {
+    config: SdkConfig,
+    endpoint_url: Option<String>,
+    routes: HashMap<String, Route>,
+}
+
+impl ClientBuilder {
+    /// Starts building a client from the provided AWS config.
+    pub fn new(config: SdkConfig) -> Self {
+        Self {
+            config,
+            endpoint_url: None,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Starts building a client from AWS config loaded from the environment, in the same way
+    /// as `Client::aws` does.
+    pub async fn from_env() -> Self {
+        Self::new(aws_config::from_env().load().await)
+    }
+
+    /// Sends the requests for all the tables that are not routed to the provided endpoint.
+    pub fn endpoint_url(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    /// Sends the requests for the provided table to a different region.
+    pub fn route_table(mut self, table: impl Into<String>, region: Region) -> Self {
+        self.routes.insert(table.into(), Route::Region(region));
+        self
+    }
+
+    /// Sends the requests for the provided table to a different endpoint.
+    pub fn route_table_to_endpoint(
+        mut self,
+        table: impl Into<String>,
+        endpoint_url: impl Into<String>,
+    ) -> Self {
+        self.routes
+            .insert(table.into(), Route::Endpoint(endpoint_url.into()));
+        self
+    }
+
+    fn dynamo(&self, route: Option<&Route>) -> aws_sdk_dynamodb::Client {
+        let mut builder = aws_sdk_dynamodb::config::Builder::from(&self.config);
+        if let Some(endpoint_url) = &self.endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        match route {
+            Some(Route::Region(region)) => builder = builder.region(region.clone()),
+            Some(Route::Endpoint(endpoint_url)) => builder = builder.endpoint_url(endpoint_url),
+            None => {}
+        }
+        aws_sdk_dynamodb::Client::from_conf(builder.build())
+    }
+
+    pub fn build(self) -> Client {
+        let routes = self
+            .routes
+            .iter()
+            .map(|(table, route)| (table.clone(), self.dynamo(Some(route))))
+            .collect();
+        Client {
+            client: self.dynamo(None),
+            routes: Arc::new(routes),
+            tenant: None,
+            audit: None,
+            actor: None,
+            stats: Default::default(),
+            collection_metrics: None,
+            external_tables: Default::default(),
+            read_consistency: Default::default(),
+            write_interceptors: vec![],
+            sequences: None,
+            codec: None,
+            clock: None,
+            known_tables: Default::default(),
+        }
+    }
+}
+
+impl Client {
+    /// The dynamo client to which the requests for the provided table are sent.
+    pub(crate) fn dynamo(&self, table: &str) -> &aws_sdk_dynamodb::Client {
+        self.routes.get(table).unwrap_or(&self.client)
+    }
+
+    /// The dynamo client for executing a transaction, failing if its items belong to tables
+    /// with different routes.
+    pub(crate) fn dynamo_for_items(
+        &self,
+        items: &[TransactWriteItem],
+    ) -> Result<&aws_sdk_dynamodb::Client, DynarustError> {
+        let mut tables = vec![];
+        for item in items {
+            tables.push(describe_item(item)?.1);
+        }
+        let Some(first) = tables.first() else {
+            return Ok(&self.client);
+        };
+        let route = |table: &str| self.routes.contains_key(table).then_some(table.to_string());
+        if tables.iter().any(|table| route(table) != route(first)) {
+            return Err(DynarustError::InvalidRequestError(
+                "Transactions cannot span tables with different routes".into(),
+            ));
+        }
+        Ok(self.dynamo(first))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_config::SdkConfig;
+    use aws_sdk_dynamodb::Region;
+
+    use crate::ClientBuilder;
+
+    #[test]
+    fn routes_tables_to_regions() {
+        let config = SdkConfig::builder()
+            .region(Region::new("us-east-1"))
+            .build();
+        let client = ClientBuilder::new(config)
+            .route_table("AuditLog", Region::new("eu-west-1"))
+            .build();
+
+        let region = |table: &str| client.dynamo(table).conf().region().cloned();
+        assert_eq!(region("AuditLog"), Some(Region::new("eu-west-1")));
+        assert_eq!(region("Cars"), Some(Region::new("us-east-1")));
+    }
+}
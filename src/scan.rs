@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity};
+use serde::de::DeserializeOwned;
+
+use crate::{Client, ConditionCheckInfo, Cursor, DynarustError, Page, ReadConsistency, Resource};
+
+/// Options of a `Client::scan` call.
+#[derive(Clone)]
+pub struct ScanOptions {
+    /// condition on the attributes of the scanned items, built with the same functions as
+    /// condition checks. An empty filter matches every item.
+    pub filter: ConditionCheckInfo,
+    /// maximum number of items evaluated in a single page, before applying the filter,
+    /// default is 25.
+    pub limit: i32,
+    /// Cursor returned by a previous page for continuing the scan where it stopped.
+    pub cursor: Option<Cursor>,
+    /// segment scanned by this call in a parallel scan, from 0 to `total_segments` - 1.
+    pub segment: i32,
+    /// number of segments in which the table is split for a parallel scan, default is 1,
+    /// which scans the whole table.
+    pub total_segments: i32,
+    /// consistency of the scan, if not provided the one of the client is used.
+    pub consistency: Option<ReadConsistency>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            filter: Default::default(),
+            limit: 25,
+            cursor: None,
+            segment: 0,
+            total_segments: 1,
+            consistency: None,
+        }
+    }
+}
+
+impl Client {
+    /// Scans the table of resource T, one page at a time. Every item of the table is read and
+    /// billed, so this is meant for processing whole tables, like migrations or exports. Large
+    /// tables can be processed concurrently with a parallel scan, where each worker scans one
+    /// of the `total_segments` segments of the table and follows its own cursors.
+    ///
+    /// Dynamo applies the filter after reading the items, so pages can have fewer items than
+    /// the limit, or none, and still have a next one. Items that cannot be deserialized as T,
+    /// like the ones of other resources sharing the table, are skipped.
+    ///
+    /// # arguments
+    ///
+    /// * `options` - filter, pagination and segment of the scan.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let segments = 4;
+    ///     let workers = (0..segments).map(|segment| async move {
+    ///         let mut options = ScanOptions {
+    ///             filter: dynarust::condition_check_string("status", DynamoOperator::Eq, "pending"),
+    ///             segment,
+    ///             total_segments: segments,
+    ///             ..Default::default()
+    ///         };
+    ///         loop {
+    ///             let page = client.scan::<Order>(&options).await?;
+    ///             process(page.items).await?;
+    ///             match page.next {
+    ///                 Some(cursor) => options.cursor = Some(cursor),
+    ///                 None => return Ok(()),
+    ///             }
+    ///         }
+    ///     });
+    ///     futures::future::try_join_all(workers).await?;
+    /// }
+    /// ```
+    pub async fn scan<T: Resource + DeserializeOwned>(
+        &self,
+        options: &ScanOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        if options.total_segments < 1 || !(0..options.total_segments).contains(&options.segment) {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "Segment {} is not one of the {} segments of the scan",
+                options.segment, options.total_segments
+            )));
+        }
+        let table = T::table();
+        let consistency = options.consistency.unwrap_or(self.read_consistency);
+        let mut builder = self
+            .dynamo(&table)
+            .scan()
+            .table_name(&table)
+            .limit(options.limit)
+            .consistent_read(consistency == ReadConsistency::Strong)
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .set_exclusive_start_key(options.cursor.clone().map(|cursor| cursor.0));
+        if options.total_segments > 1 {
+            builder = builder
+                .segment(options.segment)
+                .total_segments(options.total_segments);
+        }
+        let (expression, names, values) = options.filter.clone().recoded(self)?.into_parts();
+        if !expression.is_empty() {
+            builder = builder
+                .filter_expression(expression)
+                .set_expression_attribute_names(Some(names))
+                .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()));
+        }
+        let result = builder.send().await?;
+
+        let mut items = vec![];
+        for item in result.items().unwrap_or_default() {
+            if !self.owns_item(item) {
+                continue;
+            }
+            let value = self.decode_item(item).map_err(|err| err.in_table(&table))?;
+            if let Ok(resource) = Client::value_as_resource(value) {
+                items.push(resource);
+            }
+        }
+        Ok(Page {
+            items,
+            next: result.last_evaluated_key().cloned().map(Cursor::from),
+            scanned_count: result.scanned_count(),
+            count: result.count(),
+            consumed_capacity: result.consumed_capacity().and_then(|c| c.capacity_units()),
+        })
+    }
+
+    /// Reads a single page of a segment of a parallel scan, starting after the provided key.
+    /// Returns the items of the page and the key where the next page starts, if any.
+    #[allow(clippy::type_complexity)]
+    pub(crate) async fn scan_segment_page(
+        &self,
+        table: &str,
+        segment: i32,
+        total_segments: i32,
+        limit: Option<usize>,
+        exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<
+        (
+            Vec<HashMap<String, AttributeValue>>,
+            Option<HashMap<String, AttributeValue>>,
+        ),
+        DynarustError,
+    > {
+        let mut builder = self
+            .dynamo(table)
+            .scan()
+            .table_name(table)
+            .set_exclusive_start_key(exclusive_start_key);
+
+        if total_segments > 1 {
+            builder = builder.segment(segment).total_segments(total_segments);
+        }
+        if let Some(limit) = limit {
+            builder = builder.limit(limit.min(i32::MAX as usize) as i32);
+        }
+
+        let result = builder.send().await?;
+        Ok((
+            result
+                .items()
+                .map(|items| items.to_vec())
+                .unwrap_or_default(),
+            result.last_evaluated_key().cloned(),
+        ))
+    }
+
+    /// Scans a single segment of a parallel scan page by page, invoking `on_page` with the
+    /// items of every page. Stops when the segment is exhausted or when `limit` items were read.
+    /// Returns the number of items read.
+    pub(crate) async fn scan_segment<F>(
+        &self,
+        table: &str,
+        segment: i32,
+        total_segments: i32,
+        limit: Option<usize>,
+        mut on_page: F,
+    ) -> Result<usize, DynarustError>
+    where
+        F: FnMut(&[HashMap<String, AttributeValue>]) -> Result<(), DynarustError>,
+    {
+        let mut read = 0;
+        let mut exclusive_start_key = None;
+        loop {
+            let page_limit = limit.map(|limit| limit - read);
+            let (items, last_evaluated_key) = self
+                .scan_segment_page(
+                    table,
+                    segment,
+                    total_segments,
+                    page_limit,
+                    exclusive_start_key,
+                )
+                .await?;
+
+            read += items.len();
+            on_page(&items)?;
+
+            exclusive_start_key = last_evaluated_key;
+            if exclusive_start_key.is_none() || limit.is_some_and(|limit| read >= limit) {
+                return Ok(read);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::TestResource;
+    use crate::{condition_check_number, Client, DynamoOperator, DynarustError, ScanOptions};
+
+    #[tokio::test]
+    async fn scans_segments_with_filter() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        for i in 0..6 {
+            let resource = TestResource {
+                pk: "scans_segments_with_filter".to_string(),
+                sk: i.to_string(),
+                int: 8_000_000 + i % 2,
+                ..Default::default()
+            };
+            client.force_create(&resource).await.unwrap();
+        }
+
+        let mut sks = vec![];
+        for segment in 0..3 {
+            let mut options = ScanOptions {
+                filter: condition_check_number("int", DynamoOperator::Eq, 8_000_001),
+                limit: 10,
+                segment,
+                total_segments: 3,
+                ..Default::default()
+            };
+            loop {
+                let page = client.scan::<TestResource>(&options).await.unwrap();
+                sks.extend(page.items.into_iter().map(|r| r.sk));
+                match page.next {
+                    Some(cursor) => options.cursor = Some(cursor),
+                    None => break,
+                }
+            }
+        }
+        sks.sort();
+        assert_eq!(sks, vec!["1", "3", "5"]);
+
+        let out_of_range = ScanOptions {
+            segment: 3,
+            total_segments: 3,
+            ..Default::default()
+        };
+        let err = client
+            .scan::<TestResource>(&out_of_range)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use aws_sdk_dynamodb::model::{
+    AttributeDefinition, KeySchemaElement, KeyType, ScalarAttributeType, TableDescription,
+    TimeToLiveStatus,
+};
+
+use crate::client::{GSI1_INDEX, GSI1_PK, GSI1_SK, INVERTED_INDEX, PK, SK};
+use crate::{Client, DynarustError, Resource};
+
+/// A difference between the live table and what a resource expects from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// part of the table that differs, like `key HASH` or `index Gsi1`.
+    pub element: String,
+    /// what the resource expects.
+    pub expected: String,
+    /// what the live table has, `none` if it is missing.
+    pub actual: String,
+}
+
+impl Display for SchemaMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}",
+            self.element, self.expected, self.actual
+        )
+    }
+}
+
+/// Outcome of `Client::verify_schema`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaReport {
+    pub table: String,
+    /// every difference found, empty if the table matches the resource.
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+impl SchemaReport {
+    /// whether the table matches the resource.
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Display for SchemaReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "table {} matches its resource", self.table);
+        }
+        write!(
+            f,
+            "table {} has {} mismatches:",
+            self.table,
+            self.mismatches.len()
+        )?;
+        for mismatch in self.mismatches.iter() {
+            write!(f, "\n  - {mismatch}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Describes the live table of resource T and compares it with what the resource expects,
+    /// so drift between the code and the infrastructure is caught at startup or deploy time
+    /// instead of on the first failing request. It checks that the key attributes have the
+    /// expected names and are strings, that the indexes created by `create_table` exist with
+    /// the expected keys, which is skipped for external tables, and, if the resource declares
+    /// a `Resource::ttl_attribute`, that TTL is enabled on it. Differences are returned in the
+    /// report, errors are only returned if the table cannot be described, like when it does
+    /// not exist.
+    ///
+    /// The table is described once per client and its clones, and again after `update_table`,
+    /// so checking it on every startup of a worker or before operations is cheap. Changes made
+    /// to the table by other means are not noticed until a new client is built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let report = client.verify_schema::<Order>().await?;
+    ///     if !report.is_ok() {
+    ///         panic!("{report}");
+    ///     }
+    /// }
+    /// ```
+    pub async fn verify_schema<T: Resource>(&self) -> Result<SchemaReport, DynarustError> {
+        let table = T::table();
+        let description = self.describe_table(&table).await?;
+        let types = attribute_types(description.attribute_definitions().unwrap_or_default());
+
+        let mut mismatches = vec![];
+        let key_schema = description.key_schema().unwrap_or_default();
+        check_key_schema(
+            "key",
+            key_schema,
+            self.key_names(&table),
+            &types,
+            &mut mismatches,
+        );
+
+        if !self.external_tables.contains_key(&table) {
+            let indexes = description.global_secondary_indexes().unwrap_or_default();
+            let expected = [(GSI1_INDEX, (GSI1_PK, GSI1_SK)), (INVERTED_INDEX, (SK, PK))];
+            let declared = |name: &str| T::secondary_indexes().iter().any(|i| i.name == name);
+            for (name, keys) in expected.into_iter().filter(|(name, _)| declared(name)) {
+                let element = format!("index {name}");
+                match indexes
+                    .iter()
+                    .find(|index| index.index_name() == Some(name))
+                {
+                    Some(index) => check_key_schema(
+                        &element,
+                        index.key_schema().unwrap_or_default(),
+                        keys,
+                        &types,
+                        &mut mismatches,
+                    ),
+                    None => mismatches.push(SchemaMismatch {
+                        element,
+                        expected: "present".into(),
+                        actual: "none".into(),
+                    }),
+                }
+            }
+        }
+
+        if let Some(ttl_attribute) = T::ttl_attribute() {
+            let output = self
+                .dynamo(&table)
+                .describe_time_to_live()
+                .table_name(&table)
+                .send()
+                .await?;
+            let enabled = output
+                .time_to_live_description()
+                .filter(|ttl| {
+                    matches!(
+                        ttl.time_to_live_status(),
+                        Some(TimeToLiveStatus::Enabled | TimeToLiveStatus::Enabling)
+                    )
+                })
+                .and_then(|ttl| ttl.attribute_name());
+            if enabled != Some(ttl_attribute) {
+                mismatches.push(SchemaMismatch {
+                    element: "ttl".into(),
+                    expected: ttl_attribute.into(),
+                    actual: enabled.unwrap_or("disabled").into(),
+                });
+            }
+        }
+
+        Ok(SchemaReport { table, mismatches })
+    }
+}
+
+impl Client {
+    /// The description of a table, read from dynamo only if the client does not know it yet.
+    async fn describe_table(&self, table: &str) -> Result<TableDescription, DynarustError> {
+        if let Some(description) = self.known_tables.description(table) {
+            return Ok(description);
+        }
+        let output = self
+            .dynamo(table)
+            .describe_table()
+            .table_name(table)
+            .send()
+            .await?;
+        let description = output.table().cloned().ok_or_else(|| {
+            DynarustError::UnexpectedError(format!("Table {table} was not described"))
+        })?;
+        self.known_tables
+            .insert_description(table, description.clone());
+        Ok(description)
+    }
+}
+
+/// Types of the attributes defined in a table, by name.
+fn attribute_types(definitions: &[AttributeDefinition]) -> HashMap<&str, &ScalarAttributeType> {
+    definitions
+        .iter()
+        .filter_map(|definition| {
+            let name = definition.attribute_name()?;
+            Some((name, definition.attribute_type()?))
+        })
+        .collect()
+}
+
+/// Compares a key schema of the table or of an index with the expected hash and range string
+/// attributes.
+fn check_key_schema(
+    element: &str,
+    key_schema: &[KeySchemaElement],
+    (hash, range): (&str, &str),
+    types: &HashMap<&str, &ScalarAttributeType>,
+    mismatches: &mut Vec<SchemaMismatch>,
+) {
+    for (key_type, expected) in [(KeyType::Hash, hash), (KeyType::Range, range)] {
+        let actual = key_schema
+            .iter()
+            .find(|key| key.key_type() == Some(&key_type))
+            .and_then(|key| key.attribute_name());
+        if actual != Some(expected) {
+            mismatches.push(SchemaMismatch {
+                element: format!("{element} {}", key_type.as_str()),
+                expected: expected.into(),
+                actual: actual.unwrap_or("none").into(),
+            });
+            continue;
+        }
+        let attribute_type = types.get(expected).map(|t| t.as_str());
+        if attribute_type != Some(ScalarAttributeType::S.as_str()) {
+            mismatches.push(SchemaMismatch {
+                element: format!("{element} {expected} type"),
+                expected: ScalarAttributeType::S.as_str().into(),
+                actual: attribute_type.unwrap_or("none").into(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::model::{KeySchemaElement, KeyType, ScalarAttributeType};
+    use serde::{Deserialize, Serialize};
+
+    use super::check_key_schema;
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, Resource, SchemaMismatch};
+
+    #[test]
+    fn reports_key_mismatches() {
+        let key = |name: &str, key_type| {
+            KeySchemaElement::builder()
+                .attribute_name(name)
+                .key_type(key_type)
+                .build()
+        };
+        let key_schema = [
+            key("pk", KeyType::Hash),
+            key("SecondaryKey", KeyType::Range),
+        ];
+        let types = HashMap::from([("SecondaryKey", &ScalarAttributeType::N)]);
+
+        let mut mismatches = vec![];
+        check_key_schema(
+            "key",
+            &key_schema,
+            ("PrimaryKey", "SecondaryKey"),
+            &types,
+            &mut mismatches,
+        );
+        let mismatch = |element: &str, expected: &str, actual: &str| SchemaMismatch {
+            element: element.into(),
+            expected: expected.into(),
+            actual: actual.into(),
+        };
+        assert_eq!(
+            mismatches,
+            vec![
+                mismatch("key HASH", "PrimaryKey", "pk"),
+                mismatch("key SecondaryKey type", "S", "N"),
+            ]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "key HASH: expected PrimaryKey, found pk"
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Session {
+        id: String,
+        expires_at: i64,
+    }
+
+    impl Resource for Session {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("sessions".into(), self.id.clone())
+        }
+
+        crate::resource_ttl!(expires_at);
+    }
+
+    #[tokio::test]
+    async fn verifies_live_schema() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let report = client.verify_schema::<TestResource>().await.unwrap();
+        assert!(report.is_ok(), "{report}");
+
+        let report = client.verify_schema::<Session>().await.unwrap();
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].element, "ttl");
+        assert_eq!(report.mismatches[0].actual, "disabled");
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity, ReturnValue};
+
+use crate::stats::Operation;
+use crate::{Client, DynarustError};
+
+/// Options for generating sequence numbers with `next_sequence`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceOptions {
+    /// PrimaryKey of the partition that holds one item per sequence, default is `sequences`.
+    pub pk: String,
+    /// amount of numbers reserved with each write, default is 1. Reserving more than one cuts
+    /// round trips, but the numbers of a block are handed out only by the client that reserved
+    /// it, so numbers from different clients interleave, and the unused numbers of a block are
+    /// lost when the client is dropped.
+    pub block_size: u64,
+}
+
+impl Default for SequenceOptions {
+    fn default() -> Self {
+        Self {
+            pk: "sequences".into(),
+            block_size: 1,
+        }
+    }
+}
+
+/// Table where the sequences live, along with the blocks of numbers reserved by the client.
+pub(crate) struct Sequences {
+    table: String,
+    options: SequenceOptions,
+    reserved: Mutex<HashMap<(String, String), Range<u64>>>,
+}
+
+impl Client {
+    /// Returns a client that generates sequence numbers with `next_sequence`, storing the
+    /// sequences in the provided table.
+    ///
+    /// # arguments
+    ///
+    /// * `table` - Table where the sequences are stored, with the same keys as the rest.
+    /// * `options` - sequence options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let client = client.with_sequences(
+    ///         "Billing",
+    ///         SequenceOptions {
+    ///             block_size: 50,
+    ///             ..Default::default()
+    ///         },
+    ///     );
+    /// }
+    /// ```
+    pub fn with_sequences(&self, table: impl Into<String>, options: SequenceOptions) -> Client {
+        Client {
+            sequences: Some(Arc::new(Sequences {
+                table: table.into(),
+                options,
+                reserved: Default::default(),
+            })),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the next number of the sequence with the provided name, starting at 1. Numbers
+    /// are never repeated, and with the default block size of 1 they are handed out in
+    /// increasing order and without gaps, which makes them suitable for human-friendly
+    /// identifiers like invoice numbers. The client must have been configured with
+    /// `with_sequences`.
+    ///
+    /// # arguments
+    ///
+    /// * `name` - Name of the sequence, which is created the first time it is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let number = client.next_sequence("invoices").await?;
+    ///     let invoice = Invoice { number: format!("INV-{number:06}"), ..invoice };
+    /// }
+    /// ```
+    pub async fn next_sequence(&self, name: &str) -> Result<u64, DynarustError> {
+        let sequences = self.sequences.as_ref().ok_or_else(|| {
+            DynarustError::InvalidRequestError(
+                "Sequences are not configured, see Client::with_sequences".into(),
+            )
+        })?;
+        let key = (
+            self.scope_pk(sequences.options.pk.clone()),
+            name.to_string(),
+        );
+        if let Some(next) = sequences.take_reserved(&key) {
+            return Ok(next);
+        }
+
+        let block_size = sequences.options.block_size.max(1);
+        let last = self
+            .reserve_sequence_block(&sequences.table, key.clone(), block_size)
+            .await?;
+        let mut block = (last + 1).saturating_sub(block_size)..last + 1;
+        let next = block.next().unwrap_or(last);
+        if !block.is_empty() {
+            sequences.store_reserved(key, block);
+        }
+        Ok(next)
+    }
+
+    /// Atomically adds the block size to the sequence, returning the last number of the
+    /// reserved block.
+    pub(crate) async fn reserve_sequence_block(
+        &self,
+        table: &str,
+        (pk, sk): (String, String),
+        block_size: u64,
+    ) -> Result<u64, DynarustError> {
+        let mut expression = "add #sequence :block".to_string();
+        let mut names = HashMap::from([("#sequence".to_string(), "value".to_string())]);
+        let mut values = HashMap::from([(
+            ":block".to_string(),
+            AttributeValue::N(block_size.to_string()),
+        )]);
+        self.intercept_update(table, &mut expression, &mut names, &mut values)?;
+
+        let (pk_attr, sk_attr) = self.key_names(table);
+        let update = self
+            .dynamo(table)
+            .update_item()
+            .table_name(table)
+            .key(pk_attr, AttributeValue::S(pk))
+            .key(sk_attr, AttributeValue::S(sk))
+            .update_expression(expression)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .return_values(ReturnValue::UpdatedNew)
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send();
+        let result = self
+            .stats
+            .measure(Operation::UpdateItem, async { Ok(update.await?) })
+            .await?;
+        self.stats.add_capacity(
+            Operation::UpdateItem,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+        match result
+            .attributes()
+            .and_then(|attributes| attributes.get("value"))
+        {
+            Some(AttributeValue::N(n)) => n.parse().map_err(|_| {
+                DynarustError::AttributeParseError(format!("sequence is not an integer: {n}"))
+            }),
+            _ => Err(DynarustError::UnexpectedError(
+                "The new value of the sequence was not returned".into(),
+            )),
+        }
+    }
+}
+
+impl Sequences {
+    fn take_reserved(&self, key: &(String, String)) -> Option<u64> {
+        let mut reserved = self.reserved.lock().ok()?;
+        let block = reserved.get_mut(key)?;
+        let next = block.next();
+        if block.is_empty() {
+            reserved.remove(key);
+        }
+        next
+    }
+
+    fn store_reserved(&self, key: (String, String), block: Range<u64>) {
+        if let Ok(mut reserved) = self.reserved.lock() {
+            reserved.insert(key, block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, SequenceOptions};
+
+    #[tokio::test]
+    async fn fails_without_sequences() {
+        let client = Client::local().await;
+        assert!(client.next_sequence("invoices").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn generates_sequences_in_blocks() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let options = SequenceOptions {
+            pk: "generates_sequences_in_blocks".into(),
+            block_size: 3,
+        };
+        let first = client.with_sequences(TABLE.as_str(), options.clone());
+        let second = client.with_sequences(TABLE.as_str(), options);
+
+        let mut numbers = vec![];
+        for _ in 0..4 {
+            numbers.push(first.next_sequence("invoices").await.unwrap());
+        }
+        numbers.push(second.next_sequence("invoices").await.unwrap());
+        numbers.push(first.next_sequence("invoices").await.unwrap());
+        assert_eq!(numbers, vec![1, 2, 3, 4, 7, 5]);
+        assert_eq!(first.next_sequence("orders").await.unwrap(), 1);
+    }
+}
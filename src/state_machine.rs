@@ -0,0 +1,205 @@
+use aws_sdk_dynamodb::model::ReturnValue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::client::{GSI1_PK, GSI1_SK};
+use crate::condition_check::condition_check_exists;
+use crate::get::get_item;
+use crate::stats::Operation;
+use crate::update::update_item;
+use crate::{
+    condition_check, AuditOperation, Client, Condition, DynarustError, ItemUpdate, KeyOf, Resource,
+};
+
+/// A resource whose lifecycle is a set of states, like the status of an order, that moves
+/// between them with `Client::transition`.
+///
+/// # Examples
+///
+/// ```
+/// impl StateMachine for Order {
+///     fn state_field() -> &'static str { "status" }
+/// }
+/// ```
+pub trait StateMachine: Resource {
+    /// Field with the current state, which is stored as a string, default is `status`.
+    fn state_field() -> &'static str {
+        "status"
+    }
+}
+
+impl Client {
+    /// Moves a resource to a new state, in a single conditional update that only succeeds if
+    /// its current state is one of the allowed source states, so concurrent transitions cannot
+    /// both win. Other fields can be set in the same update with `extra_patch`, like the time
+    /// of the transition. Returns the resource as it is after the update. If the current state
+    /// is not one of the source states, a `DynarustError::InvalidTransition` error is returned
+    /// carrying it, which is None if the resource does not exist.
+    ///
+    /// # arguments
+    ///
+    /// * `key` - The key of the resource.
+    /// * `from_states` - States from which the transition is allowed.
+    /// * `to_state` - The new state.
+    /// * `extra_patch` - serde Object with other fields that are set along with the state, it
+    ///   can be `Value::Null`. The resource is not read beforehand, so unlike `update` the
+    ///   patch is not validated against the resource nor goes through `before_save`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// async {
+    ///     match client
+    ///         .transition(&Order::key("orders", id), &["pending"], "paid", json!({ "paid_at": now }))
+    ///         .await
+    ///     {
+    ///         Err(DynarustError::InvalidTransition { actual }) => println!("order is {actual:?}"),
+    ///         result => println!("paid {:?}", result?),
+    ///     }
+    /// }
+    /// ```
+    pub async fn transition<T: StateMachine + Serialize + DeserializeOwned>(
+        &self,
+        key: &KeyOf<T>,
+        from_states: &[&str],
+        to_state: &str,
+        extra_patch: Value,
+    ) -> Result<T, DynarustError> {
+        let patch: Map<String, Value> = match extra_patch {
+            Value::Null => Map::new(),
+            patch => serde_json::from_value(patch)?,
+        };
+        let field = T::state_field();
+        let (pk_attr, sk_attr) = self.key_names(&T::table());
+        if let Some(k) = patch.keys().find(|k| {
+            [field, pk_attr, sk_attr, GSI1_PK, GSI1_SK].contains(&k.as_str())
+                || T::immutable_fields().contains(&k.as_str())
+        }) {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "Cannot set {k} in a transition"
+            )));
+        }
+
+        let mut update = ItemUpdate::default();
+        update.set.push((field.to_string(), Value::from(to_state)));
+        update.set.extend(patch);
+        let from_states = from_states
+            .iter()
+            .map(|state| Value::from(*state))
+            .collect();
+        let condition = condition_check_exists()
+            .merge(vec![condition_check(field, Condition::In(from_states))?]);
+
+        let table = T::table();
+        let update = update_item(
+            self,
+            &table,
+            key.pk_sk(),
+            update,
+            condition,
+            Some(ReturnValue::AllNew),
+        );
+        let after = match self.stats.measure(Operation::UpdateItem, update).await {
+            Err(DynarustError::ConditionalCheckFailedError(_)) => {
+                let current = get_item(self, &table, key.pk_sk()).await?;
+                let actual = current
+                    .and_then(|current| current.get(field).cloned())
+                    .and_then(|state| state.as_str().map(String::from));
+                return Err(DynarustError::InvalidTransition { actual });
+            }
+            after => after?,
+        };
+        self.audit(
+            AuditOperation::Update,
+            &table,
+            key.pk_sk(),
+            None,
+            after.clone(),
+        )
+        .await?;
+        let after = after.ok_or_else(|| {
+            DynarustError::UnexpectedError("The transitioned resource was not returned".into())
+        })?;
+        Client::value_as_resource(after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{Client, DynarustError, Resource, StateMachine};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Order {
+        id: String,
+        status: String,
+        #[serde(default)]
+        paid_at: Option<i64>,
+    }
+
+    impl Resource for Order {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("transitions_states".into(), self.id.clone())
+        }
+    }
+
+    impl StateMachine for Order {}
+
+    #[tokio::test]
+    async fn transitions_states() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let order = Order {
+            id: "1".into(),
+            status: "pending".into(),
+            paid_at: None,
+        };
+        client.force_create(&order).await.unwrap();
+        let key = Order::key("transitions_states", "1");
+
+        let paid = client
+            .transition(
+                &key,
+                &["pending", "failed"],
+                "paid",
+                json!({ "paid_at": 10 }),
+            )
+            .await
+            .unwrap();
+        assert_eq!((paid.status.as_str(), paid.paid_at), ("paid", Some(10)));
+
+        let err = client
+            .transition(&key, &["pending"], "paid", Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DynarustError::InvalidTransition { actual: Some(actual) } if actual == "paid"
+        ));
+
+        let missing = Order::key("transitions_states", "2");
+        let err = client
+            .transition(&missing, &["pending"], "paid", Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DynarustError::InvalidTransition { actual: None }
+        ));
+
+        let err = client
+            .transition(&key, &["paid"], "shipped", json!({ "status": "lost" }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+    }
+}
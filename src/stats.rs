@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use aws_sdk_dynamodb::model::ConsumedCapacity;
+use serde::Serialize;
+
+use crate::{Client, DynarustError};
+
+/// The store operations whose latency is tracked by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    GetItem,
+    BatchGetItems,
+    PutItem,
+    UpdateItem,
+    DeleteItem,
+    Query,
+    Transaction,
+    BatchWriteItems,
+}
+
+impl Operation {
+    const ALL: [Operation; 8] = [
+        Operation::GetItem,
+        Operation::BatchGetItems,
+        Operation::PutItem,
+        Operation::UpdateItem,
+        Operation::DeleteItem,
+        Operation::Query,
+        Operation::Transaction,
+        Operation::BatchWriteItems,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::GetItem => "get_item",
+            Operation::BatchGetItems => "batch_get_items",
+            Operation::PutItem => "put_item",
+            Operation::UpdateItem => "update_item",
+            Operation::DeleteItem => "delete_item",
+            Operation::Query => "query",
+            Operation::Transaction => "transaction",
+            Operation::BatchWriteItems => "batch_write_items",
+        }
+    }
+}
+
+/// Each power of two of microseconds is split in 4 buckets, so percentiles are reported with
+/// an error below 25%.
+const BUCKETS: usize = 252;
+
+fn bucket(micros: u64) -> usize {
+    if micros < 4 {
+        return micros as usize;
+    }
+    let exp = 63 - micros.leading_zeros() as usize;
+    let sub = (micros >> (exp - 2)) as usize & 0b11;
+    (exp - 1) * 4 + sub
+}
+
+fn bucket_upper_bound(i: usize) -> u64 {
+    if i < 4 {
+        return i as u64;
+    }
+    let (exp, sub) = (i / 4 + 1, i % 4);
+    // the bound of the last bucket overflows to zero before subtracting, giving u64::MAX
+    ((5 + sub as u64) << (exp - 2)).wrapping_sub(1)
+}
+
+struct OperationCounters {
+    errors: AtomicU64,
+    capacity_milli_units: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Default for OperationCounters {
+    fn default() -> Self {
+        Self {
+            errors: AtomicU64::new(0),
+            capacity_milli_units: AtomicU64::new(0),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl OperationCounters {
+    fn snapshot(&self) -> OperationStats {
+        let buckets: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let count = buckets.iter().sum();
+        let percentile = |p: f64| {
+            let target = ((count as f64) * p).ceil().max(1.0) as u64;
+            let mut seen = 0;
+            for (i, n) in buckets.iter().enumerate() {
+                seen += n;
+                if seen >= target {
+                    return bucket_upper_bound(i);
+                }
+            }
+            0
+        };
+        OperationStats {
+            count,
+            errors: self.errors.load(Ordering::Relaxed),
+            p50_micros: percentile(0.5),
+            p90_micros: percentile(0.9),
+            p99_micros: percentile(0.99),
+            consumed_capacity: self.capacity_milli_units.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+/// Counters shared by a client and all the clients derived from it.
+pub(crate) struct StatsRecorder {
+    operations: [OperationCounters; Operation::ALL.len()],
+}
+
+impl Default for StatsRecorder {
+    fn default() -> Self {
+        Self {
+            operations: std::array::from_fn(|_| OperationCounters::default()),
+        }
+    }
+}
+
+impl StatsRecorder {
+    fn counters(&self, operation: Operation) -> &OperationCounters {
+        &self.operations[operation as usize]
+    }
+
+    /// Awaits the future of an operation, recording its latency and whether it failed.
+    pub(crate) async fn measure<R>(
+        &self,
+        operation: Operation,
+        future: impl Future<Output = Result<R, DynarustError>>,
+    ) -> Result<R, DynarustError> {
+        let start = Instant::now();
+        let result = future.await;
+        let counters = self.counters(operation);
+        let micros = start.elapsed().as_micros() as u64;
+        counters.buckets[bucket(micros)].fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Adds the capacity units consumed by an operation, as reported by dynamo.
+    pub(crate) fn add_capacity(&self, operation: Operation, capacity: Option<f64>) {
+        if let Some(capacity) = capacity {
+            self.counters(operation)
+                .capacity_milli_units
+                .fetch_add((capacity * 1000.0) as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds the capacity units consumed by an operation that spans multiple tables or items,
+    /// like batch gets and transactions, which dynamo reports per table.
+    pub(crate) fn add_all_capacity(
+        &self,
+        operation: Operation,
+        capacity: Option<&[ConsumedCapacity]>,
+    ) {
+        for capacity in capacity.unwrap_or_default() {
+            self.add_capacity(operation, capacity.capacity_units());
+        }
+    }
+}
+
+/// Aggregated statistics of one kind of operation. Latencies are upper bounds of the
+/// percentiles, with an error below 25%.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationStats {
+    /// Number of operations performed, including the failed ones.
+    pub count: u64,
+    /// Number of failed operations.
+    pub errors: u64,
+    /// Median latency in microseconds.
+    pub p50_micros: u64,
+    /// 90th percentile of the latency in microseconds.
+    pub p90_micros: u64,
+    /// 99th percentile of the latency in microseconds.
+    pub p99_micros: u64,
+    /// Capacity units consumed, as reported by dynamo for every operation.
+    pub consumed_capacity: f64,
+}
+
+/// Statistics of the operations performed by a client since it was built, by operation name.
+/// Operations that were never performed are not included.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientStats {
+    /// Statistics by operation name, like `get_item`, `query` or `transaction`.
+    pub operations: BTreeMap<&'static str, OperationStats>,
+}
+
+impl Client {
+    /// Returns the latency percentiles and consumed capacity of the item operations, queries,
+    /// batch writes and transactions performed since this client was built. Clients derived from this one, like
+    /// the tenant scoped ones, share the same statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client.get::<Car>(("cars".into(), "1".into())).await?;
+    ///     let stats = client.stats();
+    ///     println!("{}", serde_json::to_string(&stats)?);
+    /// }
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        let operations = Operation::ALL
+            .iter()
+            .map(|operation| (operation.name(), self.stats.counters(*operation).snapshot()))
+            .filter(|(_, stats)| stats.count > 0)
+            .collect();
+        ClientStats { operations }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::TestResource;
+    use crate::{begin_transaction, transact_create};
+
+    #[test]
+    fn buckets_contain_their_upper_bound() {
+        for micros in [0, 3, 4, 7, 8, 9, 10, 1000, 123456, u64::MAX] {
+            let i = bucket(micros);
+            assert!(bucket_upper_bound(i) >= micros);
+            assert_eq!(bucket(bucket_upper_bound(i)), i);
+            assert!(i < BUCKETS);
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregates_operation_latencies() {
+        let client = Client::local_on_port(12345).await;
+        for _ in 0..3 {
+            let _ = client
+                .stats
+                .measure(Operation::GetItem, async { Ok(()) })
+                .await;
+        }
+        let _ = client
+            .stats
+            .measure::<()>(Operation::Query, async {
+                Err(DynarustError::UnexpectedError("".into()))
+            })
+            .await;
+        client.stats.add_capacity(Operation::Query, Some(1.5));
+        let capacity = |units| ConsumedCapacity::builder().capacity_units(units).build();
+        client
+            .stats
+            .add_all_capacity(Operation::GetItem, Some(&[capacity(0.5), capacity(2.0)]));
+
+        let stats = client.scoped("tenant").unwrap().stats();
+        assert_eq!(stats.operations.len(), 2);
+        assert_eq!(stats.operations["get_item"].count, 3);
+        assert_eq!(stats.operations["get_item"].errors, 0);
+        assert_eq!(stats.operations["get_item"].consumed_capacity, 2.5);
+        assert_eq!(stats.operations["query"].errors, 1);
+        assert_eq!(stats.operations["query"].consumed_capacity, 1.5);
+    }
+
+    #[tokio::test]
+    async fn measures_counts_and_transactions() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "measures_counts_and_transactions".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        let mut context = begin_transaction();
+        transact_create(&resource, &mut context).unwrap();
+        client.execute_transaction(context).await.unwrap();
+        client
+            .count_by_pk::<TestResource>(resource.pk.clone())
+            .await
+            .unwrap();
+
+        let stats = client.stats();
+        assert_eq!(stats.operations["transaction"].count, 1);
+        assert!(stats.operations["transaction"].consumed_capacity > 0.0);
+        assert_eq!(stats.operations["query"].count, 1);
+        assert!(stats.operations["query"].consumed_capacity > 0.0);
+    }
+}
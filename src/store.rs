@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::model::ReturnValue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::condition_check::{condition_check_not_exists, ConditionCheckInfo};
+use crate::create::put_resource;
+use crate::delete::delete_resource;
+use crate::get::{
+    batch_get_all_resources, batch_get_ordered_resources, batch_get_resources, get_resource,
+};
+use crate::list::list_resources;
+use crate::stats::Operation;
+use crate::update::{update_resource, ItemUpdate};
+use crate::{create, delete, get, list, update};
+use crate::{
+    AuditOperation, Client, DynarustError, ListOptions, Page, Resource, TransactionContext,
+};
+
+/// Untyped storage operations on which every typed dynarust operation is built. It is object
+/// safe, so services can hold an `Arc<dyn DynamoStore>` and swap the dynamo backed `Client`
+/// for a fake store in their unit tests. The typed operations are available on
+/// `dyn DynamoStore` with the same signatures as the ones in `Client`.
+///
+/// Items are passed around as serde values without the PrimaryKey and SecondaryKey
+/// attributes, which are provided separately as the `key` argument.
+///
+/// # Examples
+///
+/// ```
+/// async fn rename(store: &dyn dynarust::DynamoStore, car: &Car) -> Result<Car, DynarustError> {
+///     store.update(car, json!({ "name": "Herbie" })).await
+/// }
+///
+/// async {
+///     let store: Arc<dyn dynarust::DynamoStore> = Arc::new(dynarust::Client::aws().await);
+///     rename(store.as_ref(), &car).await?;
+/// }
+/// ```
+#[async_trait]
+pub trait DynamoStore: Send + Sync {
+    /// Retrieves a single item, returning None if it does not exist.
+    async fn get_item(
+        &self,
+        table: &str,
+        key: (String, String),
+    ) -> Result<Option<Value>, DynarustError>;
+
+    /// Retrieves multiple items, the ones that do not exist are not returned.
+    async fn batch_get_items(
+        &self,
+        table: &str,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Value>, DynarustError>;
+
+    /// Writes an item, overwriting the previous one if the condition check allows it.
+    async fn put_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        item: Map<String, Value>,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError>;
+
+    /// Sets and removes attributes of an existing item.
+    async fn update_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        update: ItemUpdate,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError>;
+
+    /// Deletes an item.
+    async fn delete_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError>;
+
+    /// Retrieves a page of the items under the same pk.
+    async fn query_items(
+        &self,
+        table: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<Value>, DynarustError>;
+
+    /// Executes all the operations in the transaction context atomically.
+    async fn execute_transaction(
+        &self,
+        transaction_context: TransactionContext,
+    ) -> Result<(), DynarustError>;
+}
+
+#[async_trait]
+impl DynamoStore for Client {
+    async fn get_item(
+        &self,
+        table: &str,
+        key: (String, String),
+    ) -> Result<Option<Value>, DynarustError> {
+        let get = get::get_item(self, table, key);
+        self.stats.measure(Operation::GetItem, get).await
+    }
+
+    async fn batch_get_items(
+        &self,
+        table: &str,
+        keys: Vec<(String, String)>,
+    ) -> Result<Vec<Value>, DynarustError> {
+        let batch_get = get::batch_get_items(self, table, keys);
+        self.stats
+            .measure(Operation::BatchGetItems, batch_get)
+            .await
+    }
+
+    async fn put_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        item: Map<String, Value>,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let put = create::put_item(self, table, key.clone(), item, condition_check);
+        let before = self.stats.measure(Operation::PutItem, put).await?;
+        self.audit(AuditOperation::Put, table, key, before, None)
+            .await
+    }
+
+    async fn update_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        update: ItemUpdate,
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let return_values = self.audit.is_some().then_some(ReturnValue::AllNew);
+        let update = update::update_item(
+            self,
+            table,
+            key.clone(),
+            update,
+            condition_check,
+            return_values,
+        );
+        let after = self.stats.measure(Operation::UpdateItem, update).await?;
+        self.audit(AuditOperation::Update, table, key, None, after)
+            .await
+    }
+
+    async fn delete_item(
+        &self,
+        table: &str,
+        key: (String, String),
+        condition_check: ConditionCheckInfo,
+    ) -> Result<(), DynarustError> {
+        let delete = delete::delete_item(self, table, key.clone(), condition_check);
+        let before = self.stats.measure(Operation::DeleteItem, delete).await?;
+        self.audit(AuditOperation::Delete, table, key, before, None)
+            .await
+    }
+
+    async fn query_items(
+        &self,
+        table: &str,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<Value>, DynarustError> {
+        list::query_items(self, table, pk, options).await
+    }
+
+    async fn execute_transaction(
+        &self,
+        transaction_context: TransactionContext,
+    ) -> Result<(), DynarustError> {
+        Client::execute_transaction(self, transaction_context).await
+    }
+}
+
+impl dyn DynamoStore + '_ {
+    /// Retrieves a resource. If the resource does not exist returns Option::None.
+    pub async fn get<T: Resource + DeserializeOwned>(
+        &self,
+        pk_sk: (String, String),
+    ) -> Result<Option<T>, DynarustError> {
+        get_resource(self, pk_sk).await
+    }
+
+    /// Retrieves multiple resource in the same operation.
+    pub async fn batch_get<T: Resource + DeserializeOwned>(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<HashMap<(String, String), T>, DynarustError> {
+        batch_get_resources(self, items).await
+    }
+
+    /// Retrieves multiple resources, with an entry for every requested key that is None if the
+    /// resource does not exist.
+    pub async fn batch_get_all<T: Resource + DeserializeOwned>(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<HashMap<(String, String), Option<T>>, DynarustError> {
+        batch_get_all_resources(self, items).await
+    }
+
+    /// Retrieves multiple resources in the order of the requested keys, with None for the ones
+    /// that do not exist.
+    pub async fn batch_get_ordered<T: Resource + DeserializeOwned + Clone>(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<Vec<Option<T>>, DynarustError> {
+        batch_get_ordered_resources(self, items).await
+    }
+
+    /// Creates a new resource, if the resource already exists, this operation fails.
+    pub async fn create<'a, T: Resource + Serialize>(
+        &self,
+        resource: &'a T,
+    ) -> Result<&'a T, DynarustError> {
+        self.create_with_checks(resource, vec![]).await
+    }
+
+    /// Creates a new resource with additional condition checks.
+    pub async fn create_with_checks<'a, T: Resource + Serialize>(
+        &self,
+        resource: &'a T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<&'a T, DynarustError> {
+        let condition_check = condition_check_not_exists().merge(condition_checks);
+        put_resource(self, resource, condition_check).await?;
+        Ok(resource)
+    }
+
+    /// Creates a new resource, overwriting a previously existing resource if necessary.
+    pub async fn force_create<'a, T: Resource + Serialize>(
+        &self,
+        resource: &'a T,
+    ) -> Result<&'a T, DynarustError> {
+        put_resource(self, resource, ConditionCheckInfo::default()).await?;
+        Ok(resource)
+    }
+
+    /// Creates a new resource, overwriting a previous one only if the condition checks pass.
+    pub async fn force_create_with_checks<'a, T: Resource + Serialize>(
+        &self,
+        resource: &'a T,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<&'a T, DynarustError> {
+        let condition_check = ConditionCheckInfo::default().merge(condition_checks);
+        put_resource(self, resource, condition_check).await?;
+        Ok(resource)
+    }
+
+    /// Updates a resource. It returns an error if the resource does not exist.
+    pub async fn update<T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        resource: &T,
+        request: Value,
+    ) -> Result<T, DynarustError> {
+        self.update_with_checks(resource, request, vec![]).await
+    }
+
+    /// Updates a resource with additional condition checks.
+    pub async fn update_with_checks<T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        resource: &T,
+        request: Value,
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<T, DynarustError> {
+        update_resource(self, resource, request, condition_checks).await
+    }
+
+    /// Deletes a resource.
+    pub async fn delete<T: Resource>(&self, pk_sk: (String, String)) -> Result<(), DynarustError> {
+        self.delete_with_checks::<T>(pk_sk, vec![]).await
+    }
+
+    /// Deletes a resource with additional condition checks.
+    pub async fn delete_with_checks<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        condition_checks: Vec<ConditionCheckInfo>,
+    ) -> Result<(), DynarustError> {
+        delete_resource::<_, T>(self, pk_sk, condition_checks).await
+    }
+
+    /// List all the resources under the same pk, one page at a time.
+    pub async fn list<T: Resource + DeserializeOwned>(
+        &self,
+        pk: String,
+        options: &ListOptions,
+    ) -> Result<Page<T>, DynarustError> {
+        list_resources(self, pk, options).await
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use serde_json::{json, Map, Value};
+
+    use crate::client::tests::TestResource;
+    use crate::{
+        ConditionCheckInfo, DynamoStore, DynarustError, ItemUpdate, ListOptions, Page, Resource,
+        TransactionContext,
+    };
+
+    type Key = (String, String, String);
+
+    /// In memory store that only understands the conditions used for creating resources.
+    #[derive(Default)]
+    pub(crate) struct FakeStore {
+        items: Mutex<BTreeMap<Key, Map<String, Value>>>,
+    }
+
+    #[async_trait]
+    impl DynamoStore for FakeStore {
+        async fn get_item(
+            &self,
+            table: &str,
+            (pk, sk): (String, String),
+        ) -> Result<Option<Value>, DynarustError> {
+            let items = self.items.lock().unwrap();
+            Ok(items
+                .get(&(table.to_string(), pk, sk))
+                .cloned()
+                .map(Value::Object))
+        }
+
+        async fn batch_get_items(
+            &self,
+            table: &str,
+            keys: Vec<(String, String)>,
+        ) -> Result<Vec<Value>, DynarustError> {
+            let mut values = vec![];
+            for key in keys {
+                values.extend(self.get_item(table, key).await?)
+            }
+            Ok(values)
+        }
+
+        async fn put_item(
+            &self,
+            table: &str,
+            (pk, sk): (String, String),
+            item: Map<String, Value>,
+            condition_check: ConditionCheckInfo,
+        ) -> Result<(), DynarustError> {
+            let mut items = self.items.lock().unwrap();
+            let key = (table.to_string(), pk, sk);
+            if !condition_check.is_empty() && items.contains_key(&key) {
+                return Err(DynarustError::ConditionalCheckFailedError(None));
+            }
+            items.insert(key, item);
+            Ok(())
+        }
+
+        async fn update_item(
+            &self,
+            table: &str,
+            (pk, sk): (String, String),
+            update: ItemUpdate,
+            _condition_check: ConditionCheckInfo,
+        ) -> Result<(), DynarustError> {
+            let mut items = self.items.lock().unwrap();
+            let item = items
+                .get_mut(&(table.to_string(), pk, sk))
+                .ok_or(DynarustError::ConditionalCheckFailedError(None))?;
+            item.extend(update.set);
+            for k in update.remove {
+                item.remove(&k);
+            }
+            Ok(())
+        }
+
+        async fn delete_item(
+            &self,
+            table: &str,
+            (pk, sk): (String, String),
+            _condition_check: ConditionCheckInfo,
+        ) -> Result<(), DynarustError> {
+            self.items
+                .lock()
+                .unwrap()
+                .remove(&(table.to_string(), pk, sk));
+            Ok(())
+        }
+
+        async fn query_items(
+            &self,
+            table: &str,
+            pk: String,
+            options: &ListOptions,
+        ) -> Result<Page<Value>, DynarustError> {
+            let items = self.items.lock().unwrap();
+            let items: Vec<Value> = items
+                .iter()
+                .filter(|((t, p, _), _)| t == table && *p == pk)
+                .map(|(_, item)| Value::Object(item.clone()))
+                .take(options.limit as usize)
+                .collect();
+            Ok(Page {
+                scanned_count: items.len() as i32,
+                count: items.len() as i32,
+                items,
+                next: None,
+                consumed_capacity: None,
+            })
+        }
+
+        async fn execute_transaction(
+            &self,
+            _transaction_context: TransactionContext,
+        ) -> Result<(), DynarustError> {
+            Err(DynarustError::UnexpectedError(
+                "not supported by FakeStore".into(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_typed_operations_on_a_fake_store() {
+        let store: Arc<dyn DynamoStore> = Arc::new(FakeStore::default());
+
+        let resource = TestResource {
+            pk: "runs_typed_operations_on_a_fake_store".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        store.create(&resource).await.unwrap();
+        let err = store.create(&resource).await.unwrap_err();
+        assert_eq!(err.to_string(), "The conditional request failed");
+
+        let updated = store
+            .update(&resource, json!({ "string": "updated" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get::<TestResource>(resource.pk_sk()).await.unwrap(),
+            Some(updated.clone())
+        );
+
+        let listed = store
+            .list::<TestResource>(resource.pk.clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.items, vec![updated]);
+
+        store
+            .delete::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get::<TestResource>(resource.pk_sk()).await.unwrap(),
+            None
+        );
+    }
+}
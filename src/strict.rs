@@ -0,0 +1,94 @@
+use serde::de::{DeserializeOwned, Error, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer};
+use serde_json::Value;
+
+/// How the attributes of a stored item that are not fields of the resource are handled when
+/// reading it. They are dropped by deserialization, so they usually mean that another service
+/// writes a newer or different shape of the resource to the same table.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum UnknownAttributes {
+    /// unknown attributes are silently dropped.
+    #[default]
+    Ignore,
+    /// reading an item with unknown attributes fails with a
+    /// `DynarustError::UnknownAttributesError`.
+    Reject,
+    /// the resource is read, but the table and the unknown attributes are handed to the
+    /// provided function first, for example for logging them.
+    Report(fn(table: &str, attributes: &[String])),
+}
+
+/// Attributes of the item that are not fields of the resource T. Resources whose fields cannot
+/// be known beforehand, like the ones with flattened fields, never have unknown attributes.
+pub(crate) fn unknown_attributes<T: DeserializeOwned>(value: &Value) -> Vec<String> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldsDeserializer(&mut fields));
+    match (fields, value.as_object()) {
+        (Some(fields), Some(object)) => object
+            .keys()
+            .filter(|k| !fields.contains(&k.as_str()))
+            .cloned()
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Deserializer that only records the fields that a struct expects, failing right after.
+struct FieldsDeserializer<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> Deserializer<'de> for FieldsDeserializer<'a> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Error::custom("only structs are introspected"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(Error::custom("fields recorded"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    use crate::strict::unknown_attributes;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Strict {
+        id: String,
+        #[serde(rename = "displayName")]
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Loose {
+        id: String,
+        #[serde(flatten)]
+        extra: HashMap<String, Value>,
+    }
+
+    #[test]
+    fn finds_unknown_attributes() {
+        let item = json!({ "id": "1", "displayName": "a", "color": "red" });
+        assert_eq!(unknown_attributes::<Strict>(&item), vec!["color"]);
+        assert!(unknown_attributes::<Loose>(&item).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use aws_sdk_dynamodb::model::AttributeValue;
+use futures::future::try_join_all;
+
+use crate::{condition_check_number, Client, DynamoOperator, DynamoStore, DynarustError, Resource};
+
+/// Options of a sweep of expired items.
+#[derive(Debug, Clone)]
+pub struct SweepOptions {
+    /// number of segments of the parallel scan, default is 4.
+    pub segments: i32,
+    /// maximum delete rate, deletes are not throttled if not provided.
+    pub max_deletes_per_second: Option<u32>,
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        Self {
+            segments: 4,
+            max_deletes_per_second: None,
+        }
+    }
+}
+
+/// Spaces out the deletes shared by concurrent tasks so they do not exceed a rate.
+struct DeleteThrottle {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl DeleteThrottle {
+    fn new(per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let at = (*next).max(now);
+            *next = at + self.interval;
+            at - now
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Returns the key of a scanned item of the table if its TTL attribute, in epoch seconds, is
+/// before `now`.
+fn expired_key(
+    client: &Client,
+    table: &str,
+    item: &HashMap<String, AttributeValue>,
+    ttl_attribute: &str,
+    now: i64,
+) -> Option<(String, String)> {
+    let expires_at: f64 = match item.get(ttl_attribute) {
+        Some(AttributeValue::N(n)) => n.parse().ok()?,
+        _ => return None,
+    };
+    if expires_at >= now as f64 {
+        return None;
+    }
+    let (pk_attr, sk_attr) = client.key_names(table);
+    match (item.get(pk_attr), item.get(sk_attr)) {
+        (Some(AttributeValue::S(pk)), Some(AttributeValue::S(sk))) => {
+            let pk = match client.tenant() {
+                Some(tenant) => tenant.strip(pk)?.to_string(),
+                None => pk.clone(),
+            };
+            Some((pk, sk.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Types of the fields that `resource_ttl` accepts as TTL attribute, which are the integers
+/// that can hold epoch seconds, optionally missing for items that never expire.
+pub trait TtlValue {}
+
+impl TtlValue for i64 {}
+impl TtlValue for u64 {}
+impl TtlValue for u32 {}
+impl<T: TtlValue> TtlValue for Option<T> {}
+
+/// Implements `Resource::ttl_attribute` with the field that holds the expiration time of the
+/// resource, to be used inside the implementation of `Resource`. A field that does not exist,
+/// or that is not an integer, is a compile error, as dynamo would silently never expire the
+/// items.
+///
+/// # Examples
+///
+/// ```
+/// impl dynarust::Resource for Session {
+///     fn table() -> String { "Sessions".into() }
+///     fn pk_sk(&self) -> (String, String) { (self.user.clone(), self.id.clone()) }
+///     dynarust::resource_ttl!(expires_at);
+/// }
+///
+/// async {
+///     client.sweep_expired::<Session>(None, None).await?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! resource_ttl {
+    ($field:ident) => {
+        fn ttl_attribute() -> Option<&'static str> {
+            fn ttl_value<V: $crate::TtlValue>(_: &V) {}
+            let _ = |resource: &Self| ttl_value(&resource.$field);
+            Some(stringify!($field))
+        }
+    };
+}
+
+impl Client {
+    /// Deletes right away the items in the table of resource T whose TTL attribute is in the
+    /// past, as dynamo can take days to delete expired items. The TTL attribute must hold the
+    /// expiration time in epoch seconds, as dynamo expects. Each item is deleted on the
+    /// condition that it is still expired, so items whose TTL was extended during the sweep are
+    /// kept. Returns the number of deleted items.
+    ///
+    /// # arguments
+    ///
+    /// * `ttl_attribute` - Name of the attribute with the expiration time. If not provided, the
+    ///   one declared by the resource with `resource_ttl!` is used, and if it declares none a
+    ///   `DynarustError::InvalidRequestError` is returned.
+    /// * `options` - optional sweep options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let deleted = client
+    ///         .sweep_expired::<Session>(Some("expires_at"), Some(SweepOptions {
+    ///             max_deletes_per_second: Some(50),
+    ///             ..Default::default()
+    ///         }))
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn sweep_expired<T: Resource>(
+        &self,
+        ttl_attribute: Option<&str>,
+        options: Option<SweepOptions>,
+    ) -> Result<usize, DynarustError> {
+        let table = T::table();
+        let Some(ttl_attribute) = ttl_attribute.or(T::ttl_attribute()) else {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "No TTL attribute for sweeping the table {table}"
+            )));
+        };
+        let options = options.unwrap_or_default();
+        let segments = options.segments.max(1);
+        let throttle = options.max_deletes_per_second.map(DeleteThrottle::new);
+        let now = self.now().timestamp();
+
+        let deleted = try_join_all((0..segments).map(|segment| {
+            let (table, throttle) = (&table, &throttle);
+            async move {
+                let mut deleted = 0;
+                let mut exclusive_start_key = None;
+                loop {
+                    let (items, last_evaluated_key) = self
+                        .scan_segment_page(table, segment, segments, None, exclusive_start_key)
+                        .await?;
+
+                    for key in items
+                        .iter()
+                        .filter_map(|item| expired_key(self, table, item, ttl_attribute, now))
+                    {
+                        if let Some(throttle) = throttle {
+                            throttle.acquire().await;
+                        }
+                        let still_expired =
+                            condition_check_number(ttl_attribute, DynamoOperator::Ls, now);
+                        match DynamoStore::delete_item(self, table, key, still_expired).await {
+                            Ok(()) => deleted += 1,
+                            Err(DynarustError::ConditionalCheckFailedError(_)) => {}
+                            Err(err) => return Err(err),
+                        }
+                    }
+
+                    exclusive_start_key = last_evaluated_key;
+                    if exclusive_start_key.is_none() {
+                        return Ok(deleted);
+                    }
+                }
+            }
+        }))
+        .await?;
+
+        Ok(deleted.into_iter().sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::model::AttributeValue;
+    use chrono::Utc;
+    use serde::{Deserialize, Serialize};
+
+    use super::expired_key;
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::client::{PK, SK};
+    use crate::{Client, Resource};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Session {
+        user: String,
+        id: String,
+        expires_at: i64,
+    }
+
+    impl Resource for Session {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.user.clone(), self.id.clone())
+        }
+
+        resource_ttl!(expires_at);
+    }
+
+    #[tokio::test]
+    async fn finds_expired_items_of_the_tenant() {
+        let client = Client::local_on_port(12345).await.scoped("acme").unwrap();
+        let item = |pk: &str, expires_at: &str| {
+            HashMap::from([
+                (PK.to_string(), AttributeValue::S(pk.to_string())),
+                (SK.to_string(), AttributeValue::S("1".to_string())),
+                (
+                    "expires_at".to_string(),
+                    AttributeValue::N(expires_at.to_string()),
+                ),
+            ])
+        };
+        assert_eq!(
+            expired_key(&client, &TABLE, &item("acme#user", "10"), "expires_at", 20),
+            Some(("user".to_string(), "1".to_string()))
+        );
+        assert_eq!(
+            expired_key(&client, &TABLE, &item("acme#user", "30"), "expires_at", 20),
+            None
+        );
+        assert_eq!(
+            expired_key(&client, &TABLE, &item("other#user", "10"), "expires_at", 20),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn finds_expired_items_of_external_tables() {
+        let client = Client::local_on_port(12345)
+            .await
+            .with_external_table::<Session>("user", "id")
+            .unwrap();
+        let item = HashMap::from([
+            ("user".to_string(), AttributeValue::S("u".to_string())),
+            ("id".to_string(), AttributeValue::S("1".to_string())),
+            (
+                "expires_at".to_string(),
+                AttributeValue::N("10".to_string()),
+            ),
+        ]);
+        assert_eq!(
+            expired_key(&client, &TABLE, &item, "expires_at", 20),
+            Some(("u".to_string(), "1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn requires_a_ttl_attribute() {
+        let client = Client::local_on_port(12345).await;
+        let err = client
+            .sweep_expired::<TestResource>(None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Invalid request: No TTL attribute for sweeping the table {}",
+                *TABLE
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn sweeps_expired_sessions() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let tenant = client.scoped("sweeps_expired_sessions").unwrap();
+        let now = Utc::now().timestamp();
+        for (id, expires_at) in [("expired", now - 60), ("alive", now + 3600)] {
+            let session = Session {
+                user: "user".into(),
+                id: id.into(),
+                expires_at,
+            };
+            tenant.force_create(&session).await.unwrap();
+        }
+
+        let deleted = tenant.sweep_expired::<Session>(None, None).await.unwrap();
+        assert_eq!(deleted, 1);
+        let alive = tenant
+            .get::<Session>(("user".into(), "alive".into()))
+            .await
+            .unwrap();
+        assert!(alive.is_some());
+    }
+
+    #[test]
+    fn names_ttl_attribute() {
+        assert_eq!(Session::ttl_attribute(), Some("expires_at"));
+    }
+}
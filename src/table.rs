@@ -1,12 +1,63 @@
-use crate::{Client, DynarustError, Resource, PK, SK};
+use crate::{Client, DynarustError, Resource, SecondaryIndex, PK, SK};
 use aws_sdk_dynamodb::model::{
-    AttributeDefinition, KeySchemaElement, KeyType, ProvisionedThroughput, ScalarAttributeType,
+    AttributeDefinition, GlobalSecondaryIndex, GlobalSecondaryIndexUpdate, KeySchemaElement,
+    KeyType, Projection, ProjectionType, ProvisionedThroughput, ScalarAttributeType,
+    TableDescription, UpdateGlobalSecondaryIndexAction,
 };
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub use aws_sdk_dynamodb::model::TableClass;
+
+/// Tables that a client and its clones know to exist, along with their description once it
+/// was read, so that `create_table` and `verify_schema` do not reach dynamo again for them.
+#[derive(Default)]
+pub(crate) struct KnownTables(Mutex<HashMap<String, Option<TableDescription>>>);
+
+impl KnownTables {
+    pub(crate) fn contains(&self, table: &str) -> bool {
+        self.0.lock().unwrap().contains_key(table)
+    }
+
+    /// Records that the table exists, keeping its description if it was already read.
+    pub(crate) fn insert(&self, table: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .entry(table.to_string())
+            .or_insert(None);
+    }
+
+    pub(crate) fn description(&self, table: &str) -> Option<TableDescription> {
+        self.0.lock().unwrap().get(table).cloned().flatten()
+    }
+
+    pub(crate) fn insert_description(&self, table: &str, description: TableDescription) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(table.to_string(), Some(description));
+    }
+
+    /// Drops the description of the table, which is read again the next time it is needed.
+    pub(crate) fn forget_description(&self, table: &str) {
+        if let Some(description) = self.0.lock().unwrap().get_mut(table) {
+            *description = None;
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct CreateTableOptions {
     pub read_capacity: i64,
     pub write_capacity: i64,
+    /// storage class of the table, dynamo uses the standard class if not provided.
+    pub table_class: Option<TableClass>,
+    /// global secondary indexes created along with the `Resource::secondary_indexes` of the
+    /// resource, like the ones of other resources stored in the same table or
+    /// `SecondaryIndex::INVERTED` for adjacency lists.
+    pub indexes: Vec<SecondaryIndex>,
 }
 
 impl Default for CreateTableOptions {
@@ -14,31 +65,125 @@ impl Default for CreateTableOptions {
         Self {
             read_capacity: 5,
             write_capacity: 5,
+            table_class: None,
+            indexes: vec![],
         }
     }
 }
 
+/// The secondary indexes of resource T followed by the ones of the options, without repeating
+/// any name.
+fn secondary_indexes<T: Resource>(options: &CreateTableOptions) -> Vec<SecondaryIndex> {
+    let mut indexes: Vec<SecondaryIndex> = vec![];
+    for index in T::secondary_indexes().iter().chain(options.indexes.iter()) {
+        if !indexes.iter().any(|i| i.name == index.name) {
+            indexes.push(*index);
+        }
+    }
+    indexes
+}
+
+/// The key attributes of the secondary indexes that are not already defined by the table.
+fn secondary_index_attributes(indexes: &[SecondaryIndex]) -> Vec<&'static str> {
+    let mut attributes = vec![];
+    for attr in indexes.iter().flat_map(SecondaryIndex::attributes) {
+        if ![PK, SK].contains(&attr) && !attributes.contains(&attr) {
+            attributes.push(attr);
+        }
+    }
+    attributes
+}
+
+/// Provisioned read and write capacity units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throughput {
+    pub read_capacity: i64,
+    pub write_capacity: i64,
+}
+
+impl Throughput {
+    fn provisioned(&self) -> ProvisionedThroughput {
+        ProvisionedThroughput::builder()
+            .read_capacity_units(self.read_capacity)
+            .write_capacity_units(self.write_capacity)
+            .build()
+    }
+}
+
+/// Changes to apply to an existing table. Only the provided fields are modified.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateTableOptions {
+    /// new throughput of the table.
+    pub throughput: Option<Throughput>,
+    /// new throughput of the global secondary indexes, by index name.
+    pub index_throughput: HashMap<String, Throughput>,
+    /// new storage class of the table.
+    pub table_class: Option<TableClass>,
+}
+
 pub fn create_sam_resource<T: Resource>(maybe_options: Option<CreateTableOptions>) -> String {
     let options = maybe_options.unwrap_or_default();
     let read_capacity = options.read_capacity;
     let write_capacity = options.write_capacity;
+    let indexes = secondary_indexes::<T>(&options);
+    let table_class = match options.table_class {
+        Some(table_class) => format!("\n    TableClass: {}", table_class.as_str()),
+        None => String::new(),
+    };
+    let mut index_attributes = String::new();
+    for attr in secondary_index_attributes(&indexes) {
+        index_attributes += &format!(
+            "
+      - AttributeName: {attr}
+        AttributeType: S"
+        );
+    }
+    let mut secondary_indexes = String::new();
+    if !indexes.is_empty() {
+        secondary_indexes += "\n    GlobalSecondaryIndexes:";
+    }
+    for index in indexes.iter() {
+        let (name, pk_attribute) = (index.name, index.pk_attribute);
+        secondary_indexes += &format!(
+            "
+      - IndexName: {name}
+        KeySchema:
+          - AttributeName: {pk_attribute}
+            KeyType: HASH"
+        );
+        if let Some(sk_attribute) = index.sk_attribute {
+            secondary_indexes += &format!(
+                "
+          - AttributeName: {sk_attribute}
+            KeyType: RANGE"
+            );
+        }
+        secondary_indexes += &format!(
+            "
+        Projection:
+          ProjectionType: ALL
+        ProvisionedThroughput:
+          ReadCapacityUnits: {read_capacity}
+          WriteCapacityUnits: {write_capacity}"
+        );
+    }
     let table_name = T::table();
     format!(
         "\
 {table_name}DynamoDBTable:
   Type: AWS::DynamoDB::Table
   Properties:
-    TableName: {table_name}
+    TableName: {table_name}{table_class}
     AttributeDefinitions:
       - AttributeName: {PK}
         AttributeType: S
       - AttributeName: {SK}
-        AttributeType: S
+        AttributeType: S{index_attributes}
     KeySchema:
       - AttributeName: {PK}
         KeyType: HASH
       - AttributeName: {SK}
-        KeyType: RANGE
+        KeyType: RANGE{secondary_indexes}
     ProvisionedThroughput:
       ReadCapacityUnits: {read_capacity}
       WriteCapacityUnits: {write_capacity}
@@ -46,12 +191,134 @@ pub fn create_sam_resource<T: Resource>(maybe_options: Option<CreateTableOptions
     )
 }
 
+/// Properties for an `aws-cdk-lib.aws_dynamodb.Table` construct with the same configuration
+/// that `create_table` uses for resource T. The secondary indexes are listed
+/// under `globalSecondaryIndexes`, whose elements can be passed to `addGlobalSecondaryIndex`.
+///
+/// # Examples
+///
+/// ```
+/// let props = dynarust::create_cdk_props::<Car>(None);
+/// std::fs::write("cdk/car-table.json", props.to_string())?;
+/// ```
+pub fn create_cdk_props<T: Resource>(maybe_options: Option<CreateTableOptions>) -> Value {
+    let options = maybe_options.unwrap_or_default();
+    let mut props = json!({
+        "tableName": T::table(),
+        "partitionKey": { "name": PK, "type": "S" },
+        "sortKey": { "name": SK, "type": "S" },
+        "billingMode": "PROVISIONED",
+        "readCapacity": options.read_capacity,
+        "writeCapacity": options.write_capacity,
+        "globalSecondaryIndexes": [],
+    });
+    for index in secondary_indexes::<T>(&options) {
+        let mut gsi = json!({
+            "indexName": index.name,
+            "partitionKey": { "name": index.pk_attribute, "type": "S" },
+            "projectionType": "ALL",
+            "readCapacity": options.read_capacity,
+            "writeCapacity": options.write_capacity,
+        });
+        if let Some(sk_attribute) = index.sk_attribute {
+            gsi["sortKey"] = json!({ "name": sk_attribute, "type": "S" });
+        }
+        if let Some(indexes) = props["globalSecondaryIndexes"].as_array_mut() {
+            indexes.push(gsi);
+        }
+    }
+    if let Some(table_class) = options.table_class {
+        props["tableClass"] = json!(table_class.as_str());
+    }
+    props
+}
+
+/// Actions needed by every dynarust operation on a table.
+const SAM_POLICY_ACTIONS: [&str; 8] = [
+    "dynamodb:GetItem",
+    "dynamodb:BatchGetItem",
+    "dynamodb:PutItem",
+    "dynamodb:UpdateItem",
+    "dynamodb:DeleteItem",
+    "dynamodb:ConditionCheckItem",
+    "dynamodb:Query",
+    "dynamodb:Scan",
+];
+
+fn indent(text: &str, spaces: usize) -> String {
+    text.lines()
+        .map(|line| format!("{:spaces$}{line}\n", ""))
+        .collect()
+}
+
+/// IAM policy statements that grant the operations of dynarust on the table of resource T and
+/// its indexes, and nothing else. They can be pasted in the `Policies` of a SAM function.
+pub fn create_sam_policy_statements<T: Resource>() -> String {
+    let table_name = T::table();
+    let actions: String = SAM_POLICY_ACTIONS
+        .iter()
+        .map(|action| format!("    - {action}\n"))
+        .collect();
+    format!(
+        "\
+- Effect: Allow
+  Action:
+{actions}  Resource:
+    - !GetAtt {table_name}DynamoDBTable.Arn
+    - !Sub \"${{{table_name}DynamoDBTable.Arn}}/index/*\"
+"
+    )
+}
+
+/// SAM template with the table of resource T, a managed policy with the statements of
+/// `create_sam_policy_statements` and outputs for the table name and ARN and the policy ARN,
+/// so functions in other stacks can reference them.
+///
+/// # arguments
+///
+/// * `maybe_options` - The configuration of the table.
+pub fn create_sam_stack<T: Resource>(maybe_options: Option<CreateTableOptions>) -> String {
+    let table_name = T::table();
+    let table = indent(&create_sam_resource::<T>(maybe_options), 2);
+    let statements = indent(&create_sam_policy_statements::<T>(), 10);
+    format!(
+        "\
+Resources:
+{table}  {table_name}DynamoDBCrudPolicy:
+    Type: AWS::IAM::ManagedPolicy
+    Properties:
+      PolicyDocument:
+        Version: \"2012-10-17\"
+        Statement:
+{statements}Outputs:
+  {table_name}TableName:
+    Value: !Ref {table_name}DynamoDBTable
+  {table_name}TableArn:
+    Value: !GetAtt {table_name}DynamoDBTable.Arn
+  {table_name}CrudPolicyArn:
+    Value: !Ref {table_name}DynamoDBCrudPolicy
+"
+    )
+}
+
 impl Client {
-    /// Creates a table in dynamo with the appropriate configuration for resource T
+    /// Creates a table in dynamo with the appropriate configuration for resource T, including
+    /// the secondary indexes of the resource and of the options. The inverted index used for
+    /// listing edges of an adjacency list by their target and the index for the keys returned
+    /// by `Resource::gsi1_pk_sk` are only created if they are among them, as
+    /// `SecondaryIndex::INVERTED` and `SecondaryIndex::GSI1`.
+    ///
+    /// Tables that already exist are left as they are. Once a client knows that a table exists,
+    /// either because it created it or because dynamo reported it as existing, it and its
+    /// clones no longer send requests for creating it again.
     pub async fn create_table<T: Resource>(
         &self,
         options: Option<CreateTableOptions>,
     ) -> Result<(), DynarustError> {
+        let table = T::table();
+        if self.known_tables.contains(&table) {
+            return Ok(());
+        }
         let options = options.unwrap_or_default();
         let pk = AttributeDefinition::builder()
             .attribute_name(PK)
@@ -73,40 +340,157 @@ impl Client {
             .key_type(KeyType::Range)
             .build();
 
-        let pt = ProvisionedThroughput::builder()
-            .read_capacity_units(options.read_capacity)
-            .write_capacity_units(options.write_capacity)
-            .build();
+        let pt = Throughput {
+            read_capacity: options.read_capacity,
+            write_capacity: options.write_capacity,
+        }
+        .provisioned();
 
-        let result = self
-            .client
+        let indexes = secondary_indexes::<T>(&options);
+        let mut builder = self
+            .dynamo(&table)
             .create_table()
-            .table_name(T::table())
+            .table_name(&table)
             .attribute_definitions(pk)
             .attribute_definitions(sk)
             .key_schema(ks_pk)
-            .key_schema(ks_sk)
+            .key_schema(ks_sk);
+        for attr in secondary_index_attributes(&indexes) {
+            builder = builder.attribute_definitions(
+                AttributeDefinition::builder()
+                    .attribute_name(attr)
+                    .attribute_type(ScalarAttributeType::S)
+                    .build(),
+            );
+        }
+        for index in indexes {
+            let mut gsi = GlobalSecondaryIndex::builder()
+                .index_name(index.name)
+                .key_schema(
+                    KeySchemaElement::builder()
+                        .attribute_name(index.pk_attribute)
+                        .key_type(KeyType::Hash)
+                        .build(),
+                )
+                .projection(
+                    Projection::builder()
+                        .projection_type(ProjectionType::All)
+                        .build(),
+                )
+                .provisioned_throughput(pt.clone());
+            if let Some(sk_attribute) = index.sk_attribute {
+                gsi = gsi.key_schema(
+                    KeySchemaElement::builder()
+                        .attribute_name(sk_attribute)
+                        .key_type(KeyType::Range)
+                        .build(),
+                );
+            }
+            builder = builder.global_secondary_indexes(gsi.build());
+        }
+
+        let result = builder
             .provisioned_throughput(pt)
+            .set_table_class(options.table_class)
             .send()
             .await;
 
         if let Err(err) = result {
             let err: DynarustError = err.into();
             if let DynarustError::TableAlreadyExistsError(_) = err {
+                self.known_tables.insert(&table);
                 Ok(())
             } else {
                 Err(err)
             }
         } else {
+            self.known_tables.insert(&table);
             Ok(())
         }
     }
+    /// Updates the configuration of the table of resource T.
+    ///
+    /// # arguments
+    ///
+    /// * `options` - The changes that will be applied to the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     client.update_table::<Car>(UpdateTableOptions {
+    ///         index_throughput: HashMap::from([(
+    ///             dynarust::INVERTED_INDEX.to_string(),
+    ///             Throughput { read_capacity: 20, write_capacity: 5 },
+    ///         )]),
+    ///         ..Default::default()
+    ///     }).await?;
+    /// }
+    /// ```
+    pub async fn update_table<T: Resource>(
+        &self,
+        options: UpdateTableOptions,
+    ) -> Result<(), DynarustError> {
+        let mut builder = self
+            .dynamo(&T::table())
+            .update_table()
+            .table_name(T::table());
+
+        if let Some(throughput) = options.throughput {
+            builder = builder.provisioned_throughput(throughput.provisioned());
+        }
+
+        for (index_name, throughput) in options.index_throughput {
+            let action = UpdateGlobalSecondaryIndexAction::builder()
+                .index_name(index_name)
+                .provisioned_throughput(throughput.provisioned())
+                .build();
+            builder = builder.global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder().update(action).build(),
+            );
+        }
+
+        builder = builder.set_table_class(options.table_class);
+
+        builder.send().await?;
+        self.known_tables.forget_description(&T::table());
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::client::tests::TestResource;
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::client::{GSI1_INDEX, GSI1_PK, GSI1_SK, INVERTED_INDEX};
+
+    struct Plain;
+
+    impl Resource for Plain {
+        fn table() -> String {
+            "Plain".into()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (String::new(), String::new())
+        }
+    }
+
+    struct Indexed;
+
+    impl Resource for Indexed {
+        fn table() -> String {
+            format!("{}-indexed", *TABLE)
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (String::new(), String::new())
+        }
+
+        fn secondary_indexes() -> &'static [SecondaryIndex] {
+            &[SecondaryIndex::INVERTED, SecondaryIndex::GSI1]
+        }
+    }
 
     #[tokio::test]
     async fn test_no_connection_to_dynamo() {
@@ -115,6 +499,7 @@ mod tests {
             .create_table::<TestResource>(Some(CreateTableOptions {
                 read_capacity: 0,
                 write_capacity: 0,
+                ..Default::default()
             }))
             .await
             .unwrap_err();
@@ -123,4 +508,144 @@ mod tests {
             "Connection error: could not connect to dynamo"
         )
     }
+
+    #[tokio::test]
+    async fn updates_index_throughput() {
+        let client = Client::local().await;
+        client.create_table::<Indexed>(None).await.unwrap();
+        client
+            .update_table::<Indexed>(UpdateTableOptions {
+                index_throughput: HashMap::from([(
+                    INVERTED_INDEX.to_string(),
+                    Throughput {
+                        read_capacity: 10,
+                        write_capacity: 5,
+                    },
+                )]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn remembers_known_tables() {
+        let known = KnownTables::default();
+        assert!(!known.contains("Cars"));
+        known.insert("Cars");
+        assert!(known.contains("Cars"));
+        assert_eq!(known.description("Cars"), None);
+
+        let description = TableDescription::builder().table_name("Cars").build();
+        known.insert_description("Cars", description.clone());
+        known.insert("Cars");
+        assert_eq!(known.description("Cars"), Some(description));
+        known.forget_description("Cars");
+        assert!(known.contains("Cars"));
+        assert_eq!(known.description("Cars"), None);
+    }
+
+    #[test]
+    fn renders_table_class_in_sam_resource() {
+        let resource = create_sam_resource::<TestResource>(Some(CreateTableOptions {
+            table_class: Some(TableClass::StandardInfrequentAccess),
+            ..Default::default()
+        }));
+        assert!(resource.contains("\n    TableClass: STANDARD_INFREQUENT_ACCESS\n"));
+        assert!(!create_sam_resource::<TestResource>(None).contains("TableClass"));
+    }
+
+    #[test]
+    fn renders_cdk_props() {
+        let props = create_cdk_props::<Indexed>(Some(CreateTableOptions {
+            read_capacity: 3,
+            table_class: Some(TableClass::StandardInfrequentAccess),
+            ..Default::default()
+        }));
+        assert_eq!(props["tableName"], json!(Indexed::table()));
+        assert_eq!(props["partitionKey"], json!({ "name": PK, "type": "S" }));
+        assert_eq!(props["readCapacity"], json!(3));
+        assert_eq!(props["tableClass"], json!("STANDARD_INFREQUENT_ACCESS"));
+        assert_eq!(
+            props["globalSecondaryIndexes"][0]["partitionKey"],
+            json!({ "name": SK, "type": "S" })
+        );
+    }
+
+    #[test]
+    fn creates_builtin_indexes_only_when_asked() {
+        let index_names = |props: Value| -> Vec<Value> {
+            props["globalSecondaryIndexes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|index| index["indexName"].clone())
+                .collect()
+        };
+        assert!(index_names(create_cdk_props::<Plain>(None)).is_empty());
+        let resource = create_sam_resource::<Plain>(None);
+        assert!(!resource.contains("GlobalSecondaryIndexes"));
+        assert!(!resource.contains(GSI1_PK));
+        assert_eq!(
+            index_names(create_cdk_props::<Indexed>(None)),
+            vec![json!(INVERTED_INDEX), json!(GSI1_INDEX)]
+        );
+        let props = create_cdk_props::<Plain>(Some(CreateTableOptions {
+            indexes: vec![SecondaryIndex::INVERTED],
+            ..Default::default()
+        }));
+        assert_eq!(index_names(props), vec![json!(INVERTED_INDEX)]);
+        let resource = create_sam_resource::<Indexed>(None);
+        assert!(resource.contains(&format!(
+            "- AttributeName: {GSI1_SK}\n        AttributeType: S"
+        )));
+    }
+
+    #[test]
+    fn renders_secondary_indexes() {
+        let options = || CreateTableOptions {
+            indexes: vec![
+                SecondaryIndex::new("ByStatus", "StatusPk", None),
+                SecondaryIndex::new("ByOwner", SK, Some("OwnerSk")),
+            ],
+            ..Default::default()
+        };
+        let props = create_cdk_props::<Indexed>(Some(options()));
+        assert_eq!(
+            props["globalSecondaryIndexes"][2],
+            json!({
+                "indexName": "ByStatus",
+                "partitionKey": { "name": "StatusPk", "type": "S" },
+                "projectionType": "ALL",
+                "readCapacity": 5,
+                "writeCapacity": 5,
+            })
+        );
+        assert_eq!(
+            props["globalSecondaryIndexes"][3]["sortKey"],
+            json!({ "name": "OwnerSk", "type": "S" })
+        );
+
+        let resource = create_sam_resource::<Indexed>(Some(options()));
+        assert!(resource.contains("\n      - AttributeName: StatusPk\n        AttributeType: S\n"));
+        let definition = format!("- AttributeName: {SK}\n        AttributeType: S");
+        assert_eq!(resource.matches(&definition).count(), 1);
+        assert!(resource.contains("\n      - IndexName: ByOwner\n        KeySchema:\n"));
+    }
+
+    #[test]
+    fn renders_sam_stack_with_policy_and_outputs() {
+        let table = TestResource::table();
+        let stack = create_sam_stack::<TestResource>(None);
+        assert!(stack.starts_with(&format!("Resources:\n  {table}DynamoDBTable:\n")));
+        assert!(stack.contains(
+            "\n          - Effect: Allow\n            Action:\n              - dynamodb:GetItem\n"
+        ));
+        assert!(stack.contains(&format!(
+            "\n              - !Sub \"${{{table}DynamoDBTable.Arn}}/index/*\"\nOutputs:\n"
+        )));
+        assert!(stack.ends_with(&format!(
+            "  {table}CrudPolicyArn:\n    Value: !Ref {table}DynamoDBCrudPolicy\n"
+        )));
+    }
 }
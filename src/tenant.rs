@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::model::{AttributeValue, TransactWriteItem};
+
+use crate::client::{GSI1_PK, PK};
+use crate::{Client, DynarustError};
+
+/// Isolates the resources of a tenant that shares a physical table with other tenants by
+/// prefixing every PrimaryKey with the tenant id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenantScope {
+    tenant: String,
+}
+
+impl TenantScope {
+    /// A scope for the provided tenant id, which cannot contain '#', as it separates the tenant
+    /// id from the PrimaryKey and tenant "a#b" would otherwise own the keys of tenant "a".
+    pub fn new(tenant: impl Into<String>) -> Result<Self, DynarustError> {
+        let tenant = tenant.into();
+        if tenant.contains('#') {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "tenant id {tenant} cannot contain '#'"
+            )));
+        }
+        Ok(Self { tenant })
+    }
+
+    /// The tenant id of this scope.
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// Prefixes a PrimaryKey with the tenant id.
+    pub fn prefix(&self, pk: &str) -> String {
+        format!("{}#{pk}", self.tenant)
+    }
+
+    /// Strips the tenant id from a PrimaryKey, returning None if the key does not belong to
+    /// this tenant.
+    pub fn strip<'a>(&self, pk: &'a str) -> Option<&'a str> {
+        pk.strip_prefix(&self.tenant)?.strip_prefix('#')
+    }
+}
+
+impl Client {
+    /// Returns a client that operates only on the resources of the provided tenant. Every
+    /// PrimaryKey written or read through the returned client is transparently prefixed with the
+    /// tenant id, so tenants sharing a table never see each other's resources. Tenant ids with
+    /// '#' are rejected, and so are clients with external tables, whose keys cannot be scoped.
+    ///
+    /// # arguments
+    ///
+    /// * `tenant` - The tenant id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let tenant_client = client.scoped("tenant-42")?;
+    ///     tenant_client.create(&car).await?;
+    /// }
+    /// ```
+    pub fn scoped(&self, tenant: impl Into<String>) -> Result<Client, DynarustError> {
+        if let Some(table) = self.external_tables.keys().next() {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "External table {table} cannot be used by a tenant scoped client"
+            )));
+        }
+        Ok(Client {
+            tenant: Some(TenantScope::new(tenant)?),
+            ..self.clone()
+        })
+    }
+
+    /// The tenant scope of this client, if any.
+    pub fn tenant(&self) -> Option<&TenantScope> {
+        self.tenant.as_ref()
+    }
+
+    /// Whether a raw item read from a scan belongs to the tenant of the client, or true if the
+    /// client is not scoped.
+    pub(crate) fn owns_item(&self, item: &HashMap<String, AttributeValue>) -> bool {
+        match (item.get(PK), &self.tenant) {
+            (Some(AttributeValue::S(pk)), Some(tenant)) => tenant.strip(pk).is_some(),
+            (_, Some(_)) => false,
+            (_, None) => true,
+        }
+    }
+
+    pub(crate) fn scope_pk(&self, pk: String) -> String {
+        match &self.tenant {
+            Some(tenant) => tenant.prefix(&pk),
+            None => pk,
+        }
+    }
+
+    pub(crate) fn scope_key(&self, key: &mut HashMap<String, AttributeValue>) {
+        for attr in [PK, GSI1_PK] {
+            if let Some(AttributeValue::S(pk)) = key.get_mut(attr) {
+                *pk = self.scope_pk(std::mem::take(pk))
+            }
+        }
+    }
+
+    pub(crate) fn scope_transaction_item(&self, item: &mut TransactWriteItem) {
+        if self.tenant.is_none() {
+            return;
+        }
+        if let Some(key) = item.put.as_mut().and_then(|put| put.item.as_mut()) {
+            self.scope_key(key)
+        }
+        if let Some(update) = item.update.as_mut() {
+            if let Some(key) = update.key.as_mut() {
+                self.scope_key(key)
+            }
+            let placeholders: Vec<String> = match &update.expression_attribute_names {
+                Some(names) => names
+                    .iter()
+                    .filter(|(_, attr)| attr.as_str() == GSI1_PK)
+                    .map(|(name, _)| name.replacen('#', ":", 1))
+                    .collect(),
+                None => vec![],
+            };
+            if let Some(values) = update.expression_attribute_values.as_mut() {
+                for placeholder in placeholders {
+                    if let Some(AttributeValue::S(pk)) = values.get_mut(&placeholder) {
+                        *pk = self.scope_pk(std::mem::take(pk))
+                    }
+                }
+            }
+        }
+        if let Some(key) = item.delete.as_mut().and_then(|delete| delete.key.as_mut()) {
+            self.scope_key(key)
+        }
+        if let Some(key) = item
+            .condition_check
+            .as_mut()
+            .and_then(|check| check.key.as_mut())
+        {
+            self.scope_key(key)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use aws_sdk_dynamodb::model::AttributeValue;
+
+    use crate::client::tests::TestResource;
+    use crate::client::PK;
+    use crate::{
+        begin_transaction, transact_create, Client, DynarustError, ListOptions, Resource,
+        TenantScope,
+    };
+
+    #[test]
+    fn prefixes_and_strips_pk() {
+        let scope = TenantScope::new("tenant-42").unwrap();
+        assert_eq!(scope.prefix("pk"), "tenant-42#pk");
+        assert_eq!(scope.strip("tenant-42#pk"), Some("pk"));
+        assert_eq!(scope.strip("tenant-4#pk"), None);
+        assert_eq!(scope.strip("other#pk"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_tenants_that_collide() {
+        let err = TenantScope::new("a#b").unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+        let client = Client::local_on_port(12345).await;
+        assert!(client.scoped("a#b").is_err());
+
+        let tenant_a = client.scoped("a").unwrap();
+        let mut item = HashMap::from([(PK.to_string(), AttributeValue::S("a#b#x".into()))]);
+        assert!(tenant_a.owns_item(&item));
+        item.insert(PK.to_string(), AttributeValue::S("a".into()));
+        assert!(!tenant_a.owns_item(&item));
+    }
+
+    #[tokio::test]
+    async fn isolates_tenants() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let tenant_a = client.scoped("a").unwrap();
+        let tenant_b = client.scoped("b").unwrap();
+
+        let resource = TestResource {
+            pk: "isolates_tenants".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        tenant_a.create(&resource).await.unwrap();
+
+        let mut context = begin_transaction();
+        transact_create(&resource, &mut context).unwrap();
+        tenant_b.execute_transaction(context).await.unwrap();
+
+        assert_eq!(
+            tenant_a
+                .get::<TestResource>(resource.pk_sk())
+                .await
+                .unwrap(),
+            Some(resource.clone())
+        );
+        assert_eq!(
+            client.get::<TestResource>(resource.pk_sk()).await.unwrap(),
+            None
+        );
+
+        tenant_a
+            .delete::<TestResource>(resource.pk_sk())
+            .await
+            .unwrap();
+
+        let listed = tenant_b
+            .list::<TestResource>(resource.pk.clone(), &ListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.items, vec![resource]);
+    }
+}
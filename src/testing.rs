@@ -0,0 +1,95 @@
+use crate::DynarustError;
+
+/// Code with which dynamo reports the operations whose condition was not met.
+const CONDITIONAL_CHECK_FAILED: &str = "ConditionalCheckFailed";
+
+/// Asserts that an error is a write that was rejected because the condition of the operation
+/// at the provided position of the transaction was not met, panicking with the structured
+/// cancellation reasons otherwise. It inspects the error instead of comparing its message,
+/// so tests do not break when dynamo or the SDK word their messages differently. Operations
+/// that are not part of a transaction, like `Client::create`, are at position 0.
+///
+/// # arguments
+///
+/// * `err` - The error returned by the write.
+/// * `index` - Position in the transaction of the operation whose condition failed.
+///
+/// # Examples
+///
+/// ```
+/// async {
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_create(&order, &mut context)?;
+///     dynarust::transact_condition_check::<Customer>(
+///         customer.pk_sk(),
+///         dynarust::condition_check_exists(),
+///         &mut context,
+///     )?;
+///     let err = client.execute_transaction(context).await.unwrap_err();
+///     dynarust::assert_conditional_failure(&err, 1);
+/// }
+/// ```
+#[track_caller]
+pub fn assert_conditional_failure(err: &DynarustError, index: usize) {
+    match err {
+        DynarustError::TransactionCanceledError(operations) => {
+            let failed: Vec<_> = operations
+                .iter()
+                .filter(|operation| operation.code == CONDITIONAL_CHECK_FAILED)
+                .map(|operation| operation.index)
+                .collect();
+            assert!(
+                failed.contains(&index),
+                "expected the condition of operation {index} to fail, but the transaction was canceled because of: {operations:#?}"
+            );
+        }
+        DynarustError::ConditionalCheckFailedError(_) => assert_eq!(
+            index, 0,
+            "expected the condition of operation {index} to fail, but the failed operation was not part of a transaction"
+        ),
+        other => panic!("expected a failed condition, but got: {other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{assert_conditional_failure, CanceledOperation, DynarustError};
+
+    fn canceled(codes: &[&str]) -> DynarustError {
+        DynarustError::TransactionCanceledError(
+            codes
+                .iter()
+                .enumerate()
+                .map(|(index, code)| CanceledOperation {
+                    index,
+                    label: None,
+                    operation: "put".into(),
+                    table: "Table".into(),
+                    pk: "pk".into(),
+                    sk: index.to_string(),
+                    code: code.to_string(),
+                    message: None,
+                    item: None,
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn asserts_conditional_failures() {
+        assert_conditional_failure(&canceled(&["None", "ConditionalCheckFailed"]), 1);
+        assert_conditional_failure(&DynarustError::ConditionalCheckFailedError(None), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected the condition of operation 0 to fail")]
+    fn panics_on_other_operations() {
+        assert_conditional_failure(&canceled(&["None", "ConditionalCheckFailed"]), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a failed condition")]
+    fn panics_on_other_errors() {
+        assert_conditional_failure(&DynarustError::DynamoError("throttled".into()), 0);
+    }
+}
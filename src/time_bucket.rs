@@ -0,0 +1,296 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+
+use crate::{Client, DynarustError, ListManyOptions, Resource, SortKeyTimeFormat};
+
+/// Length of the time buckets in which a stream of resources is partitioned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketGranularity {
+    /// one bucket per hour, e.g. `2024-06-01T10`.
+    Hour,
+    /// one bucket per day, e.g. `2024-06-01`.
+    Day,
+    /// one bucket per month, e.g. `2024-06`.
+    Month,
+}
+
+impl BucketGranularity {
+    /// Start of the bucket that contains the timestamp.
+    fn truncate(&self, timestamp: &DateTime<Utc>) -> DateTime<Utc> {
+        let (year, month, day, hour) = match self {
+            BucketGranularity::Hour => (
+                timestamp.year(),
+                timestamp.month(),
+                timestamp.day(),
+                timestamp.hour(),
+            ),
+            BucketGranularity::Day => (timestamp.year(), timestamp.month(), timestamp.day(), 0),
+            BucketGranularity::Month => (timestamp.year(), timestamp.month(), 1, 0),
+        };
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0)
+            .single()
+            .unwrap_or(*timestamp)
+    }
+
+    /// Start of the bucket that follows the one starting at the timestamp.
+    fn next(&self, start: &DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            BucketGranularity::Hour => *start + Duration::hours(1),
+            BucketGranularity::Day => *start + Duration::days(1),
+            BucketGranularity::Month => {
+                let (year, month) = match start.month() {
+                    12 => (start.year() + 1, 1),
+                    month => (start.year(), month + 1),
+                };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+                    .single()
+                    .unwrap_or(*start + Duration::days(31))
+            }
+        }
+    }
+
+    fn format(&self, start: &DateTime<Utc>) -> String {
+        match self {
+            BucketGranularity::Hour => start.format("%Y-%m-%dT%H"),
+            BucketGranularity::Day => start.format("%Y-%m-%d"),
+            BucketGranularity::Month => start.format("%Y-%m"),
+        }
+        .to_string()
+    }
+}
+
+/// Partitioning of a high-write stream of resources, like events, in time buckets, each one of
+/// them split in several shards so that writes are spread across partitions. The PrimaryKeys
+/// look like `events#2024-06-01#shard3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeBuckets {
+    prefix: String,
+    granularity: BucketGranularity,
+    shards: u32,
+}
+
+impl TimeBuckets {
+    /// Buckets whose PrimaryKeys start with the prefix, split in the provided number of shards,
+    /// which is at least 1.
+    pub fn new(prefix: impl Into<String>, granularity: BucketGranularity, shards: u32) -> Self {
+        Self {
+            prefix: prefix.into(),
+            granularity,
+            shards: shards.max(1),
+        }
+    }
+
+    /// PrimaryKey of the shard of the bucket that contains the timestamp.
+    pub fn pk(&self, timestamp: &DateTime<Utc>, shard: u32) -> String {
+        let bucket = self
+            .granularity
+            .format(&self.granularity.truncate(timestamp));
+        format!("{}#{bucket}#shard{}", self.prefix, shard % self.shards)
+    }
+
+    /// A random shard. As `Resource::pk_sk` must return the same keys every time, the shard
+    /// of a resource is chosen once, when it is built, and stored along with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let event = Event { shard: BUCKETS.random_shard(), timestamp: Utc::now(), ..event };
+    ///
+    /// impl Resource for Event {
+    ///     fn table() -> String { "Events".into() }
+    ///     fn pk_sk(&self) -> (String, String) {
+    ///         (
+    ///             BUCKETS.pk(&self.timestamp, self.shard),
+    ///             SortKeyTimeFormat::Rfc3339.format(&self.timestamp).unwrap(),
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    pub fn random_shard(&self) -> u32 {
+        rand::thread_rng().gen_range(0..self.shards)
+    }
+
+    /// PrimaryKeys of every shard of every bucket that overlaps the time window, both ends
+    /// included, in chronological order.
+    pub fn pks_in_range(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Vec<String> {
+        let mut pks = vec![];
+        let mut bucket = self.granularity.truncate(start);
+        while bucket <= *end {
+            for shard in 0..self.shards {
+                pks.push(self.pk(&bucket, shard));
+            }
+            bucket = self.granularity.next(&bucket);
+        }
+        pks
+    }
+}
+
+impl Client {
+    /// Reads every resource of a time bucketed stream whose sort key is a timestamp between
+    /// `start` and `end`, both inclusive, querying every shard of every bucket in the window
+    /// and merging the results by sort key. All the pages of each shard are read, the limit of
+    /// the listing options being the size of each page. The sort keys of the resources must
+    /// have been formatted with the same `format`.
+    ///
+    /// # arguments
+    ///
+    /// * `buckets` - Time buckets in which the resources are partitioned.
+    /// * `start` - Start of the time window.
+    /// * `end` - End of the time window.
+    /// * `format` - Format in which timestamps are encoded in the sort key.
+    /// * `options` - listing options, `sorted` is ignored as the results are always sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let events = client
+    ///         .list_time_buckets::<Event>(
+    ///             &BUCKETS,
+    ///             Utc::now() - Duration::hours(6),
+    ///             Utc::now(),
+    ///             SortKeyTimeFormat::Rfc3339,
+    ///             &ListManyOptions::default(),
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn list_time_buckets<T: Resource + DeserializeOwned>(
+        &self,
+        buckets: &TimeBuckets,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        format: SortKeyTimeFormat,
+        options: &ListManyOptions,
+    ) -> Result<Vec<T>, DynarustError> {
+        if options.list.cursor.is_some() {
+            return Err(DynarustError::InvalidRequestError(
+                "Cursors are not supported when listing time buckets".into(),
+            ));
+        }
+        let shards: Vec<Vec<T>> = stream::iter(buckets.pks_in_range(&start, &end))
+            .map(|pk| async move {
+                let mut list = options.list.clone();
+                let mut items = vec![];
+                loop {
+                    let page = self
+                        .list_in_range::<T>(pk.clone(), start, end, format, &list)
+                        .await?;
+                    items.extend(page.items);
+                    match page.next {
+                        Some(next) => list.cursor = Some(next),
+                        None => return Ok::<_, DynarustError>(items),
+                    }
+                }
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let mut items: Vec<T> = shards.into_iter().flatten().collect();
+        items.sort_by(|a, b| match options.list.sort_desc {
+            true => b.pk_sk().1.cmp(&a.pk_sk().1),
+            false => a.pk_sk().1.cmp(&b.pk_sk().1),
+        });
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use serde::{Deserialize, Serialize};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{
+        BucketGranularity, Client, ListManyOptions, ListOptions, Resource, SortKeyTimeFormat,
+        TimeBuckets,
+    };
+
+    #[test]
+    fn computes_bucket_pks() {
+        let buckets = TimeBuckets::new("events", BucketGranularity::Day, 2);
+        let t = Utc.with_ymd_and_hms(2024, 6, 1, 10, 30, 0).unwrap();
+        assert_eq!(buckets.pk(&t, 3), "events#2024-06-01#shard1");
+        assert!(buckets.random_shard() < 2);
+
+        let end = Utc.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap();
+        assert_eq!(
+            buckets.pks_in_range(&t, &end),
+            vec![
+                "events#2024-06-01#shard0",
+                "events#2024-06-01#shard1",
+                "events#2024-06-02#shard0",
+                "events#2024-06-02#shard1",
+            ]
+        );
+
+        let buckets = TimeBuckets::new("events", BucketGranularity::Month, 1);
+        let start = Utc.with_ymd_and_hms(2023, 12, 31, 0, 0, 0).unwrap();
+        assert_eq!(
+            buckets.pks_in_range(&start, &(start + Duration::days(1))),
+            vec!["events#2023-12#shard0", "events#2024-01#shard0"]
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Event {
+        timestamp: DateTime<Utc>,
+        shard: u32,
+    }
+
+    fn buckets() -> TimeBuckets {
+        TimeBuckets::new("lists_time_buckets", BucketGranularity::Hour, 3)
+    }
+
+    impl Resource for Event {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (
+                buckets().pk(&self.timestamp, self.shard),
+                SortKeyTimeFormat::Rfc3339.format(&self.timestamp).unwrap(),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_time_buckets() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).unwrap();
+        let mut events = vec![];
+        for i in 0..6 {
+            let event = Event {
+                timestamp: start + Duration::minutes(25 * i),
+                shard: buckets().random_shard(),
+            };
+            client.force_create(&event).await.unwrap();
+            events.push(event);
+        }
+
+        let options = ListManyOptions {
+            list: ListOptions {
+                limit: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let listed = client
+            .list_time_buckets::<Event>(
+                &buckets(),
+                start + Duration::minutes(10),
+                start + Duration::minutes(100),
+                SortKeyTimeFormat::Rfc3339,
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed, events[1..5]);
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::DynarustError;
+
+/// Format for encoding timestamps in sort keys. All of them produce fixed width strings, so
+/// sorting the keys lexicographically is the same as sorting them chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKeyTimeFormat {
+    /// RFC3339 in UTC with millisecond precision, e.g. `2023-03-01T10:00:00.000Z`.
+    Rfc3339,
+    /// Seconds since the unix epoch, zero padded to 12 digits.
+    EpochSeconds,
+    /// Milliseconds since the unix epoch, zero padded to 15 digits.
+    EpochMillis,
+}
+
+impl SortKeyTimeFormat {
+    /// Formats a timestamp for being used as a sort key. The epoch based formats return a
+    /// `DynarustError::InvalidRequestError` for timestamps before the unix epoch or too far in
+    /// the future to fit their width, as those keys would not sort chronologically.
+    pub fn format(&self, timestamp: &DateTime<Utc>) -> Result<String, DynarustError> {
+        let (value, width) = match self {
+            SortKeyTimeFormat::Rfc3339 => {
+                return Ok(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true))
+            }
+            SortKeyTimeFormat::EpochSeconds => (timestamp.timestamp(), 12),
+            SortKeyTimeFormat::EpochMillis => (timestamp.timestamp_millis(), 15),
+        };
+        let formatted = format!("{value:0width$}");
+        if value < 0 || formatted.len() > width {
+            return Err(DynarustError::InvalidRequestError(format!(
+                "Timestamp {timestamp} does not fit the {self:?} sort key format"
+            )));
+        }
+        Ok(formatted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::SortKeyTimeFormat;
+
+    #[test]
+    fn formats_fixed_width_keys() {
+        let t = Utc.with_ymd_and_hms(2023, 3, 1, 10, 0, 0).unwrap();
+        assert_eq!(
+            SortKeyTimeFormat::Rfc3339.format(&t).unwrap(),
+            "2023-03-01T10:00:00.000Z"
+        );
+        assert_eq!(
+            SortKeyTimeFormat::EpochSeconds.format(&t).unwrap(),
+            "001677664800"
+        );
+        assert_eq!(
+            SortKeyTimeFormat::EpochMillis.format(&t).unwrap(),
+            "001677664800000"
+        );
+    }
+
+    #[test]
+    fn rejects_unsortable_epoch_keys() {
+        let before_epoch = Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 59).unwrap();
+        assert!(SortKeyTimeFormat::EpochSeconds
+            .format(&before_epoch)
+            .is_err());
+        assert!(SortKeyTimeFormat::EpochMillis
+            .format(&before_epoch)
+            .is_err());
+        assert!(SortKeyTimeFormat::Rfc3339.format(&before_epoch).is_ok());
+
+        let far_future = Utc.with_ymd_and_hms(40000, 1, 1, 0, 0, 0).unwrap();
+        assert!(SortKeyTimeFormat::EpochSeconds.format(&far_future).is_err());
+    }
+}
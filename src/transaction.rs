@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::error::{TransactWriteItemsError, TransactWriteItemsErrorKind};
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity, TransactWriteItem};
+use aws_sdk_dynamodb::types::SdkError;
+use futures::future::join_all;
+
+use crate::client::{PK, SK};
+use crate::stats::Operation;
+use crate::{CanceledOperation, Client, DynarustError};
+
+/// The operations that will be executed atomically in a transaction. It keeps track of the
+/// items referenced by every operation, as dynamo rejects transactions with more than one
+/// operation on the same item.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionContext {
+    items: Vec<TransactWriteItem>,
+    labels: Vec<Option<String>>,
+    keys: HashMap<(String, String, String), &'static str>,
+}
+
+impl TransactionContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of operations in the transaction.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Labels the operation that was added last, so it can be told apart in the
+    /// `DynarustError::TransactionCanceledError` returned if the transaction is canceled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let mut context = dynarust::begin_transaction();
+    ///     dynarust::transact_create(&user, &mut context)?;
+    ///     context.label("create user");
+    ///     dynarust::transact_update(&account, json!({ "users": 2 }), &mut context)?;
+    ///     context.label("update account");
+    ///     if let Err(DynarustError::TransactionCanceledError(operations)) =
+    ///         client.execute_transaction(context).await
+    ///     {
+    ///         println!("{:?} failed", operations[0].label);
+    ///     }
+    /// }
+    /// ```
+    pub fn label(&mut self, label: impl Into<String>) -> &mut Self {
+        if let Some(last) = self.labels.last_mut() {
+            *last = Some(label.into());
+        }
+        self
+    }
+
+    /// Adds an operation to the transaction, failing if there is already another operation
+    /// on the same item.
+    pub(crate) fn push(&mut self, item: TransactWriteItem) -> Result<(), DynarustError> {
+        let (operation, table, key) = describe_item(&item)?;
+
+        let attr = |name: &str| match key.as_ref().and_then(|key| key.get(name)) {
+            Some(AttributeValue::S(v)) => v.clone(),
+            _ => String::new(),
+        };
+        let key = (table.to_string(), attr(PK), attr(SK));
+
+        if let Some(first) = self.keys.get(&key) {
+            let (table, pk, sk) = key;
+            return Err(DynarustError::DuplicateTransactionItemError {
+                table,
+                pk,
+                sk,
+                first: first.to_string(),
+                second: operation.to_string(),
+            });
+        }
+
+        self.keys.insert(key, operation);
+        self.items.push(item);
+        self.labels.push(None);
+        Ok(())
+    }
+
+    pub(crate) fn items(&self) -> &[TransactWriteItem] {
+        &self.items
+    }
+
+    pub(crate) fn labels(&self) -> &[Option<String>] {
+        &self.labels
+    }
+
+    pub(crate) fn into_items(self) -> Vec<TransactWriteItem> {
+        self.items
+    }
+
+    /// Describes every operation as a canceled one, to be completed with the reason reported
+    /// by dynamo if the transaction is canceled.
+    pub(crate) fn operations(&self) -> Vec<CanceledOperation> {
+        let mut operations = vec![];
+        for (index, (item, label)) in self.items.iter().zip(self.labels.iter()).enumerate() {
+            let Ok((operation, table, key)) = describe_item(item) else {
+                continue;
+            };
+            let attr = |name: &str| match key.as_ref().and_then(|key| key.get(name)) {
+                Some(AttributeValue::S(v)) => v.clone(),
+                _ => String::new(),
+            };
+            operations.push(CanceledOperation {
+                index,
+                label: label.clone(),
+                operation: operation.to_string(),
+                table: table.to_string(),
+                pk: attr(PK),
+                sk: attr(SK),
+                code: String::new(),
+                message: None,
+                item: None,
+            });
+        }
+        operations
+    }
+
+    /// Splits the transaction into one transaction per pk, in order of first appearance.
+    pub(crate) fn split_per_pk(self) -> Result<Vec<(String, TransactionContext)>, DynarustError> {
+        let mut groups: Vec<(String, TransactionContext)> = vec![];
+        for (item, label) in self.items.into_iter().zip(self.labels) {
+            let (_, _, key) = describe_item(&item)?;
+            let pk = match key.as_ref().and_then(|key| key.get(PK)) {
+                Some(AttributeValue::S(pk)) => pk.clone(),
+                _ => String::new(),
+            };
+            let index = match groups.iter().position(|(group_pk, _)| *group_pk == pk) {
+                Some(index) => index,
+                None => {
+                    groups.push((pk, TransactionContext::new()));
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index].1;
+            group.push(item)?;
+            if let Some(label) = label {
+                group.label(label);
+            }
+        }
+        Ok(groups)
+    }
+}
+
+/// Maps the error of a transaction to a `DynarustError::TransactionCanceledError` with the
+/// operations that made dynamo cancel it, if that was the reason of the failure.
+pub(crate) fn transaction_error(
+    err: SdkError<TransactWriteItemsError>,
+    mut operations: Vec<CanceledOperation>,
+) -> DynarustError {
+    let SdkError::ServiceError(service_error) = err else {
+        return err.into();
+    };
+    let err = service_error.into_err();
+    let TransactWriteItemsErrorKind::TransactionCanceledException(canceled) = &err.kind else {
+        return DynarustError::DynamoError(err.message().unwrap_or("unknown error").to_string());
+    };
+    let reasons = canceled.cancellation_reasons().unwrap_or_default();
+    let mut failed = vec![];
+    for (operation, reason) in operations.iter_mut().zip(reasons) {
+        match reason.code() {
+            None | Some("None") => continue,
+            Some(code) => operation.code = code.to_string(),
+        }
+        operation.message = reason.message().map(str::to_string);
+        operation.item = reason
+            .item()
+            .and_then(|item| Client::item_as_value(item).ok());
+        failed.push(operation.clone());
+    }
+    match failed.is_empty() {
+        true => DynarustError::DynamoError(err.message().unwrap_or("unknown error").to_string()),
+        false => DynarustError::TransactionCanceledError(failed),
+    }
+}
+
+/// Returns the operation, the table and the key of a transaction item.
+#[allow(clippy::type_complexity)]
+pub(crate) fn describe_item(
+    item: &TransactWriteItem,
+) -> Result<(&'static str, &str, &Option<HashMap<String, AttributeValue>>), DynarustError> {
+    let (operation, table, key) = if let Some(put) = &item.put {
+        ("put", &put.table_name, &put.item)
+    } else if let Some(update) = &item.update {
+        ("update", &update.table_name, &update.key)
+    } else if let Some(delete) = &item.delete {
+        ("delete", &delete.table_name, &delete.key)
+    } else if let Some(check) = &item.condition_check {
+        ("condition check", &check.table_name, &check.key)
+    } else {
+        return Err(DynarustError::InvalidRequestError(
+            "Empty transaction item".into(),
+        ));
+    };
+    Ok((operation, table.as_deref().unwrap_or_default(), key))
+}
+
+impl Client {
+    /// Executes the operations of a transaction context as one transaction per pk, all of them
+    /// concurrently. Operations on the same pk are applied atomically, but the transactions of
+    /// different pks succeed or fail independently. This is useful when atomicity is only
+    /// needed within a partition, and lifts the limit of 100 operations per transaction to 100
+    /// operations per pk.
+    ///
+    /// If some of the transactions fail, a `DynarustError::PartialTransactionError` is
+    /// returned with the pks whose transaction was committed and the ones that failed, along
+    /// with their error.
+    ///
+    /// # arguments
+    ///
+    /// * `transaction_context` - The transaction context with the operations to execute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let mut context = dynarust::begin_transaction();
+    ///     for order in orders.iter() {
+    ///         dynarust::transact_create(order, &mut context)?;
+    ///     }
+    ///     client.execute_transaction_per_pk(context).await?;
+    /// }
+    /// ```
+    pub async fn execute_transaction_per_pk(
+        &self,
+        transaction_context: TransactionContext,
+    ) -> Result<(), DynarustError> {
+        let groups = transaction_context.split_per_pk()?;
+        let results = join_all(
+            groups
+                .into_iter()
+                .map(|(pk, context)| async move { (pk, self.execute_transaction(context).await) }),
+        )
+        .await;
+
+        let mut committed = vec![];
+        let mut failed = vec![];
+        for (pk, result) in results {
+            match result {
+                Ok(()) => committed.push(pk),
+                Err(err) => failed.push((pk, err)),
+            }
+        }
+        match failed.is_empty() {
+            true => Ok(()),
+            false => Err(DynarustError::PartialTransactionError { committed, failed }),
+        }
+    }
+
+    /// Executes a transaction with a single item whose key is already scoped. If its condition
+    /// check fails, a `DynarustError::ConditionalCheckFailedError` is returned carrying the
+    /// item as it was stored in dynamo, if the operation requested it. The consumed capacity is
+    /// recorded for the provided operation.
+    pub(crate) async fn execute_single_item(
+        &self,
+        operation: Operation,
+        mut item: TransactWriteItem,
+    ) -> Result<(), DynarustError> {
+        self.recode_transaction_item(&mut item)?;
+        self.intercept_transaction_item(&mut item)?;
+        self.externalize_transaction_item(&mut item);
+        let result = self
+            .dynamo_for_items(std::slice::from_ref(&item))?
+            .transact_write_items()
+            .transact_items(item)
+            .set_return_item_collection_metrics(self.return_collection_metrics())
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => {
+                self.stats
+                    .add_all_capacity(operation, output.consumed_capacity());
+                self.report_all_collection_metrics(output.item_collection_metrics());
+                Ok(())
+            }
+            Err(SdkError::ServiceError(err)) => {
+                let err = err.into_err();
+                if let TransactWriteItemsErrorKind::TransactionCanceledException(canceled) =
+                    &err.kind
+                {
+                    let reason = canceled.cancellation_reasons().and_then(|r| r.first());
+                    if let Some(reason) = reason {
+                        if reason.code() == Some("ConditionalCheckFailed") {
+                            let item = match reason.item() {
+                                Some(item) => Some(self.decode_item(item)?),
+                                None => None,
+                            };
+                            return Err(DynarustError::ConditionalCheckFailedError(item));
+                        }
+                    }
+                }
+                Err(DynarustError::DynamoError(
+                    err.message().unwrap_or("unknown error").to_string(),
+                ))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::{
+        begin_transaction, condition_check_exists, transact_condition_check, transact_create,
+        transact_update, Client, DynarustError,
+    };
+
+    #[tokio::test]
+    async fn executes_empty_transactions() {
+        let client = Client::local_on_port(1).await;
+        client
+            .execute_transaction(begin_transaction())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn splits_transactions_per_pk() {
+        let mut context = begin_transaction();
+        for (pk, sk) in [("a", "1"), ("b", "1"), ("a", "2")] {
+            let resource = TestResource {
+                pk: pk.to_string(),
+                sk: sk.to_string(),
+                ..Default::default()
+            };
+            transact_create(&resource, &mut context).unwrap();
+        }
+        let groups = context.split_per_pk().unwrap();
+        let sizes: Vec<_> = groups
+            .iter()
+            .map(|(pk, c)| (pk.as_str(), c.len()))
+            .collect();
+        assert_eq!(sizes, vec![("a", 2), ("b", 1)]);
+    }
+
+    #[tokio::test]
+    async fn executes_transactions_per_pk() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let existing = TestResource {
+            pk: "executes_transactions_per_pk_2".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&existing).await.unwrap();
+
+        let mut context = begin_transaction();
+        for pk in [
+            "executes_transactions_per_pk_1",
+            "executes_transactions_per_pk_2",
+        ] {
+            for sk in ["1", "2"] {
+                let resource = TestResource {
+                    pk: pk.to_string(),
+                    sk: sk.to_string(),
+                    ..Default::default()
+                };
+                transact_create(&resource, &mut context).unwrap();
+            }
+        }
+        let err = client
+            .execute_transaction_per_pk(context)
+            .await
+            .unwrap_err();
+        let DynarustError::PartialTransactionError { committed, failed } = err else {
+            panic!("unexpected error {err}")
+        };
+        assert_eq!(committed, vec!["executes_transactions_per_pk_1"]);
+        assert_eq!(failed[0].0, "executes_transactions_per_pk_2");
+        assert!(matches!(
+            failed[0].1,
+            DynarustError::TransactionCanceledError(_)
+        ));
+
+        let get = |pk: &str| client.get::<TestResource>((pk.to_string(), "2".to_string()));
+        assert!(get("executes_transactions_per_pk_1")
+            .await
+            .unwrap()
+            .is_some());
+        assert!(get("executes_transactions_per_pk_2")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn labels_canceled_operations() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "labels_canceled_operations".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let mut context = begin_transaction();
+        transact_update(&resource, json!({ "int": 1 }), &mut context).unwrap();
+        context.label("update existing");
+        let created = TestResource {
+            sk: "2".to_string(),
+            ..resource.clone()
+        };
+        transact_create(&created, &mut context).unwrap();
+        transact_condition_check::<TestResource>(
+            ("labels_canceled_operations".into(), "3".into()),
+            condition_check_exists(),
+            &mut context,
+        )
+        .unwrap();
+        context.label("check missing");
+        assert_eq!(context.operations()[1].label, None);
+
+        let err = client.execute_transaction(context).await.unwrap_err();
+        let DynarustError::TransactionCanceledError(operations) = err else {
+            panic!("unexpected error {err}")
+        };
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].index, 2);
+        assert_eq!(operations[0].label.as_deref(), Some("check missing"));
+        assert_eq!(operations[0].sk, "3");
+        assert_eq!(operations[0].code, "ConditionalCheckFailed");
+    }
+
+    #[test]
+    fn rejects_duplicate_items() {
+        let resource = TestResource {
+            pk: "rejects_duplicate_items".to_string(),
+            sk: "1".to_string(),
+            ..Default::default()
+        };
+        let other = TestResource {
+            sk: "2".to_string(),
+            ..resource.clone()
+        };
+
+        let mut context = begin_transaction();
+        transact_create(&resource, &mut context).unwrap();
+        transact_condition_check::<TestResource>(
+            ("rejects_duplicate_items".into(), "2".into()),
+            condition_check_exists(),
+            &mut context,
+        )
+        .unwrap();
+        let err = transact_update(&resource, json!({ "int": 1 }), &mut context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Transaction contains more than one operation on item (rejects_duplicate_items, 1) of table {}: put and update", *TABLE)
+        );
+        let err = transact_create(&other, &mut context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Transaction contains more than one operation on item (rejects_duplicate_items, 2) of table {}: condition check and put", *TABLE)
+        );
+        assert_eq!(context.len(), 2);
+    }
+}
@@ -1,15 +1,393 @@
 use std::collections::HashMap;
 
-use aws_sdk_dynamodb::model::{update, AttributeValue, TransactWriteItem};
+use aws_sdk_dynamodb::model::{
+    update, AttributeValue, ReturnConsumedCapacity, ReturnValue,
+    ReturnValuesOnConditionCheckFailure, TransactWriteItem,
+};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::client::{PK, SK};
-use crate::condition_check::{condition_check_exists, ConditionCheckInfo};
-use crate::{Client, DynarustError, Resource};
+use crate::audit::audit_image;
+use crate::client::{GSI1_PK, GSI1_SK, PK, SK};
+use crate::condition_check::{condition_check, condition_check_exists, ConditionCheckInfo};
+use crate::stats::Operation;
+use crate::{
+    AuditOperation, Client, Condition, DynamoOperator, DynamoStore, DynarustError, Resource,
+    TransactionContext,
+};
+
+/// Which image of the updated attributes is returned by `update_returning`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdatedAttributes {
+    /// the updated attributes as they are after the update.
+    New,
+    /// the updated attributes as they were before the update.
+    Old,
+}
+
+impl From<UpdatedAttributes> for ReturnValue {
+    fn from(attributes: UpdatedAttributes) -> Self {
+        match attributes {
+            UpdatedAttributes::New => ReturnValue::UpdatedNew,
+            UpdatedAttributes::Old => ReturnValue::UpdatedOld,
+        }
+    }
+}
+
+/// Attributes written and removed by an update operation, which are all applied atomically in
+/// a single update expression.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// async {
+///     let update = ItemUpdate::default()
+///         .with_set("status", json!("shipped"))
+///         .with_remove("draft")
+///         .with_add("shipments", json!(1));
+///     let order: Order = client.patch(order.pk_sk(), update).await?;
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemUpdate {
+    /// Attributes that will be set to the provided value.
+    pub set: Vec<(String, Value)>,
+    /// Attributes that will be removed from the item.
+    pub remove: Vec<String>,
+    /// Numeric attributes to which the provided number is added, missing ones start at zero.
+    /// With a codec that stores arrays as sets, set attributes to which the provided elements
+    /// are added.
+    pub add: Vec<(String, Value)>,
+    /// Set attributes from which the provided elements are removed, which needs a codec that
+    /// stores arrays as sets.
+    pub delete: Vec<(String, Value)>,
+    /// Elements of list attributes that will be set to the provided value, by position.
+    /// Positions past the end of the list append the element at the end.
+    pub set_elements: Vec<(String, usize, Value)>,
+    /// Elements of list attributes that will be removed, by position, shifting the following
+    /// ones back. Positions past the end of the list are ignored.
+    pub remove_elements: Vec<(String, usize)>,
+}
+
+impl ItemUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+            && self.remove.is_empty()
+            && self.add.is_empty()
+            && self.delete.is_empty()
+            && self.set_elements.is_empty()
+            && self.remove_elements.is_empty()
+    }
+
+    /// Returns the update also setting the attribute to the value.
+    pub fn with_set(mut self, attr: impl Into<String>, value: Value) -> Self {
+        self.set.push((attr.into(), value));
+        self
+    }
+
+    /// Returns the update also removing the attribute.
+    pub fn with_remove(mut self, attr: impl Into<String>) -> Self {
+        self.remove.push(attr.into());
+        self
+    }
+
+    /// Returns the update also adding the value to the attribute.
+    pub fn with_add(mut self, attr: impl Into<String>, value: Value) -> Self {
+        self.add.push((attr.into(), value));
+        self
+    }
+
+    /// Returns the update also deleting the elements from the set attribute.
+    pub fn with_delete(mut self, attr: impl Into<String>, elements: Value) -> Self {
+        self.delete.push((attr.into(), elements));
+        self
+    }
+
+    /// Returns the update also setting the element at a position of the list attribute.
+    pub fn with_set_element(mut self, attr: impl Into<String>, index: usize, value: Value) -> Self {
+        self.set_elements.push((attr.into(), index, value));
+        self
+    }
+
+    /// Returns the update also removing the element at a position of the list attribute.
+    pub fn with_remove_element(mut self, attr: impl Into<String>, index: usize) -> Self {
+        self.remove_elements.push((attr.into(), index));
+        self
+    }
+
+    /// Attributes touched by the update, in any of its clauses.
+    fn attributes(&self) -> impl Iterator<Item = &String> {
+        let operands = self.set.iter().chain(&self.add).chain(&self.delete);
+        operands
+            .map(|(k, _)| k)
+            .chain(&self.remove)
+            .chain(self.set_elements.iter().map(|(k, _, _)| k))
+            .chain(self.remove_elements.iter().map(|(k, _)| k))
+    }
+
+    /// Builds the update expression along with its attribute names and values.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn expression(
+        &self,
+    ) -> Result<
+        (
+            String,
+            HashMap<String, String>,
+            HashMap<String, AttributeValue>,
+        ),
+        DynarustError,
+    > {
+        let mut names = HashMap::new();
+        let mut values = HashMap::new();
+        let mut clauses = vec![];
+
+        let mut set = vec![];
+        for (i, (k, v)) in self.set.iter().enumerate() {
+            let name = format!("#updateAttr{}", i);
+            let value = format!(":updateAttr{}", i);
+            set.push(format!("{} = {}", name, value));
+            names.insert(name, k.clone());
+            values.insert(value, Client::value2attr(v)?);
+        }
+        for (i, (k, index, v)) in self.set_elements.iter().enumerate() {
+            let name = format!("#setElement{i}");
+            let value = format!(":setElement{i}");
+            set.push(format!("{name}[{index}] = {value}"));
+            names.insert(name, k.clone());
+            values.insert(value, Client::value2attr(v)?);
+        }
+        if !set.is_empty() {
+            clauses.push(format!("set {}", set.join(", ")))
+        }
+
+        let mut remove = vec![];
+        for (i, k) in self.remove.iter().enumerate() {
+            let name = format!("#removeAttr{}", i);
+            remove.push(name.clone());
+            names.insert(name, k.clone());
+        }
+        for (i, (k, index)) in self.remove_elements.iter().enumerate() {
+            let name = format!("#removeElement{i}");
+            remove.push(format!("{name}[{index}]"));
+            names.insert(name, k.clone());
+        }
+        if !remove.is_empty() {
+            clauses.push(format!("remove {}", remove.join(", ")))
+        }
+
+        for (verb, operands) in [("add", &self.add), ("delete", &self.delete)] {
+            let mut clause = vec![];
+            for (i, (k, v)) in operands.iter().enumerate() {
+                let name = format!("#{verb}Attr{i}");
+                let value = format!(":{verb}Attr{i}");
+                clause.push(format!("{name} {value}"));
+                names.insert(name, k.clone());
+                values.insert(value, Client::value2attr(v)?);
+            }
+            if !clause.is_empty() {
+                clauses.push(format!("{verb} {}", clause.join(", ")))
+            }
+        }
+
+        Ok((clauses.join(" "), names, values))
+    }
+}
+
+/// Applies the update request to the resource and runs the `before_save` hook on the result.
+/// Returns the updated resource along with the attributes that need to be written, which are
+/// the ones in the request plus the ones modified along with them or by the hook and the
+/// index keys.
+pub(crate) fn apply_update_request<T: Resource + Serialize + DeserializeOwned>(
+    resource: &T,
+    request: Value,
+) -> Result<(T, ItemUpdate), DynarustError> {
+    let mut object = Client::resource_as_object(resource)?;
+    let original = object.clone();
+
+    let request: HashMap<String, Value> = serde_json::from_value(request)?;
+
+    for field in T::immutable_fields() {
+        if let Some(new_v) = request.get(*field) {
+            if original.get(*field) != Some(new_v) {
+                return Err(DynarustError::InvalidRequestError(format!(
+                    "Cannot update immutable field {field}"
+                )));
+            }
+        }
+    }
+
+    for (k, new_v) in request.iter() {
+        object.insert(k.clone(), new_v.clone());
+    }
+    let updated: T = serde_json::from_value(Value::Object(object))?;
+
+    if request.is_empty() {
+        return Ok((updated, ItemUpdate::default()));
+    }
+
+    let updated = updated.before_save().unwrap_or(updated);
+
+    if updated.pk_sk() != resource.pk_sk() {
+        return Err(DynarustError::InvalidRequestError(
+            "Cannot update PK and/or SK".into(),
+        ));
+    }
+
+    let saved = Client::resource_as_object(&updated)?;
+
+    let mut update = ItemUpdate::default();
+    for (k, v) in saved.iter() {
+        if !request.contains_key(k) && original.get(k) != Some(v) {
+            update.set.push((k.clone(), v.clone()))
+        }
+    }
+    for (k, v) in request {
+        let v = saved.get(&k).cloned().unwrap_or(v);
+        update.set.push((k, v))
+    }
+
+    if let Some((gsi1_pk, gsi1_sk)) = updated.gsi1_pk_sk() {
+        update
+            .set
+            .push((GSI1_PK.to_string(), Value::String(gsi1_pk)));
+        update
+            .set
+            .push((GSI1_SK.to_string(), Value::String(gsi1_sk)));
+    } else if resource.gsi1_pk_sk().is_some() {
+        update.remove.push(GSI1_PK.to_string());
+        update.remove.push(GSI1_SK.to_string());
+    }
+    let index_keys = updated.index_keys();
+    for (attr, _) in resource.index_keys() {
+        if !saved.contains_key(attr) && !index_keys.iter().any(|(a, _)| *a == attr) {
+            update.remove.push(attr.to_string());
+        }
+    }
+    for (attr, value) in index_keys {
+        update.set.retain(|(k, _)| k != attr);
+        update.set.push((attr.to_string(), Value::String(value)));
+    }
+
+    let (set, skipped): (Vec<_>, Vec<_>) = update
+        .set
+        .into_iter()
+        .partition(|(_, v)| !T::write_policy().skips(v));
+    update.set = set;
+    update.remove.extend(skipped.into_iter().map(|(k, _)| k));
+
+    Ok((updated, update))
+}
+
+pub(crate) async fn update_resource<S, T>(
+    store: &S,
+    resource: &T,
+    request: Value,
+    condition_checks: Vec<ConditionCheckInfo>,
+) -> Result<T, DynarustError>
+where
+    S: DynamoStore + ?Sized,
+    T: Resource + Serialize + DeserializeOwned,
+{
+    let (updated, update) = apply_update_request(resource, request)?;
+
+    if update.is_empty() {
+        return Ok(updated);
+    }
+
+    let condition_check = condition_check_exists().merge(condition_checks);
+
+    store
+        .update_item(&T::table(), resource.pk_sk(), update, condition_check)
+        .await?;
+
+    Ok(updated)
+}
+
+pub(crate) async fn update_item(
+    client: &Client,
+    table: &str,
+    (pk, sk): (String, String),
+    mut update: ItemUpdate,
+    condition_check: ConditionCheckInfo,
+    return_values: Option<ReturnValue>,
+) -> Result<Option<Value>, DynarustError> {
+    client.scope_update(&mut update);
+    let (pk_attr, sk_attr) = client.key_names(table);
+    update.set.retain(|(k, _)| k != pk_attr && k != sk_attr);
+    let (mut expression, mut names, mut values) = update.expression()?;
+    client.recode_attributes(&mut values)?;
+    client.intercept_update(table, &mut expression, &mut names, &mut values)?;
+
+    let mut builder = client
+        .dynamo(table)
+        .update_item()
+        .table_name(table)
+        .key(pk_attr, AttributeValue::S(client.scope_pk(pk)))
+        .key(sk_attr, AttributeValue::S(sk))
+        .update_expression(expression)
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()))
+        .set_return_values(return_values)
+        .set_return_item_collection_metrics(client.return_collection_metrics())
+        .return_consumed_capacity(ReturnConsumedCapacity::Total);
+
+    builder = condition_check
+        .recoded(client)?
+        .externalized(client, table)
+        .dump_in_update_item(builder);
+
+    let result = builder.send().await?;
+    client.stats.add_capacity(
+        Operation::UpdateItem,
+        result.consumed_capacity().and_then(|c| c.capacity_units()),
+    );
+    client.report_collection_metrics(table, result.item_collection_metrics());
+    audit_image(client, result.attributes())
+}
+
+/// Updates an item as `update_item` does, but in a single item transaction, so that if the
+/// condition check fails, the returned `DynarustError::ConditionalCheckFailedError` carries
+/// the item as it was stored. The update is measured and audited as the ones of the store.
+pub(crate) async fn update_item_or_return_old(
+    client: &Client,
+    table: &str,
+    (pk, sk): (String, String),
+    update: ItemUpdate,
+    condition_check: ConditionCheckInfo,
+) -> Result<(), DynarustError> {
+    let (expression, names, values) = update.expression()?;
+    let mut builder = update::Builder::default()
+        .table_name(table)
+        .key(PK, AttributeValue::S(client.scope_pk(pk.clone())))
+        .key(SK, AttributeValue::S(sk.clone()))
+        .update_expression(expression)
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()))
+        .return_values_on_condition_check_failure(ReturnValuesOnConditionCheckFailure::AllOld);
+    builder = condition_check.dump_in_update(builder);
+
+    let update = client.execute_single_item(
+        Operation::UpdateItem,
+        TransactWriteItem::builder().update(builder.build()).build(),
+    );
+    client.stats.measure(Operation::UpdateItem, update).await?;
+    client
+        .audit(AuditOperation::Update, table, (pk, sk), None, None)
+        .await
+}
 
 impl Client {
+    /// Prefixes the index PrimaryKey set by an update with the tenant id of the client.
+    pub(crate) fn scope_update(&self, update: &mut ItemUpdate) {
+        for (k, v) in update.set.iter_mut() {
+            if let (GSI1_PK, Value::String(gsi1_pk)) = (k.as_str(), v) {
+                *gsi1_pk = self.scope_pk(std::mem::take(gsi1_pk))
+            }
+        }
+    }
+
     /// Updates a resource. It returns an error if the resource does not exist.
     ///
     /// # arguments
@@ -70,53 +448,220 @@ impl Client {
         request: Value,
         condition_checks: Vec<ConditionCheckInfo>,
     ) -> Result<T, DynarustError> {
-        let mut object = Self::resource_as_object(resource)?;
-
-        let request: HashMap<String, Value> = serde_json::from_value(request)?;
+        update_resource(self, resource, request, condition_checks).await
+    }
 
-        for (k, new_v) in request.iter() {
-            object[k] = new_v.clone()
+    /// Updates a resource and returns only the attributes touched by the update, as dynamo
+    /// sends them back, instead of the whole item. Useful for very large items, where reading
+    /// back all their attributes wastes bandwidth. The returned attributes are deserialized
+    /// as R, which can be a `serde_json::Value` or a struct whose fields are optional, as
+    /// attributes that did not exist before the update, or that are removed by it, are
+    /// missing from the response. It returns an error if the resource does not exist. The
+    /// audit record of these updates carries no after image, as the whole item is never read.
+    ///
+    /// # arguments
+    ///
+    /// * `resource` - the resource that will get updated.
+    /// * `request` - serde Object with the info for updating the request.
+    /// * `attributes` - whether the new or the old values of the updated attributes are
+    ///   returned.
+    ///
+    /// # examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// async {
+    ///     let previous: Value = client
+    ///         .update_returning(&document, json!({ "title": "Draft 2" }), UpdatedAttributes::Old)
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn update_returning<T, R>(
+        &self,
+        resource: &T,
+        request: Value,
+        attributes: UpdatedAttributes,
+    ) -> Result<R, DynarustError>
+    where
+        T: Resource + Serialize + DeserializeOwned,
+        R: DeserializeOwned,
+    {
+        let (_, update) = apply_update_request(resource, request)?;
+        if update.is_empty() {
+            return Ok(serde_json::from_value(Value::Object(Default::default()))?);
         }
-        let updated: T = serde_json::from_value(Value::Object(object))?;
 
-        if request.is_empty() {
-            return Ok(updated);
-        }
+        let table = T::table();
+        let key = resource.pk_sk();
+        let update = update_item(
+            self,
+            &table,
+            key.clone(),
+            update,
+            condition_check_exists(),
+            Some(attributes.into()),
+        );
+        let returned = self.stats.measure(Operation::UpdateItem, update).await?;
+        self.audit(AuditOperation::Update, &table, key, None, None)
+            .await?;
 
-        if updated.pk_sk() != resource.pk_sk() {
-            return Err(DynarustError::InvalidRequestError(
-                "Cannot update PK and/or SK".into(),
-            ));
+        let returned = returned.unwrap_or_else(|| Value::Object(Default::default()));
+        Ok(serde_json::from_value(returned)?)
+    }
+}
+
+impl Client {
+    /// Sets a field of a resource to a new value only if its current value is the expected
+    /// one, in a single conditional update. If the current value is a different one, a
+    /// `DynarustError::CasFailed` error is returned carrying the actual value, which is None if
+    /// the resource or the field do not exist. As in `patch`, setting the key, the index keys or
+    /// immutable fields is rejected, and a new value skipped by the write policy of the resource
+    /// removes the field.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `field` - The field that will get updated.
+    /// * `expected` - The value that the field must have for the update to happen.
+    /// * `new` - The value to which the field will be set.
+    ///
+    /// # examples
+    ///
+    /// ```
+    /// async {
+    ///     let result = client
+    ///         .compare_and_set::<Order>(order.pk_sk(), "status", "pending", "paid")
+    ///         .await;
+    ///     if let Err(DynarustError::CasFailed { actual }) = result {
+    ///         println!("the order was already {actual:?}")
+    ///     }
+    /// }
+    /// ```
+    pub async fn compare_and_set<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        field: &str,
+        expected: impl Serialize,
+        new: impl Serialize,
+    ) -> Result<(), DynarustError> {
+        let new = serde_json::to_value(new)?;
+        let mut update = ItemUpdate::default();
+        if new.is_null() || T::write_policy().skips(&new) {
+            update.remove.push(field.to_string())
+        } else {
+            update.set.push((field.to_string(), new))
         }
+        let table = T::table();
+        self.check_patch::<T>(&table, &update)?;
+        let expected = Condition::Compare(DynamoOperator::Eq, serde_json::to_value(expected)?);
+        let condition_check =
+            condition_check_exists().merge(vec![condition_check(field, expected)?]);
 
-        let condition_check = condition_check_exists().merge(condition_checks);
+        match update_item_or_return_old(self, &table, pk_sk, update, condition_check).await {
+            Err(DynarustError::ConditionalCheckFailedError(item)) => {
+                Err(DynarustError::CasFailed {
+                    actual: item.and_then(|item| item.get(field).cloned()),
+                })
+            }
+            result => result,
+        }
+    }
 
-        let (pk, sk) = resource.pk_sk();
-        let mut builder = self
-            .client
-            .update_item()
-            .table_name(T::table())
-            .key(PK, AttributeValue::S(pk))
-            .key(SK, AttributeValue::S(sk));
+    /// Applies an update to an existing resource in a single round trip, combining attributes
+    /// that are set, removed, added to and deleted from, and returns the resource as it is
+    /// after the update. Like `transition`, the resource is not read beforehand, so its GSI1
+    /// keys are not recomputed. Updating the key, the index keys or immutable fields is
+    /// rejected, and a `DynarustError::ConditionalCheckFailedError` is returned if the resource
+    /// does not exist.
+    ///
+    /// # arguments
+    ///
+    /// * `pk_sk` - Pk and sk pair for identifying the resource.
+    /// * `update` - The attributes that are set, removed, added to and deleted from. Dynamo
+    ///   rejects updates that touch the same attribute in more than one clause.
+    ///
+    /// # examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// async {
+    ///     let update = ItemUpdate::default()
+    ///         .with_set("status", json!("active"))
+    ///         .with_remove("activation_token")
+    ///         .with_add("logins", json!(1));
+    ///     let user: User = client.patch(user.pk_sk(), update).await?;
+    /// }
+    /// ```
+    pub async fn patch<T: Resource + DeserializeOwned>(
+        &self,
+        pk_sk: (String, String),
+        update: ItemUpdate,
+    ) -> Result<T, DynarustError> {
+        let after = self
+            .patch_item::<T>(pk_sk, update, Some(ReturnValue::AllNew))
+            .await?;
+        let after = after.ok_or_else(|| {
+            DynarustError::UnexpectedError("The patched resource was not returned".into())
+        })?;
+        Client::value_as_resource(after)
+    }
 
-        let mut update_expression = "set ".to_string();
-        let request_len = request.len();
-        for (i, (k, v)) in request.into_iter().enumerate() {
-            let name = format!("#updateAttr{}", i);
-            let value = format!(":updateAttr{}", i);
-            update_expression += &format!("{} = {}", name, value);
-            if i < request_len - 1 {
-                update_expression += ", "
-            }
-            builder = builder.expression_attribute_names(name, k);
-            builder = builder.expression_attribute_values(value, Self::value2attr(&v)?);
+    /// Validates and applies an update to an existing item of the table of T, measuring and
+    /// auditing it. Returns the requested image of the item.
+    pub(crate) async fn patch_item<T: Resource>(
+        &self,
+        pk_sk: (String, String),
+        update: ItemUpdate,
+        return_values: Option<ReturnValue>,
+    ) -> Result<Option<Value>, DynarustError> {
+        if update.is_empty() {
+            return Err(DynarustError::InvalidRequestError("Empty patch".into()));
         }
+        let table = T::table();
+        self.check_patch::<T>(&table, &update)?;
 
-        builder = condition_check.dump_in_update_item(builder);
+        let return_values = return_values.or(self.audit.is_some().then_some(ReturnValue::AllNew));
+        let update = update_item(
+            self,
+            &table,
+            pk_sk.clone(),
+            update,
+            condition_check_exists(),
+            return_values,
+        );
+        let after = self.stats.measure(Operation::UpdateItem, update).await?;
+        self.audit(AuditOperation::Update, &table, pk_sk, None, after.clone())
+            .await?;
+        Ok(after)
+    }
 
-        builder.update_expression(update_expression).send().await?;
+    /// Rejects patches that touch the key, the index keys or the immutable fields of T.
+    fn check_patch<T: Resource>(
+        &self,
+        table: &str,
+        update: &ItemUpdate,
+    ) -> Result<(), DynarustError> {
+        update
+            .attributes()
+            .try_for_each(|k| self.check_patched_field::<T>(table, k))
+    }
 
-        Ok(updated)
+    /// Rejects writes to a field, or to a nested path under it, if it is the key, an index key
+    /// or an immutable field of T.
+    pub(crate) fn check_patched_field<T: Resource>(
+        &self,
+        table: &str,
+        field: &str,
+    ) -> Result<(), DynarustError> {
+        let (pk_attr, sk_attr) = self.key_names(table);
+        match [pk_attr, sk_attr, GSI1_PK, GSI1_SK].contains(&field)
+            || T::immutable_fields().contains(&field)
+        {
+            true => Err(DynarustError::InvalidRequestError(format!(
+                "Cannot patch {field}"
+            ))),
+            false => Ok(()),
+        }
     }
 }
 
@@ -147,7 +692,7 @@ impl Client {
 pub fn transact_update<T: Resource + Serialize + DeserializeOwned>(
     resource: &T,
     request: Value,
-    transaction_context: &mut Vec<TransactWriteItem>,
+    transaction_context: &mut TransactionContext,
 ) -> Result<T, DynarustError> {
     transact_update_with_checks(resource, request, vec![], transaction_context)
 }
@@ -182,65 +727,269 @@ pub fn transact_update_with_checks<T: Resource + Serialize + DeserializeOwned>(
     resource: &T,
     request: Value,
     condition_checks: Vec<ConditionCheckInfo>,
-    transaction_context: &mut Vec<TransactWriteItem>,
+    transaction_context: &mut TransactionContext,
 ) -> Result<T, DynarustError> {
-    let mut object = Client::resource_as_object(resource)?;
-
-    let request: HashMap<String, Value> = serde_json::from_value(request)?;
-
-    for (k, new_v) in request.iter() {
-        object[k] = new_v.clone()
-    }
-    let updated: T = serde_json::from_value(Value::Object(object))?;
+    let (updated, update) = apply_update_request(resource, request)?;
 
-    if request.is_empty() {
+    if update.is_empty() {
         return Ok(updated);
     }
 
-    if updated.pk_sk() != resource.pk_sk() {
-        return Err(DynarustError::InvalidRequestError(
-            "Cannot update PK and/or SK".into(),
-        ));
-    }
-
     let condition_check = condition_check_exists().merge(condition_checks);
+    let (expression, names, values) = update.expression()?;
 
     let (pk, sk) = resource.pk_sk();
     let mut builder = update::Builder::default()
         .table_name(T::table())
         .key(PK, AttributeValue::S(pk))
-        .key(SK, AttributeValue::S(sk));
+        .key(SK, AttributeValue::S(sk))
+        .update_expression(expression)
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()));
+
+    builder = condition_check.dump_in_update(builder);
+
+    transaction_context.push(TransactWriteItem::builder().update(builder.build()).build())?;
+
+    Ok(updated)
+}
+
+/// Adds to the transaction context a single operation that patches an existing item only if
+/// it matches a condition. The condition and the patch are evaluated atomically on the same
+/// item, so there is no need for a separate condition check, which dynamo would reject anyway
+/// as a second operation on the item. If the item does not exist or the condition fails, the
+/// whole transaction is canceled.
+///
+/// Unlike `transact_update`, the current resource is not needed, so its GSI1 keys are not
+/// recomputed. Patching immutable fields is rejected.
+///
+/// # arguments
+///
+/// * `pk_sk` - Pk and sk pair for identifying the patched item.
+/// * `condition` - The condition that the item must match, an empty one only requires the item
+///   to exist.
+/// * `patch` - serde Object with the attributes that will be set, null values remove them.
+/// * `transaction_context` - The transaction context to which the operation will be added.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// async {
+///     let mut context = dynarust::begin_transaction();
+///     dynarust::transact_patch_if::<Seat>(
+///         seat.pk_sk(),
+///         dynarust::condition_check_string("status", DynamoOperator::Eq, "free"),
+///         json!({ "status": "taken", "holder": "user#1" }),
+///         &mut context,
+///     )?;
+///     dynarust::transact_create(&booking, &mut context)?;
+///     client.execute_transaction(context).await?;
+/// }
+/// ```
+pub fn transact_patch_if<T: Resource>(
+    (pk, sk): (String, String),
+    condition: ConditionCheckInfo,
+    patch: Value,
+    transaction_context: &mut TransactionContext,
+) -> Result<(), DynarustError> {
+    let patch: HashMap<String, Value> = serde_json::from_value(patch)?;
+    if let Some(field) = T::immutable_fields()
+        .iter()
+        .find(|field| patch.contains_key(**field))
+    {
+        return Err(DynarustError::InvalidRequestError(format!(
+            "Cannot update immutable field {field}"
+        )));
+    }
 
-    let mut update_expression = "set ".to_string();
-    let request_len = request.len();
-    for (i, (k, v)) in request.into_iter().enumerate() {
-        let name = format!("#updateAttr{}", i);
-        let value = format!(":updateAttr{}", i);
-        update_expression += &format!("{} = {}", name, value);
-        if i < request_len - 1 {
-            update_expression += ", "
+    let mut update = ItemUpdate::default();
+    for (k, v) in patch {
+        if v.is_null() || T::write_policy().skips(&v) {
+            update.remove.push(k)
+        } else {
+            update.set.push((k, v))
         }
-        builder = builder.expression_attribute_names(name, k);
-        builder = builder.expression_attribute_values(value, Client::value2attr(&v)?);
     }
+    if update.is_empty() {
+        return Err(DynarustError::InvalidRequestError("Empty patch".into()));
+    }
+    let (expression, names, values) = update.expression()?;
 
-    builder = condition_check.dump_in_update(builder);
+    let mut builder = update::Builder::default()
+        .table_name(T::table())
+        .key(PK, AttributeValue::S(pk))
+        .key(SK, AttributeValue::S(sk))
+        .update_expression(expression)
+        .set_expression_attribute_names(Some(names))
+        .set_expression_attribute_values(Some(values).filter(|v| !v.is_empty()));
 
-    let update = builder.update_expression(update_expression).build();
-    transaction_context.push(TransactWriteItem::builder().update(update).build());
+    builder = condition_check_exists()
+        .merge(vec![condition])
+        .dump_in_update(builder);
 
-    Ok(updated)
+    transaction_context.push(TransactWriteItem::builder().update(builder.build()).build())
 }
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
+    use aws_sdk_dynamodb::model::AttributeValue;
+    use futures::TryStreamExt;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::client::tests::{TestResource, TABLE};
+    use crate::client::GSI1_SK;
+    use crate::client::{GSI1_PK, PK, SK};
+    use crate::condition_check::{
+        condition_check, condition_check_number, condition_check_size, condition_check_string,
+    };
+    use crate::create::{resource_as_item, transact_create};
+    use crate::update::{apply_update_request, transact_patch_if, transact_update};
+    use crate::{
+        begin_transaction, Client, Condition, DynamoOperator, DynarustError, ItemUpdate, Resource,
+        UpdatedAttributes, WritePolicy,
+    };
+
+    #[test]
+    fn patches_with_a_single_operation() {
+        let mut context = begin_transaction();
+        transact_patch_if::<TestResource>(
+            ("pk".into(), "sk".into()),
+            condition_check_number("int", DynamoOperator::Eq, 1),
+            json!({ "int": 2, "nullable": null }),
+            &mut context,
+        )
+        .unwrap();
+        let update = context.into_items().remove(0).update.unwrap();
+        assert_eq!(
+            update.update_expression.unwrap(),
+            "set #updateAttr0 = :updateAttr0 remove #removeAttr0"
+        );
+        let condition = update.condition_expression.unwrap();
+        assert!(condition.starts_with("(attribute_exists(#pk) and attribute_exists(#sk)) and (#"));
+
+        let mut context = begin_transaction();
+        let err = transact_patch_if::<TestResource>(
+            ("pk".into(), "sk".into()),
+            Default::default(),
+            json!({}),
+            &mut context,
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid request: Empty patch");
+    }
+
+    #[test]
+    fn builds_updates_with_mixed_clauses() {
+        let update = ItemUpdate::default()
+            .with_delete("tags", json!(["old"]))
+            .with_add("int", json!(2))
+            .with_remove("nullable")
+            .with_set("string", json!("a"));
+        let (expression, names, values) = update.expression().unwrap();
+        assert_eq!(
+            expression,
+            "set #updateAttr0 = :updateAttr0 remove #removeAttr0 \
+             add #addAttr0 :addAttr0 delete #deleteAttr0 :deleteAttr0"
+        );
+        assert_eq!(names["#deleteAttr0"], "tags");
+        assert_eq!(values[":addAttr0"], AttributeValue::N("2".into()));
+        assert!(!update.is_empty());
+    }
+
+    #[tokio::test]
+    async fn patches_with_mixed_clauses() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "patches_with_mixed_clauses".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            nullable: Some("temp".into()),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let update = ItemUpdate::default()
+            .with_set("string", json!("shipped"))
+            .with_remove("nullable")
+            .with_add("int", json!(2));
+        let patched: TestResource = client.patch(resource.pk_sk(), update).await.unwrap();
+        assert_eq!(
+            (patched.string.as_str(), patched.nullable, patched.int),
+            ("shipped", None, 3)
+        );
+
+        let err = client
+            .patch::<TestResource>(resource.pk_sk(), ItemUpdate::default().with_remove(PK))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::InvalidRequestError(_)));
+        let missing = TestResource::key("patches_with_mixed_clauses", "2");
+        let err = client
+            .patch::<TestResource>(
+                missing.pk_sk(),
+                ItemUpdate::default().with_add("int", json!(1)),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DynarustError::ConditionalCheckFailedError(_)));
+    }
+
+    #[tokio::test]
+    async fn patches_if_condition_matches() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "patches_if_condition_matches".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
 
-    use crate::client::tests::TestResource;
-    use crate::condition_check::condition_check_number;
-    use crate::create::transact_create;
-    use crate::update::transact_update;
-    use crate::{begin_transaction, Client, DynamoOperator, Resource};
+        for expected in [1, 2] {
+            let mut context = begin_transaction();
+            transact_patch_if::<TestResource>(
+                resource.pk_sk(),
+                condition_check_number("int", DynamoOperator::Eq, 1),
+                json!({ "int": 2 }),
+                &mut context,
+            )
+            .unwrap();
+            let result = client.execute_transaction(context).await;
+            assert_eq!(result.is_ok(), expected == 1);
+            let stored = client.get::<TestResource>(resource.pk_sk()).await.unwrap();
+            assert_eq!(stored.unwrap().int, 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_only_updated_attributes() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "returns_only_updated_attributes".to_string(),
+            sk: "1".to_string(),
+            int: 1,
+            string: "untouched".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let old: Value = client
+            .update_returning(&resource, json!({ "int": 2 }), UpdatedAttributes::Old)
+            .await
+            .unwrap();
+        assert_eq!(old, json!({ "int": 1 }));
+
+        let new: Value = client
+            .update_returning(&resource, json!({ "int": 3 }), UpdatedAttributes::New)
+            .await
+            .unwrap();
+        assert_eq!(new, json!({ "int": 3 }));
+    }
 
     #[tokio::test]
     async fn creates_updates_gets_resource() {
@@ -375,4 +1124,320 @@ mod tests {
             .unwrap();
         assert_eq!(retrieved_2, Some(resource_2))
     }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+    struct IndexedResource {
+        pk: String,
+        sk: String,
+        email: Option<String>,
+    }
+
+    impl Resource for IndexedResource {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.pk.clone(), self.sk.clone())
+        }
+
+        fn gsi1_pk_sk(&self) -> Option<(String, String)> {
+            self.email.clone().map(|email| (email, self.pk.clone()))
+        }
+    }
+
+    async fn raw_gsi1_pk(client: &Client, resource: &IndexedResource) -> Option<AttributeValue> {
+        let (pk, sk) = resource.pk_sk();
+        client
+            .dynamo(&IndexedResource::table())
+            .get_item()
+            .table_name(IndexedResource::table())
+            .key(PK, AttributeValue::S(pk))
+            .key(SK, AttributeValue::S(sk))
+            .send()
+            .await
+            .unwrap()
+            .item()
+            .and_then(|item| item.get(GSI1_PK).cloned())
+    }
+
+    #[tokio::test]
+    async fn keeps_index_keys_consistent() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let resource = IndexedResource {
+            pk: "keeps_index_keys_consistent".to_string(),
+            sk: "1".to_string(),
+            email: Some("foo@bar.com".to_string()),
+        };
+        client.create(&resource).await.unwrap();
+        assert_eq!(
+            raw_gsi1_pk(&client, &resource).await,
+            Some(AttributeValue::S("foo@bar.com".to_string()))
+        );
+
+        let updated = client
+            .update(&resource, json!({ "email": "bar@foo.com" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            raw_gsi1_pk(&client, &updated).await,
+            Some(AttributeValue::S("bar@foo.com".to_string()))
+        );
+
+        let updated = client
+            .update(&updated, json!({ "email": null }))
+            .await
+            .unwrap();
+        assert_eq!(raw_gsi1_pk(&client, &updated).await, None);
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+    struct Document {
+        id: String,
+        owner_id: String,
+        title: String,
+    }
+
+    impl Resource for Document {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            (self.id.clone(), "document".to_string())
+        }
+
+        fn immutable_fields() -> &'static [&'static str] {
+            &["owner_id"]
+        }
+    }
+
+    #[test]
+    fn rejects_updates_on_immutable_fields() {
+        let document = Document {
+            id: "1".to_string(),
+            owner_id: "foo".to_string(),
+            title: "title".to_string(),
+        };
+
+        let err = apply_update_request(&document, json!({ "owner_id": "bar" })).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid request: Cannot update immutable field owner_id"
+        );
+
+        let (updated, _) =
+            apply_update_request(&document, json!({ "owner_id": "foo", "title": "new" })).unwrap();
+        assert_eq!(updated.title, "new");
+    }
+
+    #[tokio::test]
+    async fn compares_and_sets_field() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = TestResource {
+            pk: "compares_and_sets_field".to_string(),
+            sk: "1".to_string(),
+            string: "pending".to_string(),
+            ..Default::default()
+        };
+        client.create(&resource).await.unwrap();
+
+        client
+            .compare_and_set::<TestResource>(resource.pk_sk(), "string", "pending", "paid")
+            .await
+            .unwrap();
+
+        let err = client
+            .compare_and_set::<TestResource>(resource.pk_sk(), "string", "pending", "paid")
+            .await
+            .unwrap_err();
+        match err {
+            DynarustError::CasFailed { actual } => assert_eq!(actual, Some(json!("paid"))),
+            err => panic!("unexpected error {err}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_compare_and_set_of_keys() {
+        let client = Client::local_on_port(12345).await;
+        for field in [PK, GSI1_PK] {
+            let err = client
+                .compare_and_set::<TestResource>(("pk".into(), "sk".into()), field, "a", "b")
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Invalid request: Cannot patch {field}")
+            );
+        }
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Default)]
+    #[serde(default)]
+    struct Sparse {
+        id: String,
+        email: String,
+        tags: Vec<String>,
+        visits: i64,
+    }
+
+    impl Resource for Sparse {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("sparse".to_string(), self.id.clone())
+        }
+
+        fn gsi1_pk_sk(&self) -> Option<(String, String)> {
+            Some((self.email.clone(), self.id.clone()))
+        }
+
+        fn write_policy() -> WritePolicy {
+            WritePolicy::SkipDefaults
+        }
+    }
+
+    #[test]
+    fn skips_default_values() {
+        let resource = Sparse {
+            id: "1".to_string(),
+            ..Default::default()
+        };
+
+        let item = resource_as_item(&resource).unwrap();
+        let mut attributes: Vec<&String> = item.keys().collect();
+        attributes.sort();
+        assert_eq!(attributes, vec![GSI1_SK, "id"]);
+
+        let (updated, update) =
+            apply_update_request(&resource, json!({ "email": "foo@bar.com" })).unwrap();
+        assert_eq!(update.set.len(), 3);
+        assert!(update.remove.is_empty());
+
+        let (_, update) = apply_update_request(&updated, json!({ "email": "" })).unwrap();
+        assert_eq!(update.set, vec![(GSI1_SK.to_string(), json!("1"))]);
+        assert_eq!(
+            update.remove,
+            vec!["email".to_string(), GSI1_PK.to_string()]
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+    struct Reserved {
+        name: String,
+        status: String,
+        size: i64,
+        #[serde(rename = "shipping.address")]
+        shipping_address: String,
+        #[serde(rename = "zip-code")]
+        zip_code: String,
+    }
+
+    impl Resource for Reserved {
+        fn table() -> String {
+            TABLE.to_string()
+        }
+
+        fn pk_sk(&self) -> (String, String) {
+            ("aliases_reserved_names".into(), self.name.clone())
+        }
+    }
+
+    const RESERVED_NAMES: [&str; 5] = ["name", "status", "size", "shipping.address", "zip-code"];
+
+    #[test]
+    fn aliases_every_attribute_name() {
+        let resource = Reserved {
+            name: "1".into(),
+            ..Default::default()
+        };
+        let (_, update) = apply_update_request(
+            &resource,
+            json!({ "status": "a", "size": 1, "shipping.address": "b", "zip-code": "" }),
+        )
+        .unwrap();
+        let (expression, names, _) = update.expression().unwrap();
+        let check = condition_check("status", Condition::In(vec![json!("a")]))
+            .unwrap()
+            .merge(vec![
+                condition_check_size("shipping.address", DynamoOperator::Gt, 0),
+                condition_check("zip-code", Condition::NotExists).unwrap(),
+            ]);
+        let (condition, condition_names, _) = check.into_parts();
+
+        let both = format!("{expression} {condition}");
+        let tokens: Vec<&str> = both
+            .split(|c: char| c.is_whitespace() || "),".contains(c))
+            .map(|token| token.trim_start_matches('('))
+            .collect();
+        for attr in RESERVED_NAMES.iter().filter(|attr| **attr != "name") {
+            assert!(!tokens.contains(attr), "{attr} in {both}");
+            assert!(names
+                .values()
+                .chain(condition_names.values())
+                .any(|v| v == attr));
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_reserved_and_punctuated_names() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let resource = Reserved {
+            name: "1".into(),
+            status: "pending".into(),
+            size: 2,
+            shipping_address: "Main St.".into(),
+            zip_code: "08-001".into(),
+        };
+        client.force_create(&resource).await.unwrap();
+
+        let updated = client
+            .update_with_checks(
+                &resource,
+                json!({ "status": "shipped", "size": 3, "shipping.address": "Side St." }),
+                vec![
+                    condition_check(
+                        "status",
+                        Condition::Compare(DynamoOperator::Eq, json!("pending")),
+                    )
+                    .unwrap(),
+                    condition_check("zip-code", Condition::BeginsWith("08".into())).unwrap(),
+                    condition_check_size("shipping.address", DynamoOperator::Gt, 0),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.shipping_address, "Side St.");
+
+        let stored = client
+            .get::<Reserved>(resource.pk_sk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored, updated);
+
+        let found: Vec<Reserved> = client
+            .find_where::<Reserved>(
+                condition_check(
+                    "zip-code",
+                    Condition::Compare(DynamoOperator::Eq, json!("08-001")),
+                )
+                .unwrap()
+                .merge(vec![condition_check_string(
+                    "name",
+                    DynamoOperator::Eq,
+                    "1",
+                )]),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(found, vec![updated]);
+    }
 }
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::client::fluent_builders::Query;
+use aws_sdk_dynamodb::model::{AttributeValue, ReturnConsumedCapacity};
+use aws_sdk_dynamodb::output::QueryOutput;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::stats::Operation;
+use crate::{
+    begin_transaction, transact_update_with_checks, Client, ConditionCheckInfo, DynamoOperator,
+    DynarustError, Resource,
+};
+
+/// Condition on the sort key of the items under a pk, used by `list` and the operations on
+/// the items under a pk that match it, like `update_where`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SkCondition {
+    /// every sort key matches.
+    #[default]
+    Any,
+    Eq(String),
+    Lt(String),
+    Le(String),
+    Gt(String),
+    Ge(String),
+    /// sort keys between the two values, both included.
+    Between(String, String),
+    BeginsWith(String),
+}
+
+impl SkCondition {
+    /// The comparison of the sort key against a value with one of the operators supported by
+    /// key conditions, which are all but `DynamoOperator::NEq`.
+    pub fn compare(
+        operator: DynamoOperator,
+        value: impl Into<String>,
+    ) -> Result<Self, DynarustError> {
+        let value = value.into();
+        Ok(match operator {
+            DynamoOperator::Eq => SkCondition::Eq(value),
+            DynamoOperator::Gt => SkCondition::Gt(value),
+            DynamoOperator::GtEq => SkCondition::Ge(value),
+            DynamoOperator::Ls => SkCondition::Lt(value),
+            DynamoOperator::LsEq => SkCondition::Le(value),
+            DynamoOperator::NEq => {
+                return Err(DynarustError::InvalidRequestError(
+                    "Sort key conditions do not support the <> operator".into(),
+                ))
+            }
+        })
+    }
+
+    /// Key condition expression over the `#pk` and `#sk` names and the `:pk` value, along with
+    /// the values of the sort key placeholders.
+    pub(crate) fn key_condition(&self) -> (String, HashMap<String, AttributeValue>) {
+        let s = |v: &String| AttributeValue::S(v.clone());
+        let (condition, values) = match self {
+            SkCondition::Any => return ("#pk = :pk".into(), HashMap::new()),
+            SkCondition::Eq(v) => ("#sk = :sk", vec![(":sk", s(v))]),
+            SkCondition::Lt(v) => ("#sk < :sk", vec![(":sk", s(v))]),
+            SkCondition::Le(v) => ("#sk <= :sk", vec![(":sk", s(v))]),
+            SkCondition::Gt(v) => ("#sk > :sk", vec![(":sk", s(v))]),
+            SkCondition::Ge(v) => ("#sk >= :sk", vec![(":sk", s(v))]),
+            SkCondition::Between(a, b) => (
+                "#sk between :sk and :skEnd",
+                vec![(":sk", s(a)), (":skEnd", s(b))],
+            ),
+            SkCondition::BeginsWith(v) => ("begins_with(#sk, :sk)", vec![(":sk", s(v))]),
+        };
+        (
+            format!("#pk = :pk and {condition}"),
+            values
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+}
+
+/// Options of an `update_where` call.
+#[derive(Debug, Clone)]
+pub struct UpdateWhereOptions {
+    /// number of items read per page, default is 25.
+    pub page_size: i32,
+    /// whether the updates of every page are applied in a single transaction, so a page is
+    /// either fully updated or not at all, default is false.
+    pub transactional: bool,
+}
+
+impl Default for UpdateWhereOptions {
+    fn default() -> Self {
+        Self {
+            page_size: 25,
+            transactional: false,
+        }
+    }
+}
+
+/// Outcome of an `update_where` call.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UpdateWhereSummary {
+    /// resources to which the patch was applied.
+    pub updated: usize,
+    /// matching items that could not be read as the resource, or that stopped matching the
+    /// filter before being updated.
+    pub skipped: usize,
+}
+
+impl Client {
+    /// Query for the items under a pk that match a sort key condition and a filter.
+    pub(crate) fn query_where(
+        &self,
+        table: &str,
+        pk: String,
+        sk_condition: &SkCondition,
+        filter: ConditionCheckInfo,
+    ) -> Query {
+        let (pk_attr, sk_attr) = self.key_names(table);
+        let (key_condition, mut values) = sk_condition.key_condition();
+        values.insert(":pk".into(), AttributeValue::S(self.scope_pk(pk)));
+        let mut names = HashMap::from([
+            ("#pk".to_string(), pk_attr.to_string()),
+            ("#sk".to_string(), sk_attr.to_string()),
+        ]);
+        let (filter_expression, filter_names, filter_values) = filter.into_parts();
+        names.extend(filter_names);
+        values.extend(filter_values);
+
+        self.dynamo(table)
+            .query()
+            .table_name(table)
+            .key_condition_expression(key_condition)
+            .set_filter_expression(Some(filter_expression).filter(|e| !e.is_empty()))
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .return_consumed_capacity(ReturnConsumedCapacity::Total)
+    }
+
+    /// Sends a page of a `query_where` query, recording its latency and consumed capacity.
+    pub(crate) async fn send_query_where(
+        &self,
+        query: Query,
+    ) -> Result<QueryOutput, DynarustError> {
+        let result = self
+            .stats
+            .measure(Operation::Query, async { Ok(query.send().await?) })
+            .await?;
+        self.stats.add_capacity(
+            Operation::Query,
+            result.consumed_capacity().and_then(|c| c.capacity_units()),
+        );
+        Ok(result)
+    }
+
+    /// Applies a patch to every resource under a pk whose sort key and attributes match the
+    /// provided conditions. The partition is queried page by page, and every matching resource
+    /// is updated with the filter as condition check, so resources that were modified in the
+    /// meantime and no longer match are skipped instead of overwritten.
+    ///
+    /// When the updates are transactional, a page where any resource stopped matching fails as
+    /// a whole with an error, and the pages before it stay updated. Transactions are limited to
+    /// 100 items, so the page size is capped to that.
+    ///
+    /// # arguments
+    ///
+    /// * `pk` - Primary Key under which the updated resources live.
+    /// * `sk_condition` - The condition on the sort key of the updated resources.
+    /// * `filter` - The condition on the attributes of the updated resources, built with the
+    ///   same functions as condition checks. An empty filter matches every resource.
+    /// * `patch` - serde Object with the update applied to every resource, as in `update`.
+    /// * `options` - optional page options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// async {
+    ///     let summary = client
+    ///         .update_where::<Order>(
+    ///             "customer#1".into(),
+    ///             SkCondition::BeginsWith("2023-".into()),
+    ///             dynarust::condition_check_string("status", DynamoOperator::Eq, "pending"),
+    ///             json!({ "status": "expired" }),
+    ///             None,
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn update_where<T: Resource + Serialize + DeserializeOwned>(
+        &self,
+        pk: String,
+        sk_condition: SkCondition,
+        filter: ConditionCheckInfo,
+        patch: Value,
+        options: Option<UpdateWhereOptions>,
+    ) -> Result<UpdateWhereSummary, DynarustError> {
+        let options = options.unwrap_or_default();
+        let query = self.query_where(&T::table(), pk, &sk_condition, filter.clone());
+
+        let mut summary = UpdateWhereSummary::default();
+        let mut exclusive_start_key = None;
+        loop {
+            let page = query
+                .clone()
+                .limit(options.page_size.clamp(1, 100))
+                .set_exclusive_start_key(exclusive_start_key);
+            let result = self.send_query_where(page).await?;
+
+            let mut resources = vec![];
+            for item in result.items().unwrap_or_default() {
+                match Client::value_as_resource::<T>(self.decode_item(item)?) {
+                    Ok(resource) => resources.push(resource),
+                    Err(_) => summary.skipped += 1,
+                }
+            }
+
+            if options.transactional && !resources.is_empty() {
+                let mut context = begin_transaction();
+                for resource in resources.iter() {
+                    transact_update_with_checks(
+                        resource,
+                        patch.clone(),
+                        vec![filter.clone()],
+                        &mut context,
+                    )?;
+                }
+                self.execute_transaction(context).await?;
+                summary.updated += resources.len();
+            } else {
+                for resource in resources.iter() {
+                    let result = self
+                        .update_with_checks(resource, patch.clone(), vec![filter.clone()])
+                        .await;
+                    match result {
+                        Ok(_) => summary.updated += 1,
+                        Err(DynarustError::ConditionalCheckFailedError(_)) => summary.skipped += 1,
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+
+            exclusive_start_key = result.last_evaluated_key().cloned();
+            if exclusive_start_key.is_none() {
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::{
+        condition_check_number, Client, DynamoOperator, ListOptions, SkCondition,
+        UpdateWhereOptions, UpdateWhereSummary,
+    };
+
+    #[test]
+    fn builds_sort_key_conditions() {
+        let (expression, values) = SkCondition::Any.key_condition();
+        assert_eq!(expression, "#pk = :pk");
+        assert!(values.is_empty());
+
+        let (expression, values) = SkCondition::Between("a".into(), "b".into()).key_condition();
+        assert_eq!(expression, "#pk = :pk and #sk between :sk and :skEnd");
+        assert_eq!(values.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn updates_matching_resources() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+
+        let pk = "updates_matching_resources".to_string();
+        for i in 0..6 {
+            let resource = TestResource {
+                pk: pk.clone(),
+                sk: format!("{}#{i}", if i < 4 { "a" } else { "b" }),
+                int: i % 2,
+                ..Default::default()
+            };
+            client.force_create(&resource).await.unwrap();
+        }
+
+        for transactional in [false, true] {
+            let summary = client
+                .update_where::<TestResource>(
+                    pk.clone(),
+                    SkCondition::BeginsWith("a#".into()),
+                    condition_check_number("int", DynamoOperator::Eq, 1),
+                    json!({ "string": format!("{transactional}") }),
+                    Some(UpdateWhereOptions {
+                        page_size: 1,
+                        transactional,
+                    }),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                summary,
+                UpdateWhereSummary {
+                    updated: 2,
+                    skipped: 0
+                }
+            );
+        }
+
+        let listed = client
+            .list::<TestResource>(pk, &ListOptions::default())
+            .await
+            .unwrap();
+        let updated: Vec<_> = listed
+            .items
+            .iter()
+            .filter(|r| r.string == "true")
+            .map(|r| r.sk.as_str())
+            .collect();
+        assert_eq!(updated, vec!["a#1", "a#3"]);
+    }
+}
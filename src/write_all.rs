@@ -0,0 +1,158 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    begin_transaction, transact_condition_check, transact_create, transact_update, Client,
+    ConditionCheckInfo, DynarustError, KeyOf, Resource,
+};
+
+/// Maximum number of operations of a dynamo transaction.
+const MAX_TRANSACTION_ITEMS: usize = 100;
+
+/// A write of `Client::write_all_if`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuardedWrite<T> {
+    /// creates the resource, failing if it already exists.
+    Create(T),
+    /// updates the resource with a patch, as `Client::update` does.
+    Update(T, Value),
+}
+
+impl Client {
+    /// Writes many resources in transactions that also check the same condition on another
+    /// item, like a "not suspended" flag on the tenant that owns them, so that none of the
+    /// writes goes through if the guard does not hold. Every transaction carries the guard as
+    /// a standalone condition check along with up to 99 writes, so more writes are split in
+    /// several transactions that are executed one after the other. If one of them fails, its
+    /// error is returned and the following ones are not executed, but the previous ones stay
+    /// committed. Returns the resources as they were written.
+    ///
+    /// # arguments
+    ///
+    /// * `writes` - The creates and updates to execute.
+    /// * `guard` - The key of the item on which the condition is checked, which cannot be
+    ///   written in the same call.
+    /// * `check` - The condition that the guard item must match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// async {
+    ///     let writes = orders.into_iter().map(GuardedWrite::Create).collect();
+    ///     client
+    ///         .write_all_if(
+    ///             writes,
+    ///             Tenant::key("tenants", tenant_id),
+    ///             dynarust::condition_check_string("status", DynamoOperator::NEq, "suspended"),
+    ///         )
+    ///         .await?;
+    /// }
+    /// ```
+    pub async fn write_all_if<T, G>(
+        &self,
+        writes: Vec<GuardedWrite<T>>,
+        guard: KeyOf<G>,
+        check: ConditionCheckInfo,
+    ) -> Result<Vec<T>, DynarustError>
+    where
+        T: Resource + Serialize + DeserializeOwned,
+        G: Resource,
+    {
+        let mut written = vec![];
+        let mut writes = writes.into_iter().peekable();
+        while writes.peek().is_some() {
+            let mut context = begin_transaction();
+            transact_condition_check::<G>(guard.pk_sk(), check.clone(), &mut context)?;
+            context.label("guard");
+            let mut resources = vec![];
+            for write in writes.by_ref().take(MAX_TRANSACTION_ITEMS - 1) {
+                resources.push(match write {
+                    GuardedWrite::Create(resource) => {
+                        transact_create(&resource, &mut context)?;
+                        resource
+                    }
+                    GuardedWrite::Update(resource, patch) => {
+                        transact_update(&resource, patch, &mut context)?
+                    }
+                });
+            }
+            self.execute_transaction(context).await?;
+            written.extend(resources);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::client::tests::TestResource;
+    use crate::{
+        condition_check_number, Client, DynamoOperator, DynarustError, GuardedWrite, Resource,
+    };
+
+    #[tokio::test]
+    async fn writes_all_if_guard_holds() {
+        let client = Client::local().await;
+        client.create_table::<TestResource>(None).await.unwrap();
+        let pk = "writes_all_if_guard_holds";
+        let tenant = TestResource {
+            pk: pk.to_string(),
+            sk: "tenant".to_string(),
+            int: 0,
+            ..Default::default()
+        };
+        client.force_create(&tenant).await.unwrap();
+        let existing = TestResource {
+            pk: pk.to_string(),
+            sk: "existing".to_string(),
+            ..Default::default()
+        };
+        client.force_create(&existing).await.unwrap();
+
+        let mut writes: Vec<_> = (0..120)
+            .map(|i| {
+                GuardedWrite::Create(TestResource {
+                    pk: pk.to_string(),
+                    sk: format!("item{i:03}"),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        writes.push(GuardedWrite::Update(
+            existing,
+            json!({ "string": "updated" }),
+        ));
+        let not_suspended = || condition_check_number("int", DynamoOperator::Eq, 0);
+        let written = client
+            .write_all_if(writes, TestResource::key(pk, "tenant"), not_suspended())
+            .await
+            .unwrap();
+        assert_eq!(written.len(), 121);
+        assert_eq!(written[120].string, "updated");
+        assert_eq!(
+            client.count_by_pk::<TestResource>(pk.into()).await.unwrap(),
+            122
+        );
+
+        client.update(&tenant, json!({ "int": 1 })).await.unwrap();
+        let err = client
+            .write_all_if(
+                vec![GuardedWrite::Create(TestResource {
+                    pk: pk.to_string(),
+                    sk: "rejected".to_string(),
+                    ..Default::default()
+                })],
+                TestResource::key(pk, "tenant"),
+                not_suspended(),
+            )
+            .await
+            .unwrap_err();
+        let DynarustError::TransactionCanceledError(operations) = err else {
+            panic!("unexpected error {err}");
+        };
+        assert_eq!(operations[0].label.as_deref(), Some("guard"));
+    }
+}